@@ -0,0 +1,79 @@
+//! Benchmark batch GM trade detection.
+//!
+//! Compares checking a synthetic batch one transaction at a time against
+//! `check_gm_trades_batch`. Run with `--features rayon` to measure the parallel path:
+//!
+//! ```bash
+//! cargo bench --bench batch
+//! cargo bench --bench batch --features rayon
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gm_solana_simulator::{
+    check_gm_trade, check_gm_trades_batch, instruction_discriminator,
+    jupiter_order_engine_program_id, token_2022_program_id, usdc_mint,
+};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+use std::str::FromStr;
+
+const BATCH_SIZE: usize = 1_000;
+
+/// Build a BUY fill (USDC -> AAPLon) with the real 11-account Jupiter layout
+fn build_fill_transaction(output_amount: u64) -> Transaction {
+    let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+    let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+    let taker = Pubkey::new_unique();
+
+    let mut data = instruction_discriminator("fill").to_vec();
+    data.extend_from_slice(&200_000_000u64.to_le_bytes());
+    data.extend_from_slice(&output_amount.to_le_bytes());
+    data.extend_from_slice(&1704067200i64.to_le_bytes());
+
+    let fill_ix = Instruction {
+        program_id: jupiter_order_engine_program_id(),
+        accounts: vec![
+            AccountMeta::new(taker, true),
+            AccountMeta::new(solver, true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(usdc_mint(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(aapl, false),
+            AccountMeta::new_readonly(token_2022_program_id(), false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+        data,
+    };
+
+    Transaction::new_unsigned(Message::new(&[fill_ix], Some(&taker)))
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let txs: Vec<Transaction> = (0..BATCH_SIZE as u64)
+        .map(|i| build_fill_transaction(1_000_000_000 + i))
+        .collect();
+
+    let mut group = c.benchmark_group("check_gm_trades");
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            black_box(&txs)
+                .iter()
+                .map(check_gm_trade)
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("batch", |b| {
+        b.iter(|| check_gm_trades_batch(black_box(&txs)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_batch);
+criterion_main!(benches);
@@ -0,0 +1,110 @@
+//! Benchmark the single-transaction detection hot paths.
+//!
+//! Measures `check_gm_trade_message` on a realistic wallet transaction (compute budget,
+//! ATA create and fill), the fill parser on its own, `is_gm_token` across the full
+//! token table and `instruction_discriminator`:
+//!
+//! ```bash
+//! cargo bench --bench detection
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gm_solana_simulator::{
+    check_gm_trade_message, instruction_discriminator, is_gm_token,
+    jupiter_order_engine_program_id, parser::parse_fill_for_gm_trade, token_2022_program_id,
+    usdc_mint, GM_TOKENS,
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use std::str::FromStr;
+
+/// Build a BUY (USDC -> AAPLon) the way wallets send it: compute budget instructions,
+/// an idempotent create of the taker's GM ATA, then the fill
+fn build_fill_message() -> Message {
+    let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+    let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+    let taker = Pubkey::new_unique();
+
+    let mut data = instruction_discriminator("fill").to_vec();
+    data.extend_from_slice(&200_000_000u64.to_le_bytes());
+    data.extend_from_slice(&1_500_000_000u64.to_le_bytes());
+    data.extend_from_slice(&1704067200i64.to_le_bytes());
+
+    let fill_ix = Instruction {
+        program_id: jupiter_order_engine_program_id(),
+        accounts: vec![
+            AccountMeta::new(taker, true),
+            AccountMeta::new(solver, true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(usdc_mint(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(aapl, false),
+            AccountMeta::new_readonly(token_2022_program_id(), false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+        data,
+    };
+
+    let instructions = [
+        ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+        ComputeBudgetInstruction::set_compute_unit_price(1_000),
+        create_associated_token_account_idempotent(&taker, &taker, &aapl, &token_2022_program_id()),
+        fill_ix,
+    ];
+    Message::new(&instructions, Some(&taker))
+}
+
+fn bench_detection(c: &mut Criterion) {
+    let message = build_fill_message();
+    let fill = message.instructions.last().unwrap();
+
+    c.bench_function("check_gm_trade_message", |b| {
+        b.iter(|| check_gm_trade_message(black_box(&message)))
+    });
+    c.bench_function("parse_fill_for_gm_trade", |b| {
+        b.iter(|| parse_fill_for_gm_trade(black_box(fill), black_box(&message.account_keys)))
+    });
+}
+
+fn bench_is_gm_token(c: &mut Criterion) {
+    // Skip table entries that aren't valid 32-byte addresses (they can never match a key)
+    let mints: Vec<Pubkey> = GM_TOKENS
+        .iter()
+        .filter_map(|(_, mint)| Pubkey::from_str(mint).ok())
+        .collect();
+    let not_gm = usdc_mint();
+
+    let mut group = c.benchmark_group("is_gm_token");
+    group.bench_function("all_gm_tokens", |b| {
+        b.iter(|| {
+            mints
+                .iter()
+                .filter(|mint| is_gm_token(black_box(mint)))
+                .count()
+        })
+    });
+    group.bench_function("miss", |b| b.iter(|| is_gm_token(black_box(&not_gm))));
+    group.finish();
+}
+
+fn bench_instruction_discriminator(c: &mut Criterion) {
+    c.bench_function("instruction_discriminator", |b| {
+        b.iter(|| instruction_discriminator(black_box("fill")))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_detection,
+    bench_is_gm_token,
+    bench_instruction_discriminator
+);
+criterion_main!(benches);
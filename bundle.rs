@@ -0,0 +1,252 @@
+//! Checks on a `[mock_mint, fill]` bundle before it is sent for simulation.
+//!
+//! A bundle whose mock mint targets the wrong mint or amount, or whose transactions
+//! use different blockhashes, still simulates but produces misleading results.
+
+use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+
+use crate::{
+    constants::ondo_gm_program_id,
+    mint_instruction::MINT_GM_DISCRIMINATOR,
+    simulator::check_gm_trade,
+    types::{GmSimulatorError, GmTradeInfo},
+};
+
+/// Index of the mint account in the `mint_gm` instruction
+const MINT_GM_MINT_INDEX: usize = 6;
+
+/// Verify that a mock mint and fill transaction form a consistent bundle.
+///
+/// Checks that:
+/// 1. The mock mint contains a `mint_gm` instruction for `trade_info.gm_token_mint`
+///    minting exactly `trade_info.gm_token_amount`
+/// 2. The fill is a GM trade for the same mint, amount, maker and taker
+/// 3. Both transactions use the same recent blockhash
+///
+/// # Arguments
+///
+/// * `mock_mint` - The mock mint transaction (bundle index 0)
+/// * `fill` - The Jupiter fill transaction (bundle index 1)
+/// * `trade_info` - The GM trade info the mock mint was built from
+///
+/// # Returns
+///
+/// * `Ok(())` if the bundle is consistent
+/// * `Err(GmSimulatorError::InvalidBundle)` describing the first mismatch found
+pub fn validate_bundle(
+    mock_mint: &Transaction,
+    fill: &Transaction,
+    trade_info: &GmTradeInfo,
+) -> Result<(), GmSimulatorError> {
+    let invalid = |msg: String| Err(GmSimulatorError::InvalidBundle(msg));
+
+    // Check 1: The mock mint mints the trade's GM token and amount
+    let (mint, amount) = find_mint_gm(mock_mint).ok_or_else(|| {
+        GmSimulatorError::InvalidBundle("Mock mint has no mint_gm instruction".to_string())
+    })?;
+
+    if mint != trade_info.gm_token_mint {
+        return invalid(format!(
+            "Mock mint targets {}, expected {}",
+            mint, trade_info.gm_token_mint
+        ));
+    }
+    if amount != trade_info.gm_token_amount.0 {
+        return invalid(format!(
+            "Mock mint amount {} does not match trade amount {}",
+            amount, trade_info.gm_token_amount
+        ));
+    }
+
+    // Check 2: The fill is the trade the mock mint was built for
+    let Some(fill_info) = check_gm_trade(fill)?.trade_info else {
+        return invalid("Fill transaction is not a GM trade".to_string());
+    };
+
+    if fill_info.gm_token_mint != trade_info.gm_token_mint {
+        return invalid(format!(
+            "Fill outputs {}, expected {}",
+            fill_info.gm_token_mint, trade_info.gm_token_mint
+        ));
+    }
+    if fill_info.gm_token_amount != trade_info.gm_token_amount {
+        return invalid(format!(
+            "Fill amount {} does not match trade amount {}",
+            fill_info.gm_token_amount, trade_info.gm_token_amount
+        ));
+    }
+    if fill_info.maker != trade_info.maker || fill_info.taker != trade_info.taker {
+        return invalid("Fill maker/taker do not match trade info".to_string());
+    }
+
+    // Check 3: Both transactions were built against the same blockhash
+    if mock_mint.message.recent_blockhash != fill.message.recent_blockhash {
+        return invalid(format!(
+            "Blockhash mismatch: mock mint uses {}, fill uses {}",
+            mock_mint.message.recent_blockhash, fill.message.recent_blockhash
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check a fill's output amount against the amount a stored quote promised.
+///
+/// RFQ fills may settle less than quoted. Passing `None` skips the check, so callers
+/// without a stored quote can call this unconditionally.
+///
+/// # Arguments
+///
+/// * `trade_info` - The GM trade info parsed from the fill
+/// * `expected_output` - The quoted output amount (GM base units), if known
+///
+/// # Returns
+///
+/// * `Ok(())` if there is no expectation or the fill settles at least that much
+/// * `Err(GmSimulatorError::PartialFill)` if the fill settles less than quoted
+pub fn validate_fill_output(
+    trade_info: &GmTradeInfo,
+    expected_output: Option<u64>,
+) -> Result<(), GmSimulatorError> {
+    match expected_output {
+        Some(quoted) if trade_info.gm_token_amount.0 < quoted => {
+            Err(GmSimulatorError::PartialFill {
+                quoted,
+                filled: trade_info.gm_token_amount.0,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Find the `mint_gm` instruction in a transaction and return its mint and amount
+fn find_mint_gm(tx: &Transaction) -> Option<(Pubkey, u64)> {
+    let account_keys = &tx.message.account_keys;
+    let program_id = ondo_gm_program_id();
+
+    tx.message.instructions.iter().find_map(|ix| {
+        if account_keys.get(ix.program_id_index as usize) != Some(&program_id)
+            || ix.data.len() < 16
+            || ix.data[..8] != MINT_GM_DISCRIMINATOR
+        {
+            return None;
+        }
+
+        let mint_index = *ix.accounts.get(MINT_GM_MINT_INDEX)?;
+        let mint = *account_keys.get(mint_index as usize)?;
+        let amount = u64::from_le_bytes(ix.data[8..16].try_into().ok()?);
+        Some((mint, amount))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        build_mock_mint_transaction,
+        test_utils::{fill_transaction, sample_trade_info},
+    };
+    use solana_sdk::hash::Hash;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_validate_bundle() {
+        let trade_info = sample_trade_info();
+        let blockhash = Hash::new_unique();
+        let mock_mint = build_mock_mint_transaction(&trade_info, blockhash);
+        let fill = fill_transaction(&trade_info, 200_000_000, blockhash);
+
+        assert_eq!(validate_bundle(&mock_mint, &fill, &trade_info), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_bundle_mint_mismatch() {
+        let trade_info = sample_trade_info();
+        let blockhash = Hash::new_unique();
+        let other_mint = Pubkey::from_str("MFerpBVGKZh2jXN7cbJdXRXQTp6j6pbSnSZrfWrondo").unwrap();
+        let mock_mint = build_mock_mint_transaction(
+            &GmTradeInfo {
+                gm_token_mint: other_mint,
+                ..trade_info.clone()
+            },
+            blockhash,
+        );
+        let fill = fill_transaction(&trade_info, 200_000_000, blockhash);
+
+        let err = validate_bundle(&mock_mint, &fill, &trade_info).unwrap_err();
+        assert!(matches!(err, GmSimulatorError::InvalidBundle(msg) if msg.contains("targets")));
+    }
+
+    #[test]
+    fn test_validate_bundle_amount_mismatch() {
+        let trade_info = sample_trade_info();
+        let blockhash = Hash::new_unique();
+        let mock_mint = build_mock_mint_transaction(
+            &GmTradeInfo {
+                gm_token_amount: 1.into(),
+                ..trade_info.clone()
+            },
+            blockhash,
+        );
+        let fill = fill_transaction(&trade_info, 200_000_000, blockhash);
+
+        let err = validate_bundle(&mock_mint, &fill, &trade_info).unwrap_err();
+        assert!(matches!(err, GmSimulatorError::InvalidBundle(msg) if msg.contains("amount")));
+
+        // A fill for a different amount than the trade info is also rejected
+        let mock_mint = build_mock_mint_transaction(&trade_info, blockhash);
+        let fill = fill_transaction(
+            &GmTradeInfo {
+                gm_token_amount: 1.into(),
+                ..trade_info.clone()
+            },
+            200_000_000,
+            blockhash,
+        );
+
+        let err = validate_bundle(&mock_mint, &fill, &trade_info).unwrap_err();
+        assert!(matches!(err, GmSimulatorError::InvalidBundle(msg) if msg.contains("Fill amount")));
+    }
+
+    #[test]
+    fn test_validate_bundle_blockhash_mismatch() {
+        let trade_info = sample_trade_info();
+        let mock_mint = build_mock_mint_transaction(&trade_info, Hash::new_unique());
+        let fill = fill_transaction(&trade_info, 200_000_000, Hash::new_unique());
+
+        let err = validate_bundle(&mock_mint, &fill, &trade_info).unwrap_err();
+        assert!(matches!(err, GmSimulatorError::InvalidBundle(msg) if msg.contains("Blockhash")));
+    }
+
+    #[test]
+    fn test_validate_bundle_missing_mint_instruction() {
+        let trade_info = sample_trade_info();
+        let blockhash = Hash::new_unique();
+        let fill = fill_transaction(&trade_info, 200_000_000, blockhash);
+
+        let err = validate_bundle(&fill, &fill, &trade_info).unwrap_err();
+        assert!(matches!(err, GmSimulatorError::InvalidBundle(msg) if msg.contains("mint_gm")));
+    }
+
+    #[test]
+    fn test_validate_fill_output() {
+        let trade_info = sample_trade_info();
+
+        assert_eq!(validate_fill_output(&trade_info, None), Ok(()));
+        assert_eq!(
+            validate_fill_output(&trade_info, Some(1_500_000_000)),
+            Ok(())
+        );
+        assert_eq!(
+            validate_fill_output(&trade_info, Some(1_000_000_000)),
+            Ok(())
+        );
+        assert_eq!(
+            validate_fill_output(&trade_info, Some(2_000_000_000)),
+            Err(GmSimulatorError::PartialFill {
+                quoted: 2_000_000_000,
+                filled: 1_500_000_000,
+            })
+        );
+    }
+}
@@ -2,8 +2,13 @@
 //!
 //! Contains program IDs, solver addresses, admin accounts, and GM token list.
 
+#[cfg(not(feature = "no_std"))]
 use solana_sdk::pubkey::Pubkey;
-use std::str::FromStr;
+#[cfg(not(feature = "no_std"))]
+use std::{str::FromStr, sync::OnceLock};
+
+#[cfg(not(feature = "no_std"))]
+use crate::types::{GmConfig, GmSimulatorError, TokenListDiff, TokenMintChange};
 
 /// Ondo GM Program ID (mainnet production)
 pub const ONDO_GM_PROGRAM_ID: &str = "XzTT4XB8m7sLD2xi6snefSasaswsKCxx5Tifjondogm";
@@ -18,12 +23,65 @@ pub const ADMIN_MINTER: &str = "4pfyfezvwjBrsHtJpXPPKsqH9cphwSDDb7s63KzkVEqF";
 /// USDC Mint (mainnet)
 pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
 
+/// USDT Mint (mainnet)
+pub const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+/// Decimals of the USDON stablecoin (the same as USDC)
+pub const USDON_DECIMALS: u8 = 6;
+
+/// Decimals of every accepted stablecoin (USDC, USDT and USDON)
+pub const STABLE_DECIMALS: u8 = 6;
+
 /// SPL Token Program ID
 pub const SPL_TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
 
 /// Token-2022 Program ID
 pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
 
+/// Schema version of `GmTradeInfo::to_jsonl_line` records
+///
+/// Bumped only when a field is removed, renamed or changes meaning; new fields may be
+/// added within a version.
+pub const TRADE_EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Counter incremented for each GM BUY detected (`metrics` feature)
+pub const METRIC_DETECTED_BUY: &str = "gm_simulator_detected_buy";
+
+/// Counter incremented for each authorized fill selling a GM token (`metrics` feature)
+pub const METRIC_DETECTED_SELL: &str = "gm_simulator_detected_sell";
+
+/// Counter incremented for each transaction that is not a GM trade (`metrics` feature)
+pub const METRIC_DETECTED_NON_GM: &str = "gm_simulator_detected_non_gm";
+
+/// Counter incremented for each fill from an unauthorized maker (`metrics` feature)
+pub const METRIC_DETECTED_UNAUTHORIZED: &str = "gm_simulator_detected_unauthorized";
+
+/// Histogram of `check_gm_trade*` latency in seconds, see `render_metrics_text`
+pub const METRIC_DETECTION_LATENCY: &str = "gm_simulator_detection_latency_seconds";
+
+/// Upper bounds (seconds) of the `METRIC_DETECTION_LATENCY` buckets
+///
+/// Detection is pure and typically takes a few microseconds, so the buckets are fine
+/// grained up to 10ms.
+pub const DETECTION_LATENCY_BUCKETS: [f64; 8] = [
+    0.000_001, 0.000_005, 0.000_01, 0.000_05, 0.000_1, 0.000_5, 0.001, 0.01,
+];
+
+/// Compute units budgeted per idempotent ATA create in a mock mint
+///
+/// A Token-2022 ATA create (with `ImmutableOwner`) measures around 12k CU; this leaves
+/// headroom for mints with more extensions. See `recommended_compute_limit`.
+pub const MOCK_MINT_ATA_CREATE_COMPUTE_UNITS: u32 = 15_000;
+
+/// Compute units budgeted for a mock mint's `mint_gm` into a Token-2022 account
+pub const MOCK_MINT_GM_COMPUTE_UNITS: u32 = 30_000;
+
+/// Default `GmConfig::max_instructions`
+///
+/// Real fills carry a handful of instructions (compute budget, ATA creates, the fill),
+/// while a 1232-byte transaction packet can hold a few hundred empty ones.
+pub const DEFAULT_MAX_INSTRUCTIONS: usize = 64;
+
 /// Authorized Ondo GM Solver addresses
 pub const AUTHORIZED_SOLVERS: [&str; 4] = [
     "AMJ81TnD4EWftmVPxppiEPsSFbmfYAvvLkUaNDXuR7JH",
@@ -297,49 +355,137 @@ pub const GM_TOKENS: [(&str, &str); 260] = [
     ("WMon", "FPvKvWzSzDZqgYmSZUetrkpUXSwo2VtpR4BynVYondo"),
 ];
 
+#[cfg(not(feature = "no_std"))]
+static GLOBAL_CONFIG: OnceLock<GmConfig> = OnceLock::new();
+
+/// Install the config used by every function in this crate
+///
+/// Call once at startup, e.g. to target devnet without threading a `GmConfig` through
+/// every call. The config can only be set once, and only before it is first read:
+/// any lookup (detection, building a mock mint, ...) fixes it to `GmConfig::mainnet()`.
+///
+/// # Returns
+///
+/// * `Err(GmSimulatorError::ConfigAlreadySet)` if a config was already set or read
+#[cfg(not(feature = "no_std"))]
+pub fn set_global_config(config: GmConfig) -> Result<(), GmSimulatorError> {
+    GLOBAL_CONFIG
+        .set(config)
+        .map_err(|_| GmSimulatorError::ConfigAlreadySet)
+}
+
+/// The config set by `set_global_config`, or mainnet defaults if unset
+#[cfg(not(feature = "no_std"))]
+pub fn global_config() -> &'static GmConfig {
+    GLOBAL_CONFIG.get_or_init(GmConfig::mainnet)
+}
+
 /// Get the Ondo GM program ID
+#[cfg(not(feature = "no_std"))]
 pub fn ondo_gm_program_id() -> Pubkey {
-    Pubkey::from_str(ONDO_GM_PROGRAM_ID).expect("Invalid Ondo GM program ID")
+    global_config().ondo_gm_program_id
 }
 
 /// Get the Jupiter Order Engine program ID
+#[cfg(not(feature = "no_std"))]
 pub fn jupiter_order_engine_program_id() -> Pubkey {
-    Pubkey::from_str(JUPITER_ORDER_ENGINE_PROGRAM_ID).expect("Invalid Jupiter program ID")
+    global_config().jupiter_order_engine_program_id
 }
 
 /// Get the admin minter account (real on-chain authority)
+#[cfg(not(feature = "no_std"))]
 pub fn admin_minter() -> Pubkey {
-    Pubkey::from_str(ADMIN_MINTER).expect("Invalid admin minter")
+    global_config().admin_minter
 }
 
 /// Get the USDC mint
+#[cfg(not(feature = "no_std"))]
 pub fn usdc_mint() -> Pubkey {
-    Pubkey::from_str(USDC_MINT).expect("Invalid USDC mint")
+    global_config().usdc_mint
+}
+
+/// Get the USDON mint, if the config sets one
+///
+/// No mainnet address is built in; set `GmConfig::usdon_mint` to enable USDON.
+#[cfg(not(feature = "no_std"))]
+pub fn usdon_mint() -> Option<Pubkey> {
+    global_config().usdon_mint
+}
+
+/// Get the USDON decimals, for displaying USDON balance changes
+#[cfg(not(feature = "no_std"))]
+pub fn usdon_decimals() -> u8 {
+    USDON_DECIMALS
+}
+
+/// Get the USDT mint, if the config accepts USDT
+#[cfg(not(feature = "no_std"))]
+pub fn usdt_mint() -> Option<Pubkey> {
+    global_config().usdt_mint
+}
+
+/// The stablecoins a GM token trades against: USDC, then USDT and USDON if set
+#[cfg(not(feature = "no_std"))]
+pub fn stable_mints() -> Vec<Pubkey> {
+    std::iter::once(usdc_mint())
+        .chain(usdt_mint())
+        .chain(usdon_mint())
+        .collect()
+}
+
+/// Get the symbol of an accepted stablecoin ("USDC", "USDT" or "USDON")
+#[cfg(not(feature = "no_std"))]
+pub fn stable_mint_symbol(mint: &Pubkey) -> Option<&'static str> {
+    if *mint == usdc_mint() {
+        Some("USDC")
+    } else if usdt_mint() == Some(*mint) {
+        Some("USDT")
+    } else if usdon_mint() == Some(*mint) {
+        Some("USDON")
+    } else {
+        None
+    }
+}
+
+/// Check if a mint is a stablecoin a GM token trades against (see `stable_mints`)
+#[cfg(not(feature = "no_std"))]
+pub fn is_stable_mint(mint: &Pubkey) -> bool {
+    stable_mint_symbol(mint).is_some()
 }
 
 /// Get the SPL Token program ID
+#[cfg(not(feature = "no_std"))]
 pub fn spl_token_program_id() -> Pubkey {
     Pubkey::from_str(SPL_TOKEN_PROGRAM_ID).expect("Invalid SPL Token program ID")
 }
 
 /// Get the Token-2022 program ID
+#[cfg(not(feature = "no_std"))]
 pub fn token_2022_program_id() -> Pubkey {
     Pubkey::from_str(TOKEN_2022_PROGRAM_ID).expect("Invalid Token-2022 program ID")
 }
 
 /// Check if a pubkey is an authorized Ondo GM solver
+#[cfg(not(feature = "no_std"))]
 pub fn is_authorized_solver(pubkey: &Pubkey) -> bool {
-    let pubkey_str = pubkey.to_string();
-    AUTHORIZED_SOLVERS.contains(&pubkey_str.as_str())
+    global_config().authorized_solvers.contains(pubkey)
+}
+
+/// Check if a pubkey is the admin minter that signs simulation mints
+#[cfg(not(feature = "no_std"))]
+pub fn is_admin_minter(pubkey: &Pubkey) -> bool {
+    *pubkey == global_config().admin_minter
 }
 
 /// Check if a pubkey is an Ondo GM token mint
+#[cfg(not(feature = "no_std"))]
 pub fn is_gm_token(pubkey: &Pubkey) -> bool {
     let pubkey_str = pubkey.to_string();
     GM_TOKENS.iter().any(|(_, addr)| *addr == pubkey_str)
 }
 
 /// Get the symbol for a GM token mint address
+#[cfg(not(feature = "no_std"))]
 pub fn get_gm_token_symbol(pubkey: &Pubkey) -> Option<&'static str> {
     let pubkey_str = pubkey.to_string();
     GM_TOKENS
@@ -348,6 +494,77 @@ pub fn get_gm_token_symbol(pubkey: &Pubkey) -> Option<&'static str> {
         .map(|(symbol, _)| *symbol)
 }
 
+/// GM token symbols that could be mistaken for a longer symbol
+///
+/// A symbol is ambiguous when its ticker (the symbol without the `on` suffix) is a
+/// proper prefix of another symbol's ticker, e.g. `Con` (C) next to `COINon` (COIN).
+/// A prefix match on such a ticker finds the longer token too, so lookups by symbol
+/// should always match exactly. Returned in `GM_TOKENS` order.
+#[cfg(not(feature = "no_std"))]
+pub fn ambiguous_symbols() -> Vec<&'static str> {
+    let ticker = |symbol: &'static str| symbol.strip_suffix("on").unwrap_or(symbol);
+
+    GM_TOKENS
+        .iter()
+        .map(|(symbol, _)| *symbol)
+        .filter(|symbol| {
+            let short = ticker(symbol);
+            GM_TOKENS.iter().any(|(other, _)| {
+                let long = ticker(other);
+                long.len() > short.len() && long.starts_with(short)
+            })
+        })
+        .collect()
+}
+
+/// Get the token program a mint is assumed to belong to, without an RPC lookup
+///
+/// GM tokens are Token-2022; everything else (e.g. USDC) is assumed to be SPL Token.
+/// Use `rpc::token_program_for_mint` to read the mint's actual owner.
+#[cfg(not(feature = "no_std"))]
+pub fn known_token_program_for_mint(mint: &Pubkey) -> Pubkey {
+    if is_gm_token(mint) {
+        token_2022_program_id()
+    } else {
+        spl_token_program_id()
+    }
+}
+
+/// Compare an external GM token list against the built-in `GM_TOKENS`
+///
+/// Entries are `(symbol, mint)` pairs, the same shape as `GM_TOKENS`, and are matched
+/// by symbol. `added` and `changed` follow the external list's order, `removed` follows
+/// `GM_TOKENS`.
+#[cfg(not(feature = "no_std"))]
+pub fn diff_token_list(external: &[(String, String)]) -> TokenListDiff {
+    let builtin_mint = |symbol: &str| {
+        GM_TOKENS
+            .iter()
+            .find(|(builtin, _)| *builtin == symbol)
+            .map(|(_, mint)| *mint)
+    };
+
+    let mut diff = TokenListDiff::default();
+    for (symbol, mint) in external {
+        match builtin_mint(symbol) {
+            None => diff.added.push((symbol.clone(), mint.clone())),
+            Some(builtin) if builtin != mint => diff.changed.push(TokenMintChange {
+                symbol: symbol.clone(),
+                builtin_mint: builtin.to_string(),
+                external_mint: mint.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.removed = GM_TOKENS
+        .iter()
+        .filter(|(symbol, _)| !external.iter().any(|(external, _)| external == symbol))
+        .map(|(symbol, mint)| (symbol.to_string(), mint.to_string()))
+        .collect();
+
+    diff
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -361,6 +578,79 @@ mod tests {
         assert!(!is_authorized_solver(&random));
     }
 
+    #[test]
+    fn test_ambiguous_symbols() {
+        let ambiguous = ambiguous_symbols();
+
+        for symbol in ["Con", "Fon", "Ton", "Von", "SOon", "LIon"] {
+            assert!(
+                ambiguous.contains(&symbol),
+                "{} should be ambiguous",
+                symbol
+            );
+        }
+        // COINon is the longer symbol, not the ambiguous one
+        assert!(!ambiguous.contains(&"COINon"));
+        assert!(!ambiguous.contains(&"AAPLon"));
+
+        // Each one shadows some other ticker
+        for symbol in &ambiguous {
+            let short = symbol.strip_suffix("on").unwrap();
+            assert!(GM_TOKENS.iter().any(|(other, _)| {
+                other != symbol && other.strip_suffix("on").unwrap().starts_with(short)
+            }));
+        }
+    }
+
+    #[test]
+    fn test_gm_token_lookups_consistent() {
+        let mut malformed = vec![];
+        for (symbol, mint) in GM_TOKENS {
+            let Ok(mint) = Pubkey::from_str(mint) else {
+                malformed.push(symbol);
+                continue;
+            };
+            assert!(is_gm_token(&mint), "{} is not recognized", symbol);
+            assert_eq!(get_gm_token_symbol(&mint), Some(symbol));
+        }
+        // ONDSon's listed mint decodes to 31 bytes, so no fill can match it; this
+        // pins it until the list has the correct address
+        assert_eq!(malformed, vec!["ONDSon"]);
+    }
+
+    #[test]
+    fn test_usdon_defaults() {
+        // Mainnet has no USDON mint configured, so only USDC is a stable leg
+        assert_eq!(usdon_mint(), None);
+        assert_eq!(usdon_decimals(), 6);
+        assert!(is_stable_mint(&usdc_mint()));
+        assert!(!is_stable_mint(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_stable_mints() {
+        let usdt = Pubkey::from_str(USDT_MINT).unwrap();
+        assert_eq!(usdt_mint(), Some(usdt));
+        assert_eq!(stable_mints(), vec![usdc_mint(), usdt]);
+        assert_eq!(stable_mint_symbol(&usdc_mint()), Some("USDC"));
+        assert_eq!(stable_mint_symbol(&usdt), Some("USDT"));
+        assert!(is_stable_mint(&usdt));
+        assert_eq!(
+            stable_mint_symbol(&Pubkey::from_str(GM_TOKENS[0].1).unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_admin_minter() {
+        assert!(is_admin_minter(&Pubkey::from_str(ADMIN_MINTER).unwrap()));
+        assert!(!is_admin_minter(&Pubkey::new_unique()));
+
+        // Solvers are not the admin minter
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        assert!(!is_admin_minter(&solver));
+    }
+
     #[test]
     fn test_is_gm_token() {
         let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
@@ -370,4 +660,49 @@ mod tests {
         let random = Pubkey::new_unique();
         assert!(!is_gm_token(&random));
     }
+
+    #[test]
+    fn test_known_token_program_for_mint() {
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        assert_eq!(known_token_program_for_mint(&aapl), token_2022_program_id());
+        assert_eq!(
+            known_token_program_for_mint(&usdc_mint()),
+            spl_token_program_id()
+        );
+    }
+
+    #[test]
+    fn test_diff_token_list() {
+        let builtin = |tokens: &[(&str, &str)]| -> Vec<(String, String)> {
+            tokens
+                .iter()
+                .map(|(symbol, mint)| (symbol.to_string(), mint.to_string()))
+                .collect()
+        };
+
+        // The built-in list diffs clean against itself
+        assert!(diff_token_list(&builtin(&GM_TOKENS)).is_empty());
+
+        // Drop the first token, change the second's mint and add a new one
+        let mut external = builtin(&GM_TOKENS[1..]);
+        let changed_mint = Pubkey::new_unique().to_string();
+        external[0].1 = changed_mint.clone();
+        external.push(("NEWon".to_string(), "NewMint1111".to_string()));
+
+        let diff = diff_token_list(&external);
+
+        assert_eq!(
+            diff.added,
+            vec![("NEWon".to_string(), "NewMint1111".to_string())]
+        );
+        assert_eq!(diff.removed, builtin(&GM_TOKENS[..1]));
+        assert_eq!(
+            diff.changed,
+            vec![TokenMintChange {
+                symbol: GM_TOKENS[1].0.to_string(),
+                builtin_mint: GM_TOKENS[1].1.to_string(),
+                external_mint: changed_mint,
+            }]
+        );
+    }
 }
@@ -0,0 +1,408 @@
+//! `no_std`-friendly core of Jupiter fill detection.
+//!
+//! Holds the fill layouts, account indices and allocation-free decoding that
+//! [`crate::parser`] re-exports and builds on, so the two can't drift apart. Keys are
+//! anything comparable and viewable as bytes (a raw [`Key`] or a `Pubkey`), symbols are
+//! borrowed `&'static str`s from [`crate::constants::GM_TOKENS`], and nothing
+//! allocates. The RPC and mock-mint building code remains std-only.
+//!
+//! With the `no_std` feature the crate is `#![no_std]` and builds only this module and
+//! the constant tables.
+
+use ::core::str;
+
+use thiserror::Error;
+
+use crate::constants::{
+    AUTHORIZED_SOLVERS, GM_TOKENS, SPL_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID,
+};
+
+/// A raw 32-byte account address
+pub type Key = [u8; 32];
+
+const SPL_TOKEN_PROGRAM: Key =
+    bs58::decode(SPL_TOKEN_PROGRAM_ID.as_bytes()).into_array_const_unwrap();
+const TOKEN_2022_PROGRAM: Key =
+    bs58::decode(TOKEN_2022_PROGRAM_ID.as_bytes()).into_array_const_unwrap();
+
+/// A Jupiter Order Engine fill instruction variant and its argument layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillLayout {
+    /// Anchor instruction name
+    pub name: &'static str,
+    /// First 8 bytes of the instruction data
+    pub discriminator: [u8; 8],
+    /// Byte offset of the u64 `input_amount`
+    pub input_amount_offset: usize,
+    /// Byte offset of the u64 `output_amount`
+    pub output_amount_offset: usize,
+    /// Byte offset of the i64 `expire_at`
+    pub expire_at_offset: usize,
+}
+
+impl FillLayout {
+    /// Minimum instruction data length holding every decoded argument
+    pub fn min_data_len(&self) -> usize {
+        self.input_amount_offset
+            .max(self.output_amount_offset)
+            .max(self.expire_at_offset)
+            + 8
+    }
+}
+
+/// `fill`: discriminator (8) + input_amount (8) + output_amount (8) + expire_at (8)
+pub const FILL: FillLayout = FillLayout {
+    name: "fill",
+    discriminator: [0xa8, 0x60, 0xb7, 0xa3, 0x5c, 0x0a, 0x28, 0xa0],
+    input_amount_offset: 8,
+    output_amount_offset: 16,
+    expire_at_offset: 24,
+};
+
+/// `fill_v2`: `sha256("global:fill_v2")[..8]`, not yet seen on-chain.
+/// Assumed to keep `fill`'s argument layout until verified against the IDL, so it is
+/// only recognized with `GmConfig::accept_fill_v2`.
+pub const FILL_V2: FillLayout = FillLayout {
+    name: "fill_v2",
+    discriminator: [0x8a, 0x20, 0xaf, 0x8b, 0x4c, 0xfc, 0xf1, 0xd7],
+    input_amount_offset: 8,
+    output_amount_offset: 16,
+    expire_at_offset: 24,
+};
+
+/// Every fill variant with a known layout; `parser::fill_layouts` gives the enabled ones
+pub const KNOWN_FILL_LAYOUTS: [FillLayout; 2] = [FILL, FILL_V2];
+
+/// Jupiter Order Engine "fill" discriminator: `sha256("global:fill")[..8]`
+pub const FILL_DISCRIMINATOR: [u8; 8] = FILL.discriminator;
+
+/// Jupiter Order Engine "fill" instruction discriminator
+/// This is the first 8 bytes of the instruction data for a fill
+/// Verified from Jupiter Order Engine on-chain program
+///
+/// Account indices in the Jupiter Order Engine fill instruction
+/// Based on actual on-chain transaction analysis (verified from mainnet)
+///
+/// Layout: taker, maker, taker_input_ata, maker_input_ata, taker_output_ata, maker_output_ata,
+///         input_mint, input_token_program, output_mint, output_token_program, system_program
+pub mod account_indices {
+    pub const TAKER: usize = 0; // Signer, user
+    pub const MAKER: usize = 1; // Signer, market maker (solver)
+    pub const TAKER_INPUT_ATA: usize = 2; // Taker's input token account
+    pub const MAKER_INPUT_ATA: usize = 3; // Maker's input token account
+    pub const TAKER_OUTPUT_ATA: usize = 4; // Taker's output token account (receives GM tokens)
+    pub const MAKER_OUTPUT_ATA: usize = 5; // Maker's output token account (sends GM tokens)
+    pub const INPUT_MINT: usize = 6; // Input token mint
+    pub const INPUT_TOKEN_PROGRAM: usize = 7; // Input token program
+    pub const OUTPUT_MINT: usize = 8; // Output token mint (GM token)
+    pub const OUTPUT_TOKEN_PROGRAM: usize = 9; // Output token program, when present (see `output_token_program`)
+}
+
+/// Most extra accounts (e.g. a referral or platform-fee account) tolerated before the mints
+const MAX_EXTRA_FILL_ACCOUNTS: usize = 2;
+
+/// Errors from the allocation-free parser
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreError {
+    #[error("Instruction data too short: expected at least {expected} bytes, got {got}")]
+    DataTooShort { expected: usize, got: usize },
+
+    #[error("Invalid account index in instruction")]
+    InvalidAccountIndex,
+
+    #[error("Missing required account")]
+    MissingAccount,
+}
+
+/// Accounts of a fill instruction, borrowed from the message's account keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillAccounts<'a, K = Key> {
+    pub taker: &'a K,
+    pub maker: &'a K,
+    pub maker_output_account: &'a K,
+    pub input_mint: &'a K,
+    pub output_mint: &'a K,
+    pub input_token_program: &'a K,
+    /// `None` when the fill omits the trailing output token program
+    pub output_token_program: Option<&'a K>,
+    /// Extra accounts after the maker, see `fill_account_offset`
+    pub offset: usize,
+}
+
+/// Amounts and expiry decoded from fill instruction data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillAmounts {
+    pub input_amount: u64,
+    pub output_amount: u64,
+    pub expire_at: i64,
+}
+
+/// Find the layout among `layouts` whose discriminator prefixes `data`
+pub fn find_fill_layout<'l>(
+    data: &[u8],
+    layouts: impl IntoIterator<Item = &'l FillLayout>,
+) -> Option<&'l FillLayout> {
+    let discriminator = data.get(..8)?;
+    layouts
+        .into_iter()
+        .find(|layout| layout.discriminator == discriminator)
+}
+
+/// The layout among `layouts` of a fill instruction for `program_id`
+///
+/// Returns `None` if the instruction is for another program or has an unrecognized
+/// discriminator.
+pub fn fill_layout_for_program<'l, K: PartialEq>(
+    program_id_index: u8,
+    data: &[u8],
+    program_id: &K,
+    account_keys: &[K],
+    layouts: impl IntoIterator<Item = &'l FillLayout>,
+) -> Option<&'l FillLayout> {
+    // Check program ID matches Jupiter Order Engine
+    if account_keys.get(program_id_index as usize) != Some(program_id) {
+        return None;
+    }
+
+    // Check discriminator
+    find_fill_layout(data, layouts)
+}
+
+/// Check if an instruction is a Jupiter Order Engine fill
+///
+/// Only `FILL` is recognized here; `parser::is_jupiter_fill_instruction` also accepts
+/// `FILL_V2` when the config enables it.
+pub fn is_jupiter_fill_instruction<K: PartialEq>(
+    program_id_index: u8,
+    data: &[u8],
+    program_id: &K,
+    account_keys: &[K],
+) -> bool {
+    fill_layout_for_program(program_id_index, data, program_id, account_keys, [&FILL]).is_some()
+}
+
+/// Check if a key is the SPL Token or Token-2022 program
+fn is_token_program(key: &[u8]) -> bool {
+    key == SPL_TOKEN_PROGRAM.as_slice() || key == TOKEN_2022_PROGRAM.as_slice()
+}
+
+/// Shift to apply to `account_indices` after the maker for this fill
+///
+/// Some fills insert extra accounts, such as a referral or platform-fee account, after
+/// the maker and ahead of the input mint, which shifts every later index. The input mint
+/// is always followed by its token program, so this is the smallest shift (up to
+/// `MAX_EXTRA_FILL_ACCOUNTS`) that puts SPL Token or Token-2022 at `INPUT_TOKEN_PROGRAM`
+/// and an account that isn't a token program at `INPUT_MINT`. Falls back to no shift.
+pub fn fill_account_offset<K: AsRef<[u8]>>(accounts: &[u8], account_keys: &[K]) -> usize {
+    let is_token_program_at = |idx: usize| {
+        accounts
+            .get(idx)
+            .and_then(|&key_idx| account_keys.get(key_idx as usize))
+            .is_some_and(|key| is_token_program(key.as_ref()))
+    };
+
+    (0..=MAX_EXTRA_FILL_ACCOUNTS)
+        .find(|offset| {
+            is_token_program_at(account_indices::INPUT_TOKEN_PROGRAM + offset)
+                && !is_token_program_at(account_indices::INPUT_MINT + offset)
+        })
+        .unwrap_or(0)
+}
+
+/// Index of `account_indices` entry `idx` in a fill whose accounts are shifted by `offset`
+pub fn shifted_account_index(idx: usize, offset: usize) -> usize {
+    if idx > account_indices::MAKER {
+        idx + offset
+    } else {
+        idx
+    }
+}
+
+/// The fill's output token program, if it lists one after the output mint
+///
+/// Real fills list `output_mint, output_token_program, system_program`, but some
+/// layouts omit the output token program or put other accounts ahead of it, so rather
+/// than trusting `OUTPUT_TOKEN_PROGRAM` this returns the first SPL Token or Token-2022
+/// account from that index on.
+pub fn output_token_program<'a, K: AsRef<[u8]>>(
+    accounts: &[u8],
+    account_keys: &'a [K],
+    offset: usize,
+) -> Option<&'a K> {
+    let start = shifted_account_index(account_indices::OUTPUT_TOKEN_PROGRAM, offset);
+    accounts
+        .iter()
+        .skip(start)
+        .filter_map(|&key_idx| account_keys.get(key_idx as usize))
+        .find(|key| is_token_program(key.as_ref()))
+}
+
+/// Resolve the fill's accounts from its compiled account indices
+pub fn parse_fill_accounts<'a, K: AsRef<[u8]>>(
+    accounts: &[u8],
+    account_keys: &'a [K],
+) -> Result<FillAccounts<'a, K>, CoreError> {
+    // Extra accounts (e.g. a referral account) shift the indices after the maker
+    let offset = fill_account_offset(accounts, account_keys);
+
+    let get_account = |idx: usize| -> Result<&'a K, CoreError> {
+        let account_idx = accounts
+            .get(shifted_account_index(idx, offset))
+            .ok_or(CoreError::InvalidAccountIndex)?;
+        account_keys
+            .get(*account_idx as usize)
+            .ok_or(CoreError::MissingAccount)
+    };
+
+    Ok(FillAccounts {
+        maker: get_account(account_indices::MAKER)?,
+        taker: get_account(account_indices::TAKER)?,
+        maker_output_account: get_account(account_indices::MAKER_OUTPUT_ATA)?,
+        input_mint: get_account(account_indices::INPUT_MINT)?,
+        output_mint: get_account(account_indices::OUTPUT_MINT)?,
+        input_token_program: get_account(account_indices::INPUT_TOKEN_PROGRAM)?,
+        output_token_program: output_token_program(accounts, account_keys, offset),
+        offset,
+    })
+}
+
+/// Decode the fill arguments at `layout`'s offsets
+pub fn parse_fill_amounts(data: &[u8], layout: &FillLayout) -> Result<FillAmounts, CoreError> {
+    let min_len = layout.min_data_len();
+    if data.len() < min_len {
+        return Err(CoreError::DataTooShort {
+            expected: min_len,
+            got: data.len(),
+        });
+    }
+
+    let read = |offset: usize| -> [u8; 8] {
+        data[offset..offset + 8]
+            .try_into()
+            .expect("slice is 8 bytes")
+    };
+
+    Ok(FillAmounts {
+        input_amount: u64::from_le_bytes(read(layout.input_amount_offset)),
+        output_amount: u64::from_le_bytes(read(layout.output_amount_offset)),
+        expire_at: i64::from_le_bytes(read(layout.expire_at_offset)),
+    })
+}
+
+/// Base58-encode a key into `buf` without allocating
+fn encode_key<'b>(key: &Key, buf: &'b mut [u8; 44]) -> &'b str {
+    let len = bs58::encode(key)
+        .onto(&mut buf[..])
+        .expect("44 bytes fits any 32-byte key");
+    str::from_utf8(&buf[..len]).expect("base58 is ASCII")
+}
+
+/// Check if a key is one of the built-in `AUTHORIZED_SOLVERS`
+///
+/// Unlike `constants::is_authorized_solver`, this ignores `GmConfig::authorized_solvers`.
+pub fn is_authorized_solver(key: &Key) -> bool {
+    let mut buf = [0u8; 44];
+    let key_str = encode_key(key, &mut buf);
+    AUTHORIZED_SOLVERS.contains(&key_str)
+}
+
+/// Get the symbol for a GM token mint, if it is one
+pub fn gm_token_symbol(mint: &Key) -> Option<&'static str> {
+    let mut buf = [0u8; 44];
+    let mint_str = encode_key(mint, &mut buf);
+    GM_TOKENS
+        .iter()
+        .find(|(_, addr)| *addr == mint_str)
+        .map(|(symbol, _)| *symbol)
+}
+
+/// Check if a key is an Ondo GM token mint
+pub fn is_gm_token(mint: &Key) -> bool {
+    gm_token_symbol(mint).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{instruction_discriminator, jupiter_order_engine_program_id};
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_fill_discriminator_matches_anchor() {
+        assert_eq!(FILL_DISCRIMINATOR, instruction_discriminator("fill"));
+        assert_eq!(FILL_V2.discriminator, instruction_discriminator("fill_v2"));
+    }
+
+    #[test]
+    fn test_token_program_keys() {
+        assert_eq!(
+            Pubkey::new_from_array(SPL_TOKEN_PROGRAM),
+            crate::constants::spl_token_program_id()
+        );
+        assert_eq!(
+            Pubkey::new_from_array(TOKEN_2022_PROGRAM),
+            crate::token_2022_program_id()
+        );
+    }
+
+    #[test]
+    fn test_parse_fill() {
+        let jupiter = jupiter_order_engine_program_id().to_bytes();
+        let solver = Pubkey::from_str("AMJ81TnD4EWftmVPxppiEPsSFbmfYAvvLkUaNDXuR7JH")
+            .unwrap()
+            .to_bytes();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo")
+            .unwrap()
+            .to_bytes();
+
+        let mut keys = [[0u8; 32]; 10];
+        for (i, key) in keys.iter_mut().enumerate() {
+            *key = Pubkey::new_unique().to_bytes();
+            key[0] = i as u8;
+        }
+        keys[1] = solver;
+        keys[7] = SPL_TOKEN_PROGRAM;
+        keys[8] = aapl;
+        keys[9] = jupiter;
+
+        let mut data = [0u8; 32];
+        data[..8].copy_from_slice(&FILL_DISCRIMINATOR);
+        data[8..16].copy_from_slice(&200_000_000u64.to_le_bytes());
+        data[16..24].copy_from_slice(&1_500_000_000u64.to_le_bytes());
+        data[24..32].copy_from_slice(&1704067200i64.to_le_bytes());
+
+        assert!(is_jupiter_fill_instruction(9, &data, &jupiter, &keys));
+        assert!(!is_jupiter_fill_instruction(8, &data, &jupiter, &keys));
+
+        let accounts = parse_fill_accounts(&[0, 1, 2, 3, 4, 5, 6, 7, 8], &keys).unwrap();
+        assert_eq!(accounts.maker, &solver);
+        assert_eq!(accounts.output_mint, &aapl);
+        assert_eq!(accounts.output_token_program, None);
+        assert_eq!(accounts.offset, 0);
+        assert!(is_authorized_solver(accounts.maker));
+        assert!(!is_authorized_solver(accounts.taker));
+        assert_eq!(gm_token_symbol(accounts.output_mint), Some("AAPLon"));
+
+        // A fee account after the maker shifts the rest
+        let shifted = parse_fill_accounts(&[0, 1, 2, 2, 3, 4, 5, 6, 7, 8], &keys).unwrap();
+        assert_eq!(shifted.offset, 1);
+        assert_eq!(shifted.output_mint, &aapl);
+
+        let amounts = parse_fill_amounts(&data, &FILL).unwrap();
+        assert_eq!(amounts.output_amount, 1_500_000_000);
+        assert_eq!(amounts.expire_at, 1704067200);
+
+        assert_eq!(
+            parse_fill_accounts(&[0, 1, 2], &keys),
+            Err(CoreError::InvalidAccountIndex)
+        );
+        assert_eq!(
+            parse_fill_amounts(&data[..24], &FILL),
+            Err(CoreError::DataTooShort {
+                expected: 32,
+                got: 24
+            })
+        );
+    }
+}
@@ -31,7 +31,23 @@ use sha2::{Digest, Sha256};
 /// let _disc = instruction_discriminator("mint_gm");
 /// ```
 pub fn instruction_discriminator(name: &str) -> [u8; 8] {
-    let preimage = format!("global:{}", name);
+    sha256_prefix(&format!("global:{}", name))
+}
+
+/// Compute Anchor account discriminator: sha256("account:<name>")[0..8]
+///
+/// Anchor prefixes every account's data with it, so decoders can tell an account
+/// type from any other account owned by the same program.
+///
+/// # Arguments
+///
+/// * `name` - The account type name (e.g., "OracleSanityCheck")
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    sha256_prefix(&format!("account:{}", name))
+}
+
+/// The first 8 bytes of sha256(`preimage`)
+fn sha256_prefix(preimage: &str) -> [u8; 8] {
     let mut hasher = Sha256::new();
     hasher.update(preimage.as_bytes());
     let hash_result = hasher.finalize();
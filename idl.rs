@@ -0,0 +1,487 @@
+//! Check an Anchor IDL against the layouts this crate hardcodes.
+//!
+//! The `mint_gm` accounts, the `OracleSanityCheck` account fields and the Jupiter
+//! `fill` arguments are encoded by position, so a program upgrade that reorders them
+//! breaks simulation without any error at build time. Run `validate_against_idl` on a
+//! freshly fetched IDL to catch that.
+
+use serde_json::Value;
+
+use crate::{
+    mint_instruction::{MINT_GM_DISCRIMINATOR, ORACLE_SANITY_CHECK_DISCRIMINATOR},
+    parser::FILL,
+    types::{GmSimulatorError, IdlMismatch, IdlValidationReport},
+};
+
+/// `mint_gm` accounts in the order `MintGmAccounts::to_account_metas` emits them
+const MINT_GM_ACCOUNTS: [&str; 12] = [
+    "payer",
+    "authority",
+    "user",
+    "authority_role_account",
+    "oracle_sanity_check",
+    "mint_authority",
+    "mint",
+    "destination",
+    "usdon_manager_state",
+    "token_program",
+    "associated_token_program",
+    "system_program",
+];
+
+/// `mint_gm` arguments as `(name, type)`, in `build_mint_gm_instruction`'s data order
+const MINT_GM_ARGS: [(&str, &str); 1] = [("amount", "u64")];
+
+/// Name of the account type `OracleSanityCheck::try_from_account_data` decodes
+const ORACLE_SANITY_CHECK: &str = "OracleSanityCheck";
+
+/// `OracleSanityCheck` fields as `(name, type)`, in `try_from_account_data`'s order
+const ORACLE_SANITY_CHECK_FIELDS: [(&str, &str); 5] = [
+    ("mint", "pubkey"),
+    ("last_price", "u64"),
+    ("allowed_deviation_bps", "u64"),
+    ("max_time_delay", "i64"),
+    ("last_updated", "i64"),
+];
+
+/// `fill` arguments as `(name, type)`, in the order of `FILL`'s offsets
+const FILL_ARGS: [(&str, &str); 3] = [
+    ("input_amount", "u64"),
+    ("output_amount", "u64"),
+    ("expire_at", "i64"),
+];
+
+/// Validate an Anchor IDL against the crate's hardcoded instruction layouts
+///
+/// Checks the `mint_gm` account order and arguments and the `OracleSanityCheck`
+/// account fields (Ondo GM IDL) and the `fill` argument order (Jupiter Order Engine
+/// IDL), plus each discriminator when the IDL includes them. Only the instructions and
+/// accounts present in the IDL are checked, so either program's IDL can be passed. Both the legacy (camelCase) and Anchor 0.30+
+/// (snake_case) IDL formats are accepted.
+///
+/// # Arguments
+///
+/// * `idl_json` - The IDL, e.g. from `anchor idl fetch <program id>`
+///
+/// # Returns
+///
+/// * `Ok(report)` listing the instructions checked and any mismatches
+/// * `Err(InvalidIdl)` if the JSON is not an IDL or contains neither instruction
+pub fn validate_against_idl(idl_json: &str) -> Result<IdlValidationReport, GmSimulatorError> {
+    let idl: Value =
+        serde_json::from_str(idl_json).map_err(|e| GmSimulatorError::InvalidIdl(e.to_string()))?;
+    let instructions = idl
+        .get("instructions")
+        .and_then(Value::as_array)
+        .ok_or_else(|| GmSimulatorError::InvalidIdl("missing `instructions` array".into()))?;
+
+    let find = |name: &str| {
+        instructions.iter().find(|ix| {
+            ix.get("name")
+                .and_then(Value::as_str)
+                .is_some_and(|n| same_name(n, name))
+        })
+    };
+
+    let mut report = IdlValidationReport::default();
+
+    if let Some(mint_gm) = find("mint_gm") {
+        report.checked.push("mint_gm".to_string());
+        check_discriminator(&mut report, "mint_gm", mint_gm, &MINT_GM_DISCRIMINATOR);
+
+        let accounts = idl_account_names(mint_gm);
+        if accounts.len() != MINT_GM_ACCOUNTS.len() {
+            report.mismatches.push(IdlMismatch::AccountCount {
+                instruction: "mint_gm".to_string(),
+                expected: MINT_GM_ACCOUNTS.len(),
+                actual: accounts.len(),
+            });
+        }
+        for (index, (expected, actual)) in MINT_GM_ACCOUNTS.iter().zip(&accounts).enumerate() {
+            if !same_name(actual, expected) {
+                report.mismatches.push(IdlMismatch::Account {
+                    instruction: "mint_gm".to_string(),
+                    index,
+                    expected: expected.to_string(),
+                    actual: actual.clone(),
+                });
+            }
+        }
+
+        check_args(&mut report, "mint_gm", mint_gm, &MINT_GM_ARGS);
+    }
+
+    if let Some(account) = find_account(&idl, ORACLE_SANITY_CHECK) {
+        report.checked.push(ORACLE_SANITY_CHECK.to_string());
+        check_discriminator(
+            &mut report,
+            ORACLE_SANITY_CHECK,
+            account,
+            &ORACLE_SANITY_CHECK_DISCRIMINATOR,
+        );
+        check_fields(
+            &mut report,
+            ORACLE_SANITY_CHECK,
+            account_fields(&idl, account, ORACLE_SANITY_CHECK),
+            &ORACLE_SANITY_CHECK_FIELDS,
+        );
+    }
+
+    if let Some(fill) = find(FILL.name) {
+        report.checked.push(FILL.name.to_string());
+        check_discriminator(&mut report, FILL.name, fill, &FILL.discriminator);
+        check_args(&mut report, FILL.name, fill, &FILL_ARGS);
+    }
+
+    if report.checked.is_empty() {
+        return Err(GmSimulatorError::InvalidIdl(
+            "IDL has no `mint_gm` or `fill` instruction or `OracleSanityCheck` account".into(),
+        ));
+    }
+
+    Ok(report)
+}
+
+/// Compare IDL names ignoring case and underscores (legacy IDLs use camelCase)
+fn same_name(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| -> String {
+        s.chars()
+            .filter(|c| *c != '_')
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    };
+    normalize(a) == normalize(b)
+}
+
+/// The IDL's account type named `name`
+fn find_account<'a>(idl: &'a Value, name: &str) -> Option<&'a Value> {
+    idl.get("accounts")?.as_array()?.iter().find(|account| {
+        account
+            .get("name")
+            .and_then(Value::as_str)
+            .is_some_and(|n| same_name(n, name))
+    })
+}
+
+/// An account type's fields
+///
+/// Legacy IDLs define them on the account itself; Anchor 0.30+ IDLs list only the
+/// name and discriminator under `accounts` and define the fields under `types`.
+fn account_fields<'a>(idl: &'a Value, account: &'a Value, name: &str) -> Option<&'a Vec<Value>> {
+    let fields = |ty: &'a Value| ty.get("type")?.get("fields")?.as_array();
+    fields(account).or_else(|| {
+        idl.get("types")?
+            .as_array()?
+            .iter()
+            .find(|ty| {
+                ty.get("name")
+                    .and_then(Value::as_str)
+                    .is_some_and(|n| same_name(n, name))
+            })
+            .and_then(fields)
+    })
+}
+
+/// Compare IDL types, treating the legacy `publicKey` as Anchor 0.30+'s `pubkey`
+fn same_type(actual: &str, expected: &str) -> bool {
+    actual == expected || (expected == "pubkey" && actual == "publicKey")
+}
+
+/// An instruction's account names in order, flattening composite account groups
+fn idl_account_names(instruction: &Value) -> Vec<String> {
+    fn collect(accounts: &[Value], names: &mut Vec<String>) {
+        for account in accounts {
+            match account.get("accounts").and_then(Value::as_array) {
+                Some(nested) => collect(nested, names),
+                None => names.push(
+                    account
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                ),
+            }
+        }
+    }
+
+    let mut names = Vec::new();
+    if let Some(accounts) = instruction.get("accounts").and_then(Value::as_array) {
+        collect(accounts, &mut names);
+    }
+    names
+}
+
+/// Record a mismatch if the IDL lists a discriminator that differs from `expected`
+fn check_discriminator(
+    report: &mut IdlValidationReport,
+    name: &str,
+    instruction: &Value,
+    expected: &[u8; 8],
+) {
+    let Some(discriminator) = instruction.get("discriminator").and_then(Value::as_array) else {
+        // Legacy IDLs don't list discriminators
+        return;
+    };
+    let actual: Vec<u8> = discriminator
+        .iter()
+        .filter_map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+        .collect();
+    if actual != expected {
+        report.mismatches.push(IdlMismatch::Discriminator {
+            instruction: name.to_string(),
+            expected: *expected,
+            actual,
+        });
+    }
+}
+
+/// Record mismatches between the IDL's arguments and the expected `(name, type)` list
+fn check_args(
+    report: &mut IdlValidationReport,
+    name: &str,
+    instruction: &Value,
+    expected: &[(&str, &str)],
+) {
+    check_fields(
+        report,
+        name,
+        instruction.get("args").and_then(Value::as_array),
+        expected,
+    );
+}
+
+/// Record mismatches between IDL arguments or struct fields and the expected
+/// `(name, type)` list
+fn check_fields(
+    report: &mut IdlValidationReport,
+    name: &str,
+    fields: Option<&Vec<Value>>,
+    expected: &[(&str, &str)],
+) {
+    let args: Vec<(String, String)> = fields
+        .map(|args| {
+            args.iter()
+                .map(|arg| {
+                    let arg_name = arg.get("name").and_then(Value::as_str).unwrap_or_default();
+                    let arg_type = match arg.get("type") {
+                        Some(Value::String(ty)) => ty.clone(),
+                        Some(other) => other.to_string(),
+                        None => String::new(),
+                    };
+                    (arg_name.to_string(), arg_type)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if args.len() != expected.len() {
+        report.mismatches.push(IdlMismatch::ArgCount {
+            instruction: name.to_string(),
+            expected: expected.len(),
+            actual: args.len(),
+        });
+    }
+    for (index, ((expected_name, expected_type), (actual_name, actual_type))) in
+        expected.iter().zip(&args).enumerate()
+    {
+        if !same_name(actual_name, expected_name) || !same_type(actual_type, expected_type) {
+            report.mismatches.push(IdlMismatch::Arg {
+                instruction: name.to_string(),
+                index,
+                expected: format!("{}: {}", expected_name, expected_type),
+                actual: format!("{}: {}", actual_name, actual_type),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mint_instruction::MintGmAccounts;
+    use serde_json::json;
+    use solana_sdk::pubkey::Pubkey;
+
+    /// An Anchor 0.30+ style Ondo GM IDL matching the crate's layout
+    fn mint_gm_idl() -> Value {
+        json!({
+            "address": "XzTT4XB8m7sLD2xi6snefSasaswsKCxx5Tifjondogm",
+            "instructions": [{
+                "name": "mint_gm",
+                "discriminator": MINT_GM_DISCRIMINATOR,
+                "accounts": MINT_GM_ACCOUNTS
+                    .iter()
+                    .map(|name| json!({ "name": name }))
+                    .collect::<Vec<_>>(),
+                "args": [{ "name": "amount", "type": "u64" }],
+            }],
+        })
+    }
+
+    #[test]
+    fn test_expected_accounts_match_builder() {
+        let metas = MintGmAccounts::new(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        )
+        .to_account_metas();
+        assert_eq!(metas.len(), MINT_GM_ACCOUNTS.len());
+    }
+
+    #[test]
+    fn test_validate_against_idl_matching() {
+        let report = validate_against_idl(&mint_gm_idl().to_string()).unwrap();
+        assert_eq!(report.checked, vec!["mint_gm".to_string()]);
+        assert!(report.is_ok());
+
+        // Legacy Jupiter IDL: camelCase names and no discriminators
+        let jupiter = json!({
+            "instructions": [{
+                "name": "fill",
+                "accounts": [{ "name": "taker", "isMut": true, "isSigner": true }],
+                "args": [
+                    { "name": "inputAmount", "type": "u64" },
+                    { "name": "outputAmount", "type": "u64" },
+                    { "name": "expireAt", "type": "i64" },
+                ],
+            }],
+        });
+        let report = validate_against_idl(&jupiter.to_string()).unwrap();
+        assert_eq!(report.checked, vec!["fill".to_string()]);
+        assert!(report.is_ok());
+    }
+
+    #[test]
+    fn test_validate_against_idl_mismatches() {
+        let mut idl = mint_gm_idl();
+        let mint_gm = &mut idl["instructions"][0];
+        let accounts = mint_gm["accounts"].as_array_mut().unwrap();
+        accounts.swap(6, 7);
+        accounts.pop();
+        mint_gm["discriminator"] = json!([0, 0, 0, 0, 0, 0, 0, 0]);
+        mint_gm["args"] = json!([{ "name": "amount", "type": "u128" }]);
+
+        let report = validate_against_idl(&idl.to_string()).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.mismatches,
+            vec![
+                IdlMismatch::Discriminator {
+                    instruction: "mint_gm".to_string(),
+                    expected: MINT_GM_DISCRIMINATOR,
+                    actual: vec![0; 8],
+                },
+                IdlMismatch::AccountCount {
+                    instruction: "mint_gm".to_string(),
+                    expected: 12,
+                    actual: 11,
+                },
+                IdlMismatch::Account {
+                    instruction: "mint_gm".to_string(),
+                    index: 6,
+                    expected: "mint".to_string(),
+                    actual: "destination".to_string(),
+                },
+                IdlMismatch::Account {
+                    instruction: "mint_gm".to_string(),
+                    index: 7,
+                    expected: "destination".to_string(),
+                    actual: "mint".to_string(),
+                },
+                IdlMismatch::Arg {
+                    instruction: "mint_gm".to_string(),
+                    index: 0,
+                    expected: "amount: u64".to_string(),
+                    actual: "amount: u128".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_against_idl_oracle_sanity_check() {
+        let fields = |fields: &[(&str, &str)]| {
+            fields
+                .iter()
+                .map(|(name, ty)| json!({ "name": name, "type": ty }))
+                .collect::<Vec<_>>()
+        };
+
+        // Anchor 0.30+: the discriminator under `accounts`, the fields under `types`
+        let mut idl = mint_gm_idl();
+        idl["accounts"] = json!([{
+            "name": "OracleSanityCheck",
+            "discriminator": ORACLE_SANITY_CHECK_DISCRIMINATOR,
+        }]);
+        idl["types"] = json!([{
+            "name": "OracleSanityCheck",
+            "type": { "kind": "struct", "fields": fields(&ORACLE_SANITY_CHECK_FIELDS) },
+        }]);
+        let report = validate_against_idl(&idl.to_string()).unwrap();
+        assert_eq!(report.checked, vec!["mint_gm", "OracleSanityCheck"]);
+        assert!(report.is_ok());
+
+        // Legacy: camelCase fields on the account itself, with `publicKey`
+        let legacy = json!({
+            "instructions": [],
+            "accounts": [{
+                "name": "OracleSanityCheck",
+                "type": {
+                    "kind": "struct",
+                    "fields": fields(&[
+                        ("mint", "publicKey"),
+                        ("lastPrice", "u64"),
+                        ("allowedDeviationBps", "u64"),
+                        ("maxTimeDelay", "i64"),
+                        ("lastUpdated", "i64"),
+                    ]),
+                },
+            }],
+        });
+        let report = validate_against_idl(&legacy.to_string()).unwrap();
+        assert_eq!(report.checked, vec!["OracleSanityCheck"]);
+        assert!(report.is_ok());
+
+        // A reordered layout and a different discriminator
+        let mut reordered = ORACLE_SANITY_CHECK_FIELDS;
+        reordered.swap(3, 4);
+        idl["accounts"][0]["discriminator"] = json!([0, 0, 0, 0, 0, 0, 0, 0]);
+        idl["types"][0]["type"]["fields"] = json!(fields(&reordered));
+        let report = validate_against_idl(&idl.to_string()).unwrap();
+        assert_eq!(
+            report.mismatches,
+            vec![
+                IdlMismatch::Discriminator {
+                    instruction: "OracleSanityCheck".to_string(),
+                    expected: ORACLE_SANITY_CHECK_DISCRIMINATOR,
+                    actual: vec![0; 8],
+                },
+                IdlMismatch::Arg {
+                    instruction: "OracleSanityCheck".to_string(),
+                    index: 3,
+                    expected: "max_time_delay: i64".to_string(),
+                    actual: "last_updated: i64".to_string(),
+                },
+                IdlMismatch::Arg {
+                    instruction: "OracleSanityCheck".to_string(),
+                    index: 4,
+                    expected: "last_updated: i64".to_string(),
+                    actual: "max_time_delay: i64".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_against_idl_invalid() {
+        assert!(matches!(
+            validate_against_idl("not json"),
+            Err(GmSimulatorError::InvalidIdl(_))
+        ));
+        assert!(matches!(
+            validate_against_idl(r#"{"instructions": [{"name": "cancel"}]}"#),
+            Err(GmSimulatorError::InvalidIdl(_))
+        ));
+    }
+}
@@ -83,30 +83,106 @@
 //!   against the actual on-chain IDL at program `XzTT4XB8m7sLD2xi6snefSasaswsKCxx5Tifjondogm`
 //! - GM tokens use Token-2022 (not SPL Token)
 //! - All GM tokens have 9 decimal places
+//!
+//! ## `no_std`
+//!
+//! With the `no_std` feature the crate is `#![no_std]` and builds only the allocation-free
+//! fill detection in [`core`] and the constant tables it reads.
+
+#![cfg_attr(feature = "no_std", no_std)]
 
+#[cfg(feature = "no_std")]
+extern crate alloc;
+
+#[cfg(not(feature = "no_std"))]
+pub mod bundle;
 pub mod constants;
+pub mod core;
+#[cfg(not(feature = "no_std"))]
 pub mod discriminator;
+#[cfg(not(feature = "no_std"))]
+pub mod idl;
+#[cfg(not(feature = "no_std"))]
 pub mod mint_instruction;
+#[cfg(not(feature = "no_std"))]
 pub mod parser;
+#[cfg(not(feature = "no_std"))]
+pub mod prometheus;
+#[cfg(not(feature = "no_std"))]
+pub mod rpc;
+#[cfg(not(feature = "no_std"))]
 pub mod simulator;
+#[cfg(feature = "test-bank")]
+pub mod test_bank;
+#[cfg(test)]
+mod test_utils;
+#[cfg(feature = "test-validator")]
+pub mod test_validator;
+#[cfg(not(feature = "no_std"))]
+pub mod transaction;
+#[cfg(not(feature = "no_std"))]
 pub mod types;
 
 // Re-export main public API
+#[cfg(not(feature = "no_std"))]
+pub use bundle::{validate_bundle, validate_fill_output};
+#[cfg(not(feature = "no_std"))]
 pub use constants::{
-    admin_minter, get_gm_token_symbol, is_authorized_solver, is_gm_token,
-    jupiter_order_engine_program_id, ondo_gm_program_id, token_2022_program_id, usdc_mint,
-    ADMIN_MINTER, AUTHORIZED_SOLVERS, GM_TOKENS, JUPITER_ORDER_ENGINE_PROGRAM_ID,
-    ONDO_GM_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, USDC_MINT,
+    admin_minter, ambiguous_symbols, diff_token_list, get_gm_token_symbol, global_config,
+    is_admin_minter, is_authorized_solver, is_gm_token, is_stable_mint,
+    jupiter_order_engine_program_id, known_token_program_for_mint, ondo_gm_program_id,
+    set_global_config, stable_mint_symbol, stable_mints, token_2022_program_id, usdc_mint,
+    usdon_decimals, usdon_mint, usdt_mint, ADMIN_MINTER, AUTHORIZED_SOLVERS,
+    DEFAULT_MAX_INSTRUCTIONS, DETECTION_LATENCY_BUCKETS, GM_TOKENS,
+    JUPITER_ORDER_ENGINE_PROGRAM_ID, MOCK_MINT_ATA_CREATE_COMPUTE_UNITS,
+    MOCK_MINT_GM_COMPUTE_UNITS, ONDO_GM_PROGRAM_ID, STABLE_DECIMALS, TOKEN_2022_PROGRAM_ID,
+    TRADE_EVENT_SCHEMA_VERSION, USDC_MINT, USDON_DECIMALS, USDT_MINT,
 };
-pub use discriminator::instruction_discriminator;
+#[cfg(not(feature = "no_std"))]
+pub use discriminator::{account_discriminator, instruction_discriminator};
+#[cfg(not(feature = "no_std"))]
+pub use idl::validate_against_idl;
+#[cfg(not(feature = "no_std"))]
 pub use mint_instruction::{
-    build_mock_mint_gm_instruction, build_mock_mint_gm_instruction_with_ata, get_gm_token_ata,
+    build_mint_gm_instruction, build_mock_mint_gm_instruction,
+    build_mock_mint_gm_instruction_with_ata, build_mock_mint_gm_instruction_with_minter,
+    get_gm_token_ata, get_gm_token_ata_with_program, mint_authority_account, mint_gm_discriminator,
+    oracle_sanity_check_account, taker_usdon_ata, MintGmAccounts, MINT_GM_DISCRIMINATOR,
+    ORACLE_SANITY_CHECK_DISCRIMINATOR,
+};
+#[cfg(not(feature = "no_std"))]
+pub use parser::decode_fill_args;
+#[cfg(not(feature = "no_std"))]
+pub use prometheus::render_metrics_text;
+#[cfg(not(feature = "no_std"))]
+pub use rpc::{
+    fetch_ata_extensions, fetch_oracle_sanity_check, token_program_for_mint, verify_mint_authority,
 };
+#[cfg(not(feature = "no_std"))]
 pub use simulator::{
-    build_mock_mint_instruction, build_mock_mint_instruction_to_ata, build_mock_mint_transaction,
-    check_gm_trade, check_gm_trade_message, check_gm_trade_versioned,
-    check_gm_trade_versioned_message, maybe_build_mock_mint, simulate_as_bundle,
+    build_mock_mint_instruction, build_mock_mint_instruction_to_ata, build_mock_mint_instructions,
+    build_mock_mint_transaction, build_mock_mint_transaction_with_nonce,
+    build_mock_mint_transaction_with_options, check_gm_trade, check_gm_trade_base64,
+    check_gm_trade_bundle, check_gm_trade_detailed, check_gm_trade_message, check_gm_trade_raw,
+    check_gm_trade_v0_with_tables, check_gm_trade_versioned, check_gm_trade_versioned_message,
+    check_gm_trade_with_max_instructions, check_gm_trades_batch, contains_mint_gm, detect_stream,
+    estimate_mock_mint_rent, extract_inner_fill, gm_mints_in_message, has_nonidempotent_ata_create,
+    maybe_build_mock_mint, maybe_build_mock_mint_with_rpc, mock_mint_lookup_addresses,
+    mock_mint_writable_accounts, parse_enhanced_simulation, parse_simulate_bundle_response,
+    recommended_compute_limit, resolve_instruction_accounts, resolve_v0_accounts,
+    simulate_as_bundle, simulate_as_bundle_with_options, simulate_as_bundle_with_transcript,
+    simulate_by_signature, simulate_mock_mint_only, simulate_trades_as_bundle,
+};
+#[cfg(not(feature = "no_std"))]
+pub use transaction::{
+    bundle_fingerprint, decode_transaction, encode_bundle, estimate_bundle_size,
+    estimate_serialized_size, load_transaction_from_file, with_blockhash, with_blockhash_versioned,
 };
+#[cfg(not(feature = "no_std"))]
 pub use types::{
-    BalanceChange, BundleSimulationResult, GmCheckResult, GmSimulatorError, GmTradeInfo,
+    AccountOverride, AccountsConfig, BalanceChange, BalanceChangeDiff, BundleError,
+    BundleSimulationResult, DetectionMetrics, DetectionOutcome, FillArgs, GmCheckResult, GmConfig,
+    GmSimulatorError, GmTradeInfo, IdlMismatch, IdlValidationReport, MockMintOptions,
+    NoBundleReason, OracleSanityCheck, ProgramError, RawAmount, ResolvedAccount, ResultDiff,
+    SimulateOptions, SimulationTranscript, TokenListDiff, TokenMintChange, TradeDirection,
 };
@@ -8,12 +8,24 @@ use solana_sdk::{
     pubkey::Pubkey,
 };
 use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::sync::OnceLock;
 
-use crate::constants::{admin_minter, ondo_gm_program_id, token_2022_program_id};
+use crate::constants::{admin_minter, ondo_gm_program_id, token_2022_program_id, usdon_mint};
 
 /// Anchor discriminator for "mint_gm" instruction
 /// Verified from on-chain IDL at XzTT4XB8m7sLD2xi6snefSasaswsKCxx5Tifjondogm
-const MINT_GM_DISCRIMINATOR: [u8; 8] = [117, 223, 58, 111, 44, 36, 16, 43];
+pub const MINT_GM_DISCRIMINATOR: [u8; 8] = [117, 223, 58, 111, 44, 36, 16, 43];
+
+/// Anchor discriminator of the `OracleSanityCheck` account,
+/// `sha256("account:OracleSanityCheck")[..8]`
+pub const ORACLE_SANITY_CHECK_DISCRIMINATOR: [u8; 8] = [27, 186, 33, 40, 113, 34, 206, 76];
+
+/// Get the Anchor discriminator of the Ondo GM `mint_gm` instruction
+///
+/// Useful for scanning transactions for Ondo mint instructions.
+pub fn mint_gm_discriminator() -> [u8; 8] {
+    MINT_GM_DISCRIMINATOR
+}
 
 /// PDA seeds (verified from Ondo GM program source)
 const MINT_AUTHORITY_SEED: &[u8] = b"mint_authority";
@@ -21,6 +33,167 @@ const MINTER_ROLE_GMTOKEN_SEED: &[u8] = b"MinterRoleGMToken";
 const ORACLE_SANITY_CHECK_SEED: &[u8] = b"sanity_check";
 const USDON_MANAGER_STATE_SEED: &[u8] = b"usdon_manager";
 
+/// Get the `mint_authority` PDA that GM token mints must name as their mint authority.
+///
+/// Derived once per process since its seeds are constant. See `verify_mint_authority`
+/// to check an on-chain mint against it.
+pub fn mint_authority_account() -> Pubkey {
+    static MINT_AUTHORITY: OnceLock<Pubkey> = OnceLock::new();
+    *MINT_AUTHORITY.get_or_init(|| {
+        Pubkey::find_program_address(&[MINT_AUTHORITY_SEED], &ondo_gm_program_id()).0
+    })
+}
+
+/// Get the `usdon_manager_state` PDA, derived once per process since its seeds are constant
+fn usdon_manager_state_account() -> Pubkey {
+    static USDON_MANAGER_STATE: OnceLock<Pubkey> = OnceLock::new();
+    *USDON_MANAGER_STATE.get_or_init(|| {
+        Pubkey::find_program_address(&[USDON_MANAGER_STATE_SEED], &ondo_gm_program_id()).0
+    })
+}
+
+/// Get the `oracle_sanity_check` PDA for a GM token mint.
+///
+/// `mint_gm` rejects the mint unless this account holds a fresh oracle price, so
+/// callers can fetch it (see `fetch_oracle_sanity_check`) before simulating.
+pub fn oracle_sanity_check_account(gm_token_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[ORACLE_SANITY_CHECK_SEED, gm_token_mint.as_ref()],
+        &ondo_gm_program_id(),
+    )
+    .0
+}
+
+/// Accounts of the `mint_gm` instruction, in on-chain IDL order.
+///
+/// `to_account_metas` produces the 12 `AccountMeta`s in the order the program
+/// expects, so custom builders can adjust individual accounts without copying
+/// the list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MintGmAccounts {
+    /// Pays for the destination ATA if needed (signer, writable)
+    pub payer: Pubkey,
+    /// The minter with MINTER_ROLE_GMTOKEN (signer)
+    pub authority: Pubkey,
+    /// The recipient wallet owner
+    pub user: Pubkey,
+    /// PDA verifying the authority's MINTER_ROLE_GMTOKEN
+    pub authority_role_account: Pubkey,
+    /// PDA validating oracle price updates (writable)
+    pub oracle_sanity_check: Pubkey,
+    /// The mint authority PDA
+    pub mint_authority: Pubkey,
+    /// The GM token mint (writable)
+    pub mint: Pubkey,
+    /// Destination token account (writable, init_if_needed ATA)
+    pub destination: Pubkey,
+    /// Manager state PDA used for validation
+    pub usdon_manager_state: Pubkey,
+    /// Token-2022
+    pub token_program: Pubkey,
+    /// Associated token account program
+    pub associated_token_program: Pubkey,
+    /// System program
+    pub system_program: Pubkey,
+}
+
+impl MintGmAccounts {
+    /// Derive the accounts for minting `gm_token_mint` to `destination` as the admin minter.
+    ///
+    /// # Arguments
+    ///
+    /// * `gm_token_mint` - The GM token mint address
+    /// * `destination` - The destination token account (ATA)
+    /// * `destination_owner` - The owner of the destination ATA (must match for constraint)
+    pub fn new(gm_token_mint: &Pubkey, destination: &Pubkey, destination_owner: &Pubkey) -> Self {
+        Self::new_with_minter(
+            gm_token_mint,
+            destination,
+            destination_owner,
+            &admin_minter(),
+        )
+    }
+
+    /// Derive the accounts for minting as `minter` instead of the admin minter.
+    ///
+    /// `minter` is both payer and authority, and `authority_role_account` is derived
+    /// from it, e.g. for a staging GM deployment with its own minter.
+    ///
+    /// # Arguments
+    ///
+    /// * `gm_token_mint` - The GM token mint address
+    /// * `destination` - The destination token account (ATA)
+    /// * `destination_owner` - The owner of the destination ATA (must match for constraint)
+    /// * `minter` - The minter holding MINTER_ROLE_GMTOKEN
+    pub fn new_with_minter(
+        gm_token_mint: &Pubkey,
+        destination: &Pubkey,
+        destination_owner: &Pubkey,
+        minter: &Pubkey,
+    ) -> Self {
+        let program_id = ondo_gm_program_id();
+        let minter = *minter;
+
+        // Derive PDAs with verified seeds
+        let (authority_role_account, _) =
+            Pubkey::find_program_address(&[MINTER_ROLE_GMTOKEN_SEED, minter.as_ref()], &program_id);
+
+        let oracle_sanity_check = oracle_sanity_check_account(gm_token_mint);
+
+        Self {
+            payer: minter,
+            authority: minter,
+            user: *destination_owner,
+            authority_role_account,
+            oracle_sanity_check,
+            mint_authority: mint_authority_account(),
+            mint: *gm_token_mint,
+            destination: *destination,
+            usdon_manager_state: usdon_manager_state_account(),
+            token_program: token_2022_program_id(),
+            associated_token_program: spl_associated_token_account::id(),
+            system_program: solana_system_interface::program::id(),
+        }
+    }
+
+    /// Build the account metas in the exact order from the on-chain IDL
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.payer, true), // 0: payer (signer, writable)
+            AccountMeta::new_readonly(self.authority, true), // 1: authority (signer)
+            AccountMeta::new_readonly(self.user, false), // 2: user (recipient)
+            AccountMeta::new_readonly(self.authority_role_account, false), // 3: authority_role_account PDA
+            AccountMeta::new(self.oracle_sanity_check, false), // 4: oracle_sanity_check PDA (writable)
+            AccountMeta::new_readonly(self.mint_authority, false), // 5: mint_authority PDA
+            AccountMeta::new(self.mint, false),                // 6: mint (writable)
+            AccountMeta::new(self.destination, false),         // 7: destination ATA (writable)
+            AccountMeta::new_readonly(self.usdon_manager_state, false), // 8: usdon_manager_state PDA
+            AccountMeta::new_readonly(self.token_program, false), // 9: token_program (Token-2022)
+            AccountMeta::new_readonly(self.associated_token_program, false), // 10: ATA program
+            AccountMeta::new_readonly(self.system_program, false), // 11: system_program
+        ]
+    }
+}
+
+/// Build a `mint_gm` instruction from an explicit account layout.
+///
+/// # Arguments
+///
+/// * `accounts` - The instruction accounts
+/// * `amount` - Amount of tokens to mint (in base units, 9 decimals)
+pub fn build_mint_gm_instruction(accounts: &MintGmAccounts, amount: u64) -> Instruction {
+    // Build instruction data: discriminator + amount
+    let mut data = Vec::with_capacity(16);
+    data.extend_from_slice(&MINT_GM_DISCRIMINATOR);
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: ondo_gm_program_id(),
+        accounts: accounts.to_account_metas(),
+        data,
+    }
+}
+
 /// Build a mock mint_gm instruction for simulation.
 ///
 /// This creates an admin mint instruction that will mint GM tokens to the
@@ -43,6 +216,8 @@ const USDON_MANAGER_STATE_SEED: &[u8] = b"usdon_manager";
 /// 11. associated_token_program - ATA program
 /// 12. system_program - System program
 ///
+/// See `MintGmAccounts` for the full account list.
+///
 /// # Arguments
 ///
 /// * `gm_token_mint` - The GM token mint address
@@ -57,57 +232,39 @@ pub fn build_mock_mint_gm_instruction(
     destination_owner: &Pubkey,
     amount: u64,
 ) -> Instruction {
-    let program_id = ondo_gm_program_id();
-    let minter = admin_minter();
-    let token_program = token_2022_program_id();
-
-    // Derive PDAs with verified seeds
-    let (authority_role_account, _) =
-        Pubkey::find_program_address(&[MINTER_ROLE_GMTOKEN_SEED, minter.as_ref()], &program_id);
-
-    let (oracle_sanity_check, _) = Pubkey::find_program_address(
-        &[ORACLE_SANITY_CHECK_SEED, gm_token_mint.as_ref()],
-        &program_id,
-    );
-
-    let (mint_authority, _) = Pubkey::find_program_address(&[MINT_AUTHORITY_SEED], &program_id);
-
-    let (usdon_manager_state, _) =
-        Pubkey::find_program_address(&[USDON_MANAGER_STATE_SEED], &program_id);
-
     // Get the destination ATA (Token-2022)
     let destination_ata = get_associated_token_address_with_program_id(
         destination_owner,
         gm_token_mint,
-        &token_program,
+        &token_2022_program_id(),
     );
 
-    // Build instruction data: discriminator + amount
-    let mut data = Vec::with_capacity(16);
-    data.extend_from_slice(&MINT_GM_DISCRIMINATOR);
-    data.extend_from_slice(&amount.to_le_bytes());
-
-    // Build accounts list in the exact order from the on-chain IDL
-    let accounts = vec![
-        AccountMeta::new(minter, true),          // 0: payer (signer, writable)
-        AccountMeta::new_readonly(minter, true), // 1: authority (signer)
-        AccountMeta::new_readonly(*destination_owner, false), // 2: user (recipient)
-        AccountMeta::new_readonly(authority_role_account, false), // 3: authority_role_account PDA
-        AccountMeta::new(oracle_sanity_check, false), // 4: oracle_sanity_check PDA (writable)
-        AccountMeta::new_readonly(mint_authority, false), // 5: mint_authority PDA
-        AccountMeta::new(*gm_token_mint, false), // 6: mint (writable)
-        AccountMeta::new(destination_ata, false), // 7: destination ATA (writable)
-        AccountMeta::new_readonly(usdon_manager_state, false), // 8: usdon_manager_state PDA
-        AccountMeta::new_readonly(token_program, false), // 9: token_program (Token-2022)
-        AccountMeta::new_readonly(spl_associated_token_account::id(), false), // 10: ATA program
-        AccountMeta::new_readonly(solana_system_interface::program::id(), false), // 11: system_program
-    ];
+    let accounts = MintGmAccounts::new(gm_token_mint, &destination_ata, destination_owner);
+    build_mint_gm_instruction(&accounts, amount)
+}
 
-    Instruction {
-        program_id,
-        accounts,
-        data,
-    }
+/// Build a mock mint_gm instruction signed by a specific minter.
+///
+/// Same as `build_mock_mint_gm_instruction`, but `minter` replaces the admin minter as
+/// payer and authority, and the `authority_role_account` PDA is derived from it. Useful
+/// for staging deployments with a different minter.
+///
+/// # Arguments
+///
+/// * `gm_token_mint` - The GM token mint address
+/// * `destination_owner` - The wallet that will own the minted tokens (the solver)
+/// * `amount` - Amount of tokens to mint (in base units, 9 decimals)
+/// * `minter` - The minter holding MINTER_ROLE_GMTOKEN
+pub fn build_mock_mint_gm_instruction_with_minter(
+    gm_token_mint: &Pubkey,
+    destination_owner: &Pubkey,
+    amount: u64,
+    minter: &Pubkey,
+) -> Instruction {
+    let destination_ata = get_gm_token_ata(destination_owner, gm_token_mint);
+    let accounts =
+        MintGmAccounts::new_with_minter(gm_token_mint, &destination_ata, destination_owner, minter);
+    build_mint_gm_instruction(&accounts, amount)
 }
 
 /// Build a mock mint_gm instruction using a specific destination ATA and owner.
@@ -127,57 +284,45 @@ pub fn build_mock_mint_gm_instruction_with_ata(
     destination_owner: &Pubkey,
     amount: u64,
 ) -> Instruction {
-    let program_id = ondo_gm_program_id();
-    let minter = admin_minter();
-    let token_program = token_2022_program_id();
-
-    // Derive PDAs with verified seeds
-    let (authority_role_account, _) =
-        Pubkey::find_program_address(&[MINTER_ROLE_GMTOKEN_SEED, minter.as_ref()], &program_id);
-
-    let (oracle_sanity_check, _) = Pubkey::find_program_address(
-        &[ORACLE_SANITY_CHECK_SEED, gm_token_mint.as_ref()],
-        &program_id,
-    );
-
-    let (mint_authority, _) = Pubkey::find_program_address(&[MINT_AUTHORITY_SEED], &program_id);
-
-    let (usdon_manager_state, _) =
-        Pubkey::find_program_address(&[USDON_MANAGER_STATE_SEED], &program_id);
-
-    // Build instruction data: discriminator + amount
-    let mut data = Vec::with_capacity(16);
-    data.extend_from_slice(&MINT_GM_DISCRIMINATOR);
-    data.extend_from_slice(&amount.to_le_bytes());
-
-    // Build accounts list - using destination_ata directly with correct owner
-    let accounts = vec![
-        AccountMeta::new(minter, true),          // 0: payer (signer, writable)
-        AccountMeta::new_readonly(minter, true), // 1: authority (signer)
-        AccountMeta::new_readonly(*destination_owner, false), // 2: user (destination owner)
-        AccountMeta::new_readonly(authority_role_account, false), // 3: authority_role_account PDA
-        AccountMeta::new(oracle_sanity_check, false), // 4: oracle_sanity_check PDA (writable)
-        AccountMeta::new_readonly(mint_authority, false), // 5: mint_authority PDA
-        AccountMeta::new(*gm_token_mint, false), // 6: mint (writable)
-        AccountMeta::new(*destination_ata, false), // 7: destination ATA (writable)
-        AccountMeta::new_readonly(usdon_manager_state, false), // 8: usdon_manager_state PDA
-        AccountMeta::new_readonly(token_program, false), // 9: token_program (Token-2022)
-        AccountMeta::new_readonly(spl_associated_token_account::id(), false), // 10: ATA program
-        AccountMeta::new_readonly(solana_system_interface::program::id(), false), // 11: system_program
-    ];
-
-    Instruction {
-        program_id,
-        accounts,
-        data,
-    }
+    let accounts = MintGmAccounts::new(gm_token_mint, destination_ata, destination_owner);
+    build_mint_gm_instruction(&accounts, amount)
 }
 
 /// Get the expected destination ATA for a GM token mint.
 ///
 /// GM tokens use Token-2022, so this derives the ATA using the Token-2022 program.
+/// Use `get_gm_token_ata_with_program` with the program from `token_program_for_mint`
+/// when the mint may belong to SPL Token.
 pub fn get_gm_token_ata(owner: &Pubkey, gm_token_mint: &Pubkey) -> Pubkey {
-    get_associated_token_address_with_program_id(owner, gm_token_mint, &token_2022_program_id())
+    get_gm_token_ata_with_program(owner, gm_token_mint, &token_2022_program_id())
+}
+
+/// Get the destination ATA for a GM token mint owned by `token_program`.
+///
+/// The ATA address depends on the token program, so an SPL Token mint's ATA differs
+/// from the Token-2022 one `get_gm_token_ata` derives.
+///
+/// # Arguments
+///
+/// * `owner` - The wallet that owns the ATA
+/// * `gm_token_mint` - The GM token mint address
+/// * `token_program` - The mint's token program, e.g. from `token_program_for_mint`
+pub fn get_gm_token_ata_with_program(
+    owner: &Pubkey,
+    gm_token_mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, gm_token_mint, token_program)
+}
+
+/// Get a wallet's USDON ATA, or `None` if no USDON mint is configured.
+///
+/// USDON is an Ondo token like the GM tokens, so this derives the ATA using the
+/// Token-2022 program. Use `token_program_for_mint` to confirm on-chain.
+pub fn taker_usdon_ata(owner: &Pubkey) -> Option<Pubkey> {
+    usdon_mint().map(|mint| {
+        get_associated_token_address_with_program_id(owner, &mint, &token_2022_program_id())
+    })
 }
 
 #[cfg(test)]
@@ -198,6 +343,84 @@ mod tests {
         assert!(ix.data.len() >= 16); // discriminator + amount
     }
 
+    #[test]
+    fn test_cached_pdas_match_fresh_derivations() {
+        let program_id = ondo_gm_program_id();
+
+        let (mint_authority, _) = Pubkey::find_program_address(&[MINT_AUTHORITY_SEED], &program_id);
+        let (usdon_manager_state, _) =
+            Pubkey::find_program_address(&[USDON_MANAGER_STATE_SEED], &program_id);
+
+        // Repeated calls return the cached value
+        for _ in 0..2 {
+            assert_eq!(mint_authority_account(), mint_authority);
+            assert_eq!(usdon_manager_state_account(), usdon_manager_state);
+        }
+    }
+
+    #[test]
+    fn test_mint_gm_accounts_layout() {
+        let gm_token = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let destination = get_gm_token_ata(&solver, &gm_token);
+
+        let accounts = MintGmAccounts::new(&gm_token, &destination, &solver);
+        let metas = accounts.to_account_metas();
+
+        assert_eq!(metas.len(), 12);
+        assert_eq!(metas[0].pubkey, admin_minter());
+        assert!(metas[0].is_signer && metas[0].is_writable);
+        assert!(metas[1].is_signer && !metas[1].is_writable);
+        assert_eq!(metas[6].pubkey, gm_token);
+        assert_eq!(metas[7].pubkey, destination);
+        assert!(metas[4].is_writable);
+
+        // Both builders produce the same layout for the derived ATA
+        let amount = 1_500_000_000u64;
+        assert_eq!(
+            build_mock_mint_gm_instruction(&gm_token, &solver, amount),
+            build_mint_gm_instruction(&accounts, amount)
+        );
+        assert_eq!(
+            build_mock_mint_gm_instruction_with_ata(&gm_token, &destination, &solver, amount),
+            build_mint_gm_instruction(&accounts, amount)
+        );
+    }
+
+    #[test]
+    fn test_build_mock_mint_gm_instruction_with_minter() {
+        let gm_token = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let staging_minter = Pubkey::new_unique();
+        let amount = 1_500_000_000u64;
+
+        let ix =
+            build_mock_mint_gm_instruction_with_minter(&gm_token, &solver, amount, &staging_minter);
+        let (role_account, _) = Pubkey::find_program_address(
+            &[MINTER_ROLE_GMTOKEN_SEED, staging_minter.as_ref()],
+            &ondo_gm_program_id(),
+        );
+
+        assert_eq!(ix.accounts[0].pubkey, staging_minter);
+        assert_eq!(ix.accounts[1].pubkey, staging_minter);
+        assert_eq!(ix.accounts[3].pubkey, role_account);
+        assert_eq!(ix.accounts[7].pubkey, get_gm_token_ata(&solver, &gm_token));
+
+        // The admin minter reproduces the default builder
+        assert_eq!(
+            build_mock_mint_gm_instruction_with_minter(&gm_token, &solver, amount, &admin_minter()),
+            build_mock_mint_gm_instruction(&gm_token, &solver, amount)
+        );
+    }
+
+    #[test]
+    fn test_mint_gm_discriminator() {
+        assert_eq!(mint_gm_discriminator(), [117, 223, 58, 111, 44, 36, 16, 43]);
+
+        let ix = build_mock_mint_gm_instruction(&Pubkey::new_unique(), &Pubkey::new_unique(), 1);
+        assert_eq!(ix.data[..8], mint_gm_discriminator());
+    }
+
     #[test]
     fn test_get_gm_token_ata() {
         let owner = Pubkey::new_unique();
@@ -208,4 +431,23 @@ mod tests {
         assert_ne!(ata, owner);
         assert_ne!(ata, mint);
     }
+
+    #[test]
+    fn test_get_gm_token_ata_with_program() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let spl_token = crate::constants::spl_token_program_id();
+
+        // An SPL Token mint gets the SPL-derived ATA, not the Token-2022 one
+        let ata = get_gm_token_ata_with_program(&owner, &mint, &spl_token);
+        assert_eq!(
+            ata,
+            get_associated_token_address_with_program_id(&owner, &mint, &spl_token)
+        );
+        assert_ne!(ata, get_gm_token_ata(&owner, &mint));
+        assert_eq!(
+            get_gm_token_ata_with_program(&owner, &mint, &token_2022_program_id()),
+            get_gm_token_ata(&owner, &mint)
+        );
+    }
 }
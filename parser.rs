@@ -3,38 +3,59 @@
 //! The Jupiter Order Engine program uses a specific instruction layout for RFQ fills.
 //! This module parses those instructions to extract the relevant trade details.
 
-use solana_sdk::{instruction::CompiledInstruction, pubkey::Pubkey};
+use solana_sdk::{instruction::CompiledInstruction, message::MessageHeader, pubkey::Pubkey};
 
 use crate::{
-    constants::{get_gm_token_symbol, is_authorized_solver, is_gm_token},
-    instruction_discriminator,
-    types::{GmSimulatorError, GmTradeInfo},
+    constants::{
+        get_gm_token_symbol, global_config, is_admin_minter, is_authorized_solver, is_gm_token,
+        is_stable_mint, token_2022_program_id,
+    },
+    core::{shifted_account_index, CoreError},
+    types::{FillArgs, GmSimulatorError, GmTradeInfo, NoBundleReason, RawAmount, TradeDirection},
 };
 
-/// Jupiter Order Engine "fill" instruction discriminator
-/// This is the first 8 bytes of the instruction data for a fill
-/// Verified from Jupiter Order Engine on-chain program
+pub use crate::core::{account_indices, FillLayout, FILL, FILL_V2, KNOWN_FILL_LAYOUTS};
+
+/// Fill variants recognized by `fill_layout_for_instruction`
 ///
-/// Account indices in the Jupiter Order Engine fill instruction
-/// Based on actual on-chain transaction analysis (verified from mainnet)
+/// `FILL`, plus `FILL_V2` when the config's `accept_fill_v2` is set.
+pub fn fill_layouts() -> impl Iterator<Item = &'static FillLayout> {
+    let accept_fill_v2 = global_config().accept_fill_v2;
+    KNOWN_FILL_LAYOUTS
+        .iter()
+        .filter(move |layout| accept_fill_v2 || **layout != FILL_V2)
+}
+
+/// Find the recognized fill layout whose discriminator prefixes `data`
+pub fn fill_layout(data: &[u8]) -> Option<&'static FillLayout> {
+    crate::core::find_fill_layout(data, fill_layouts())
+}
+
+/// A program whose fill instructions are checked for GM trades
+///
+/// The venue's fill must list its accounts in the Jupiter fill's order (taker, maker,
+/// token accounts, then mints and token programs; see `account_indices`). `layout`
+/// gives its discriminator and argument offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillVenue {
+    /// The venue's program
+    pub program_id: Pubkey,
+    /// The fill instruction's discriminator and argument layout
+    pub layout: FillLayout,
+}
+
+/// Every venue detection consults, in order
 ///
-/// Layout: taker, maker, taker_input_ata, maker_input_ata, taker_output_ata, maker_output_ata,
-///         input_mint, input_token_program, output_mint, output_token_program, system_program
-mod account_indices {
-    pub const TAKER: usize = 0; // Signer, user
-    pub const MAKER: usize = 1; // Signer, market maker (solver)
-    #[allow(dead_code)]
-    pub const TAKER_INPUT_ATA: usize = 2; // Taker's input token account
-    #[allow(dead_code)]
-    pub const MAKER_INPUT_ATA: usize = 3; // Maker's input token account
-    #[allow(dead_code)]
-    pub const TAKER_OUTPUT_ATA: usize = 4; // Taker's output token account (receives GM tokens)
-    pub const MAKER_OUTPUT_ATA: usize = 5; // Maker's output token account (receives USDC)
-    #[allow(dead_code)]
-    pub const INPUT_MINT: usize = 6; // Input token mint
-    #[allow(dead_code)]
-    pub const INPUT_TOKEN_PROGRAM: usize = 7; // Input token program
-    pub const OUTPUT_MINT: usize = 8; // Output token mint (GM token)
+/// Jupiter Order Engine (with each of `fill_layouts`) comes first, followed by the
+/// config's `extra_fill_venues`.
+pub fn fill_venues() -> impl Iterator<Item = FillVenue> {
+    let config = global_config();
+    fill_layouts()
+        .map(|layout| FillVenue {
+            program_id: config.jupiter_order_engine_program_id,
+            layout: *layout,
+        })
+        .chain(config.extra_fill_venues.iter().copied())
 }
 
 /// Check if an instruction is a Jupiter Order Engine fill
@@ -43,93 +64,588 @@ pub fn is_jupiter_fill_instruction(
     program_id: &Pubkey,
     account_keys: &[Pubkey],
 ) -> bool {
-    // Check program ID matches Jupiter Order Engine
-    let ix_program_id = account_keys
-        .get(instruction.program_id_index as usize)
-        .cloned();
+    fill_layout_for_instruction(instruction, program_id, account_keys).is_some()
+}
 
-    if ix_program_id != Some(*program_id) {
-        return false;
-    }
+/// The layout of a Jupiter Order Engine fill instruction
+///
+/// Returns the matched layout from `fill_layouts`, or `None` if the instruction is for
+/// another program or has an unrecognized discriminator.
+pub fn fill_layout_for_instruction(
+    instruction: &CompiledInstruction,
+    program_id: &Pubkey,
+    account_keys: &[Pubkey],
+) -> Option<&'static FillLayout> {
+    crate::core::fill_layout_for_program(
+        instruction.program_id_index,
+        &instruction.data,
+        program_id,
+        account_keys,
+        fill_layouts(),
+    )
+}
+
+/// Check if an instruction is a non-idempotent `createAssociatedTokenAccount`
+///
+/// The associated token account program treats empty data or a `0` tag as `Create`,
+/// which fails if the ATA already exists; `1` is `CreateIdempotent`.
+pub fn is_nonidempotent_ata_create(
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+) -> bool {
+    account_keys.get(instruction.program_id_index as usize)
+        == Some(&spl_associated_token_account::id())
+        && matches!(instruction.data.first(), None | Some(0))
+}
 
-    // Check discriminator
-    if instruction.data.len() < 8 {
-        return false;
+/// Whether the static account key at `index` is writable according to `header`
+///
+/// Keys are ordered writable signers, readonly signers, writable non-signers, then
+/// readonly non-signers.
+fn is_writable_index(header: &MessageHeader, num_keys: usize, index: usize) -> bool {
+    let num_signers = header.num_required_signatures as usize;
+    if index < num_signers {
+        index < num_signers.saturating_sub(header.num_readonly_signed_accounts as usize)
+    } else {
+        let num_writable_unsigned = num_keys
+            .saturating_sub(num_signers)
+            .saturating_sub(header.num_readonly_unsigned_accounts as usize);
+        index - num_signers < num_writable_unsigned
     }
+}
+
+/// Decode a fill's amounts and expiry without any GM-specific checks
+///
+/// Works for any Jupiter fill, whatever its maker or mints, so non-GM fills can be
+/// logged too. The layout is selected from the instruction's discriminator.
+///
+/// Returns `Err(NotJupiterFill)` if the discriminator is not a known fill variant.
+pub fn decode_fill_args(instruction: &CompiledInstruction) -> Result<FillArgs, GmSimulatorError> {
+    let layout = fill_layout(&instruction.data).ok_or(GmSimulatorError::NotJupiterFill)?;
+    decode_fill_args_with_layout(&instruction.data, layout)
+}
+
+/// Decode fill arguments from instruction data at `layout`'s offsets
+fn decode_fill_args_with_layout(
+    data: &[u8],
+    layout: &FillLayout,
+) -> Result<FillArgs, GmSimulatorError> {
+    let amounts = crate::core::parse_fill_amounts(data, layout).map_err(core_error)?;
 
-    let ix_discriminator = instruction_discriminator("fill");
+    Ok(FillArgs {
+        input_amount: RawAmount(amounts.input_amount),
+        output_amount: RawAmount(amounts.output_amount),
+        expire_at: amounts.expire_at,
+        raw_tail: data[layout.min_data_len()..].to_vec(),
+    })
+}
 
-    ix_discriminator == instruction.data[..8]
+/// The crate error for a `core` parse error
+fn core_error(error: CoreError) -> GmSimulatorError {
+    match error {
+        CoreError::DataTooShort { .. } => {
+            GmSimulatorError::InstructionParseError(error.to_string())
+        }
+        CoreError::InvalidAccountIndex => GmSimulatorError::InvalidAccountIndex,
+        CoreError::MissingAccount => GmSimulatorError::MissingAccount,
+    }
 }
 
 /// Parse a Jupiter Order Engine fill instruction and extract GM trade info
 ///
-/// Returns Ok(Some(GmTradeInfo)) if this is a valid GM trade
+/// The argument layout is selected from the instruction's discriminator, falling
+/// back to `FILL` if it is not a known fill variant.
+///
+/// Without the message header the keys' writability is unknown, so, as in
+/// `check_gm_trade_raw`, the check that the fill's mints are readonly is skipped. Use
+/// `parse_fill_for_gm_trade_with_header` to include it.
+///
+/// Returns Ok(Some(GmTradeInfo)) if this is a valid GM trade: a BUY (the taker receives
+/// a GM token for a stablecoin) or a SELL (the taker sends a GM token for a stablecoin),
+/// see `direction`
 /// Returns Ok(None) if this is a Jupiter fill but not a GM trade
 /// Returns Err if parsing fails
 pub fn parse_fill_for_gm_trade(
     instruction: &CompiledInstruction,
     account_keys: &[Pubkey],
 ) -> Result<Option<GmTradeInfo>, GmSimulatorError> {
-    // Validate instruction data length
-    // Discriminator (8) + input_amount (8) + output_amount (8) + expire_at (8) = 32 minimum
-    if instruction.data.len() < 32 {
-        return Err(GmSimulatorError::InstructionParseError(format!(
-            "Instruction data too short: expected at least 32 bytes, got {}",
-            instruction.data.len()
-        )));
-    }
-
-    // Helper to get account pubkey from instruction accounts
-    let get_account = |idx: usize| -> Result<Pubkey, GmSimulatorError> {
-        let account_idx = instruction
-            .accounts
-            .get(idx)
-            .ok_or(GmSimulatorError::InvalidAccountIndex)?;
-        account_keys
-            .get(*account_idx as usize)
-            .cloned()
-            .ok_or(GmSimulatorError::MissingAccount)
-    };
+    parse_fill_for_gm_trade_with_header(instruction, account_keys, &readonly_header(account_keys))
+}
+
+/// Same as `parse_fill_for_gm_trade`, also checking the fill's mints are readonly
+///
+/// `header` is the message header of `account_keys`; the input and output mints must
+/// be readonly in it, as in the real Jupiter layout.
+pub fn parse_fill_for_gm_trade_with_header(
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+    header: &MessageHeader,
+) -> Result<Option<GmTradeInfo>, GmSimulatorError> {
+    let layout = fill_layout(&instruction.data).unwrap_or(&FILL);
+    parse_fill_with_layout(instruction, account_keys, header, layout)
+}
+
+/// A header that marks every key readonly, for instructions without their message
+///
+/// Writability is unknown, so no fill mint is rejected as writable.
+pub(crate) fn readonly_header(account_keys: &[Pubkey]) -> MessageHeader {
+    MessageHeader {
+        num_required_signatures: 0,
+        num_readonly_signed_accounts: 0,
+        num_readonly_unsigned_accounts: u8::try_from(account_keys.len()).unwrap_or(u8::MAX),
+    }
+}
+
+/// Parse a fill instruction using the layout returned by `fill_layout_for_instruction`
+pub fn parse_fill_with_layout(
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+    header: &MessageHeader,
+    layout: &FillLayout,
+) -> Result<Option<GmTradeInfo>, GmSimulatorError> {
+    classify_fill(instruction, account_keys, header, layout).map(Result::ok)
+}
 
-    // Extract accounts
-    let maker = get_account(account_indices::MAKER)?;
-    let taker = get_account(account_indices::TAKER)?;
-    let maker_output_account = get_account(account_indices::MAKER_OUTPUT_ATA)?;
-    let output_mint = get_account(account_indices::OUTPUT_MINT)?;
+/// `parse_fill_with_layout`, with why a fill that isn't a GM trade was passed over
+///
+/// Returns `Ok(Err(reason))` where `parse_fill_with_layout` returns `Ok(None)`.
+pub(crate) fn classify_fill(
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+    header: &MessageHeader,
+    layout: &FillLayout,
+) -> Result<Result<GmTradeInfo, NoBundleReason>, GmSimulatorError> {
+    // Validate instruction data length and decode the arguments
+    let args = decode_fill_args_with_layout(&instruction.data, layout)?;
+
+    // Extract accounts; extra accounts (e.g. a referral account) shift the indices after the maker
+    let accounts = crate::core::parse_fill_accounts(&instruction.accounts, account_keys)
+        .map_err(core_error)?;
+    let offset = accounts.offset;
+    let maker = *accounts.maker;
+    let taker = *accounts.taker;
+    let maker_output_account = *accounts.maker_output_account;
+    let input_mint = *accounts.input_mint;
+    let output_mint = *accounts.output_mint;
+    let input_token_program = *accounts.input_token_program;
+    // Some fills omit the trailing output token program; GM tokens are Token-2022
+    let output_token_program = accounts
+        .output_token_program
+        .copied()
+        .unwrap_or_else(token_2022_program_id);
+
+    // Check 0: Mints are readonly in the real layout; a writable one is some other account
+    for (name, idx) in [
+        ("input", account_indices::INPUT_MINT),
+        ("output", account_indices::OUTPUT_MINT),
+    ] {
+        let account_idx = *instruction
+            .accounts
+            .get(shifted_account_index(idx, offset))
+            .ok_or(GmSimulatorError::InvalidAccountIndex)? as usize;
+        if is_writable_index(header, account_keys.len(), account_idx) {
+            return Err(GmSimulatorError::InstructionParseError(format!(
+                "Fill {} mint {} is writable",
+                name, account_keys[account_idx]
+            )));
+        }
+    }
 
     // Check 1: Is maker an authorized solver?
     if !is_authorized_solver(&maker) {
+        if !global_config().unauthorized_maker_is_error {
+            return Ok(Err(NoBundleReason::UnauthorizedMaker)); // An ordinary non-GM fill
+        }
         return Err(GmSimulatorError::UnauthorizedMaker(maker));
     }
 
-    // Check 2: Is output_mint (what taker receives) a GM token?
-    if !is_gm_token(&output_mint) {
-        return Ok(None); // Valid Jupiter fill, but not a GM trade
-    }
+    // Check 2: Is the taker buying a GM token with a stablecoin (USDC, USDT or USDON),
+    // or selling one for a stablecoin? Balance tracking reports the quote leg with
+    // stablecoin decimals, so other quotes aren't GM trades.
+    let (direction, gm_token_mint, gm_token_amount, quote_mint) = if is_gm_token(&output_mint) {
+        if !is_stable_mint(&input_mint) {
+            return Ok(Err(NoBundleReason::QuoteNotStable));
+        }
+        (
+            TradeDirection::Buy,
+            output_mint,
+            args.output_amount,
+            input_mint,
+        )
+    } else if is_stable_mint(&output_mint) && is_gm_token(&input_mint) {
+        (
+            TradeDirection::Sell,
+            input_mint,
+            args.input_amount,
+            output_mint,
+        )
+    } else if is_gm_token(&input_mint) {
+        // Valid Jupiter fill selling a GM token, but not for a stablecoin
+        return Ok(Err(NoBundleReason::SellDirection));
+    } else {
+        return Ok(Err(NoBundleReason::OutputNotGm)); // Valid Jupiter fill, but not a GM trade
+    };
 
-    // Parse fill instruction arguments
-    // Data layout: discriminator (8) + input_amount (8) + output_amount (8) + expire_at (8)
-    let output_amount = u64::from_le_bytes(instruction.data[16..24].try_into().map_err(|_| {
-        GmSimulatorError::InstructionParseError("Invalid output amount".to_string())
-    })?);
+    // Checks 3-5 guard the mock mint, which only a BUY needs
+    if direction == TradeDirection::Buy {
+        // Check 3: A solver filling its own order would make the mock mint and balance tracking meaningless
+        if taker == maker {
+            return Err(GmSimulatorError::TakerMakerSame(maker));
+        }
 
-    let expire_at = i64::from_le_bytes(instruction.data[24..32].try_into().map_err(|_| {
-        GmSimulatorError::InstructionParseError("Invalid expire_at timestamp".to_string())
-    })?);
+        // Check 4: The mock mint pays for ATAs and signs as the admin minter, so the minter
+        // being a trade party would make its accounts collide with the fill's
+        if let Some(party) = [taker, maker].into_iter().find(is_admin_minter) {
+            return Err(GmSimulatorError::AdminMinterInTrade(party));
+        }
 
-    // Get GM token symbol
-    let gm_token_symbol = get_gm_token_symbol(&output_mint)
-        .unwrap_or("GM")
-        .to_string();
+        // Check 5: A zero-amount fill would mock mint nothing, so its simulation is meaningless
+        if gm_token_amount.0 == 0 {
+            return Err(GmSimulatorError::ZeroAmount);
+        }
+    }
 
-    Ok(Some(GmTradeInfo {
+    // Get GM token symbol. `is_gm_token` and `get_gm_token_symbol` read the same list,
+    // so the fallback means the token list is inconsistent (constants.rs tests guard this)
+    let gm_token_symbol = match get_gm_token_symbol(&gm_token_mint) {
+        Some(symbol) => symbol.to_string(),
+        None => {
+            log::warn!(
+                "GM mint {} has no symbol in the token list, using \"GM\"",
+                gm_token_mint
+            );
+            "GM".to_string()
+        }
+    };
+
+    Ok(Ok(GmTradeInfo {
         maker,
         taker,
-        gm_token_mint: output_mint,
+        gm_token_mint,
         gm_token_symbol,
-        gm_token_amount: output_amount,
+        gm_token_amount,
+        input_amount: args.input_amount,
         maker_output_account,
-        expire_at,
+        expire_at: args.expire_at,
+        input_token_program,
+        output_token_program,
+        direction,
+        quote_mint,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{fill_account_offset, output_token_program},
+        instruction_discriminator, jupiter_order_engine_program_id,
+        test_utils::{fill_instruction, sample_trade_info},
+    };
+    use solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        message::Message,
+    };
+
+    #[test]
+    fn test_fill_discriminators_match_anchor() {
+        for layout in KNOWN_FILL_LAYOUTS {
+            assert_eq!(layout.discriminator, instruction_discriminator(layout.name));
+        }
+    }
+
+    #[test]
+    fn test_fill_venues_default() {
+        let venues: Vec<FillVenue> = fill_venues().collect();
+
+        // Only Jupiter's `fill` is registered by default; `fill_v2` is unverified
+        assert_eq!(
+            venues,
+            vec![FillVenue {
+                program_id: jupiter_order_engine_program_id(),
+                layout: FILL,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_is_jupiter_fill_instruction_layouts() {
+        let jupiter = jupiter_order_engine_program_id();
+        let account_keys = [Pubkey::new_unique(), jupiter];
+        let instruction = |program_id_index: u8, discriminator: [u8; 8]| {
+            let mut data = discriminator.to_vec();
+            data.extend_from_slice(&[0; 24]);
+            CompiledInstruction::new_from_raw_parts(program_id_index, data, vec![])
+        };
+
+        let fill = instruction(1, FILL.discriminator);
+        assert!(is_jupiter_fill_instruction(&fill, &jupiter, &account_keys));
+        assert_eq!(
+            fill_layout_for_instruction(&fill, &jupiter, &account_keys),
+            Some(&FILL)
+        );
+
+        // `fill_v2` needs `accept_fill_v2`, which is off by default
+        let fill_v2 = instruction(1, FILL_V2.discriminator);
+        assert!(!is_jupiter_fill_instruction(
+            &fill_v2,
+            &jupiter,
+            &account_keys
+        ));
+        assert_eq!(
+            fill_layout_for_instruction(&fill_v2, &jupiter, &account_keys),
+            None
+        );
+
+        let other = instruction(1, instruction_discriminator("cancel"));
+        assert!(!is_jupiter_fill_instruction(
+            &other,
+            &jupiter,
+            &account_keys
+        ));
+        let wrong_program = instruction(0, FILL.discriminator);
+        assert!(!is_jupiter_fill_instruction(
+            &wrong_program,
+            &jupiter,
+            &account_keys
+        ));
+        assert_eq!(
+            fill_layout_for_instruction(&wrong_program, &jupiter, &account_keys),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_nonidempotent_ata_create() {
+        let account_keys = [Pubkey::new_unique(), spl_associated_token_account::id()];
+        let ix = |program_id_index: u8, data: Vec<u8>| {
+            CompiledInstruction::new_from_raw_parts(program_id_index, data, vec![])
+        };
+
+        assert!(is_nonidempotent_ata_create(&ix(1, vec![]), &account_keys));
+        assert!(is_nonidempotent_ata_create(&ix(1, vec![0]), &account_keys));
+        assert!(!is_nonidempotent_ata_create(&ix(1, vec![1]), &account_keys));
+        assert!(!is_nonidempotent_ata_create(&ix(0, vec![]), &account_keys));
+    }
+
+    /// Parse the first instruction of a message holding only `ix`, paid for by its taker
+    fn parse(ix: Instruction) -> Result<Option<GmTradeInfo>, GmSimulatorError> {
+        let message = Message::new(std::slice::from_ref(&ix), Some(&ix.accounts[0].pubkey));
+        parse_fill_for_gm_trade_with_header(
+            &message.instructions[0],
+            &message.account_keys,
+            &message.header,
+        )
+    }
+
+    #[test]
+    fn test_parse_fill_rejects_writable_mints() {
+        let trade_info = sample_trade_info();
+        let fill = fill_instruction(&trade_info, 200_000_000);
+        assert!(parse(fill.clone()).unwrap().is_some());
+
+        let mut writable = fill;
+        writable.accounts[account_indices::OUTPUT_MINT].is_writable = true;
+        assert!(matches!(
+            parse(writable.clone()),
+            Err(GmSimulatorError::InstructionParseError(msg)) if msg.contains("output mint")
+        ));
+
+        // Without the header, writability is unknown and the check is skipped
+        let message = Message::new(&[writable], Some(&trade_info.taker));
+        assert!(
+            parse_fill_for_gm_trade(&message.instructions[0], &message.account_keys)
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_parse_fill_rejects_admin_minter_party() {
+        use crate::constants::admin_minter;
+
+        let trade_info = sample_trade_info();
+        let parse_with = |taker: Pubkey, maker: Pubkey| {
+            parse(fill_instruction(
+                &GmTradeInfo {
+                    taker,
+                    maker,
+                    ..trade_info.clone()
+                },
+                200_000_000,
+            ))
+        };
+
+        assert!(parse_with(trade_info.taker, trade_info.maker)
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            parse_with(admin_minter(), trade_info.maker),
+            Err(GmSimulatorError::AdminMinterInTrade(admin_minter()))
+        );
+        // The admin minter is never an authorized solver
+        assert_eq!(
+            parse_with(trade_info.taker, admin_minter()),
+            Err(GmSimulatorError::UnauthorizedMaker(admin_minter()))
+        );
+    }
+
+    #[test]
+    fn test_parse_fill_with_extra_fee_account() {
+        let trade_info = GmTradeInfo {
+            maker_output_account: Pubkey::new_unique(),
+            ..sample_trade_info()
+        };
+        let mut fill = fill_instruction(&trade_info, 200_000_000);
+        // A referral fee account right after the maker
+        fill.accounts
+            .insert(2, AccountMeta::new(Pubkey::new_unique(), false));
+
+        let message = Message::new(&[fill], Some(&trade_info.taker));
+        let instruction = &message.instructions[0];
+        assert_eq!(
+            fill_account_offset(&instruction.accounts, &message.account_keys),
+            1
+        );
+
+        let info = parse_fill_for_gm_trade_with_header(
+            instruction,
+            &message.account_keys,
+            &message.header,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(info, trade_info);
+    }
+
+    #[test]
+    fn test_output_token_program_layouts() {
+        use crate::constants::spl_token_program_id;
+        use solana_system_interface::program as system_program;
+
+        let trade_info = sample_trade_info();
+
+        // Parse a BUY fill whose accounts after the output mint are `trailing`
+        let parse_with = |trailing: &[Pubkey]| {
+            let mut fill = fill_instruction(&trade_info, 200_000_000);
+            fill.accounts.truncate(account_indices::OUTPUT_MINT + 1);
+            fill.accounts.extend(
+                trailing
+                    .iter()
+                    .map(|key| AccountMeta::new_readonly(*key, false)),
+            );
+            let message = Message::new(&[fill], Some(&trade_info.taker));
+            let instruction = &message.instructions[0];
+            let detected =
+                output_token_program(&instruction.accounts, &message.account_keys, 0).copied();
+            let info = parse_fill_for_gm_trade_with_header(
+                instruction,
+                &message.account_keys,
+                &message.header,
+            )
+            .unwrap()
+            .unwrap();
+            (detected, info.output_token_program)
+        };
+
+        // 9 accounts (mock layout): no output token program, Token-2022 is assumed
+        assert_eq!(parse_with(&[]), (None, token_2022_program_id()));
+
+        // 11 accounts (mainnet layout): output_token_program, system_program
+        let token_2022 = token_2022_program_id();
+        assert_eq!(
+            parse_with(&[token_2022, system_program::id()]),
+            (Some(token_2022), token_2022)
+        );
+        let spl_token = spl_token_program_id();
+        assert_eq!(
+            parse_with(&[spl_token, system_program::id()]),
+            (Some(spl_token), spl_token)
+        );
+
+        // Another account ahead of the output token program
+        assert_eq!(
+            parse_with(&[system_program::id(), spl_token]),
+            (Some(spl_token), spl_token)
+        );
+    }
+
+    #[test]
+    fn test_parse_fill_detects_gm_sell() {
+        use crate::constants::spl_token_program_id;
+
+        // The taker sends the GM token and receives the quote mint
+        let sell = GmTradeInfo {
+            direction: TradeDirection::Sell,
+            input_token_program: token_2022_program_id(),
+            output_token_program: spl_token_program_id(),
+            ..sample_trade_info()
+        };
+
+        let info = parse(fill_instruction(&sell, 200_000_000))
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.direction, TradeDirection::Sell);
+        assert_eq!(info.gm_token_mint, sell.gm_token_mint);
+        assert_eq!(info.gm_token_symbol, "AAPLon");
+        // The GM amount is what the taker sends
+        assert_eq!(info.gm_token_amount.0, 1_500_000_000);
+        assert_eq!(info.input_amount, info.gm_token_amount);
+
+        // Selling a GM token for anything but an accepted stablecoin is not a recognized GM trade
+        let other = GmTradeInfo {
+            quote_mint: Pubkey::new_unique(),
+            ..sell
+        };
+        assert_eq!(parse(fill_instruction(&other, 200_000_000)), Ok(None));
+    }
+
+    #[test]
+    fn test_decode_fill_args() {
+        // A non-GM fill from an unknown maker still decodes
+        let mut data = FILL.discriminator.to_vec();
+        data.extend_from_slice(&200_000_000u64.to_le_bytes());
+        data.extend_from_slice(&5_000_000u64.to_le_bytes());
+        data.extend_from_slice(&1704067200i64.to_le_bytes());
+        let fill = CompiledInstruction::new_from_raw_parts(0, data.clone(), vec![]);
+
+        assert_eq!(
+            decode_fill_args(&fill),
+            Ok(FillArgs {
+                input_amount: RawAmount(200_000_000),
+                output_amount: RawAmount(5_000_000),
+                expire_at: 1704067200,
+                raw_tail: vec![],
+            })
+        );
+
+        // Trailing args past expire_at are kept rather than dropped
+        let mut extended = data.clone();
+        extended.extend_from_slice(&4_900_000u64.to_le_bytes());
+        extended.push(1);
+        let args = decode_fill_args(&CompiledInstruction::new_from_raw_parts(
+            0,
+            extended,
+            vec![],
+        ))
+        .unwrap();
+        assert_eq!(args.expire_at, 1704067200);
+        assert_eq!(args.raw_tail.len(), 9);
+        assert_eq!(args.tail_u64(0), Some(4_900_000));
+        assert_eq!(args.tail_u64(2), None);
+        assert_eq!(args.tail_u64(usize::MAX), None);
+
+        let short = CompiledInstruction::new_from_raw_parts(0, data[..24].to_vec(), vec![]);
+        assert!(matches!(
+            decode_fill_args(&short),
+            Err(GmSimulatorError::InstructionParseError(_))
+        ));
+
+        data[..8].copy_from_slice(&instruction_discriminator("cancel"));
+        let other = CompiledInstruction::new_from_raw_parts(0, data, vec![]);
+        assert_eq!(
+            decode_fill_args(&other),
+            Err(GmSimulatorError::NotJupiterFill)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Prometheus text exposition of detection metrics.
+//!
+//! Renders a `DetectionMetrics` snapshot as the body of a scrape endpoint, for
+//! monitoring sidecars that don't want a full metrics runtime.
+
+use std::fmt::Write;
+
+use crate::{
+    constants::{
+        DETECTION_LATENCY_BUCKETS, METRIC_DETECTED_BUY, METRIC_DETECTED_NON_GM,
+        METRIC_DETECTED_SELL, METRIC_DETECTED_UNAUTHORIZED, METRIC_DETECTION_LATENCY,
+    },
+    types::DetectionMetrics,
+};
+
+/// Render detection metrics in the Prometheus text exposition format
+///
+/// Emits one counter per detection outcome, named like the `metrics` feature's
+/// counters, and a `gm_simulator_detection_latency_seconds` histogram over
+/// `DETECTION_LATENCY_BUCKETS`. The output ends with a newline and can be served
+/// as-is with content type `text/plain; version=0.0.4`.
+///
+/// # Arguments
+///
+/// * `snapshot` - The counts to render
+pub fn render_metrics_text(snapshot: &DetectionMetrics) -> String {
+    let counters = [
+        (METRIC_DETECTED_BUY, "GM BUY fills detected", snapshot.buy),
+        (
+            METRIC_DETECTED_SELL,
+            "Authorized fills selling a GM token",
+            snapshot.sell,
+        ),
+        (
+            METRIC_DETECTED_NON_GM,
+            "Transactions that are not GM trades",
+            snapshot.non_gm,
+        ),
+        (
+            METRIC_DETECTED_UNAUTHORIZED,
+            "Fills from an unauthorized maker",
+            snapshot.unauthorized,
+        ),
+    ];
+
+    // Writing to a String can't fail
+    let mut out = String::new();
+    for (name, help, value) in counters {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} counter", name);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+
+    let name = METRIC_DETECTION_LATENCY;
+    let _ = writeln!(
+        out,
+        "# HELP {} Time to check a transaction for a GM trade",
+        name
+    );
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    for (bound, count) in DETECTION_LATENCY_BUCKETS
+        .iter()
+        .zip(&snapshot.latency_buckets)
+    {
+        let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, count);
+    }
+    let _ = writeln!(
+        out,
+        "{}_bucket{{le=\"+Inf\"}} {}",
+        name, snapshot.latency_count
+    );
+    let _ = writeln!(out, "{}_sum {}", name, snapshot.latency_sum_seconds);
+    let _ = writeln!(out, "{}_count {}", name, snapshot.latency_count);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{GmCheckResult, GmSimulatorError, NoBundleReason};
+    use solana_sdk::pubkey::Pubkey;
+    use std::time::Duration;
+
+    #[test]
+    fn test_render_metrics_text() {
+        let mut metrics = DetectionMetrics::default();
+        metrics.record(
+            &Ok(GmCheckResult::not_gm_trade_because(
+                NoBundleReason::SellDirection,
+            )),
+            Duration::from_micros(3),
+        );
+        metrics.record(
+            &Ok(GmCheckResult::not_gm_trade_because(
+                NoBundleReason::NotAFill,
+            )),
+            Duration::from_micros(20),
+        );
+        metrics.record(
+            &Err(GmSimulatorError::UnauthorizedMaker(Pubkey::new_unique())),
+            Duration::from_millis(50),
+        );
+        // Malformed transactions are timed but not counted
+        metrics.record(
+            &Err(GmSimulatorError::EmptyTransaction),
+            Duration::from_micros(1),
+        );
+
+        let text = render_metrics_text(&metrics);
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert!(text.ends_with('\n'));
+        assert!(lines.contains(&"# TYPE gm_simulator_detected_buy counter"));
+        assert!(lines.contains(&"gm_simulator_detected_buy 0"));
+        assert!(lines.contains(&"gm_simulator_detected_sell 1"));
+        assert!(lines.contains(&"gm_simulator_detected_non_gm 1"));
+        assert!(lines.contains(&"gm_simulator_detected_unauthorized 1"));
+
+        assert!(lines.contains(&"# TYPE gm_simulator_detection_latency_seconds histogram"));
+        assert!(lines.contains(&"gm_simulator_detection_latency_seconds_bucket{le=\"0.000001\"} 1"));
+        assert!(lines.contains(&"gm_simulator_detection_latency_seconds_bucket{le=\"0.000005\"} 2"));
+        assert!(lines.contains(&"gm_simulator_detection_latency_seconds_bucket{le=\"0.01\"} 3"));
+        assert!(lines.contains(&"gm_simulator_detection_latency_seconds_bucket{le=\"+Inf\"} 4"));
+        assert!(lines.contains(&"gm_simulator_detection_latency_seconds_count 4"));
+        let sum = lines
+            .iter()
+            .find_map(|line| line.strip_prefix("gm_simulator_detection_latency_seconds_sum "))
+            .unwrap();
+        assert!((sum.parse::<f64>().unwrap() - 0.050024).abs() < 1e-9);
+    }
+}
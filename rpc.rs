@@ -0,0 +1,590 @@
+//! Minimal JSON-RPC helpers for reading on-chain state.
+//!
+//! These use the same blocking `reqwest` client as bundle simulation, so callers
+//! don't need a full `solana-client` dependency.
+
+use solana_sdk::{
+    hash::Hash, pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction,
+};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    state::Mint,
+};
+use std::str::FromStr;
+
+use crate::constants::{spl_token_program_id, token_2022_program_id, ONDO_GM_PROGRAM_ID};
+use crate::mint_instruction::{mint_authority_account, oracle_sanity_check_account};
+use crate::types::{GmSimulatorError, OracleSanityCheck};
+
+/// Get the token program that owns a mint account.
+///
+/// Fetches the mint with `getAccountInfo` and returns its owner, which must be
+/// SPL Token or Token-2022. Use `known_token_program_for_mint` as a fallback when
+/// no RPC is available.
+///
+/// # Arguments
+///
+/// * `mint` - The token mint address
+/// * `rpc_url` - A Solana RPC URL
+pub fn token_program_for_mint(mint: &Pubkey, rpc_url: &str) -> Result<Pubkey, GmSimulatorError> {
+    let json = get_account_info(mint, rpc_url)?;
+    parse_mint_owner(&json, mint)
+}
+
+/// Fetch and decode the `oracle_sanity_check` account for a GM token mint.
+///
+/// `mint_gm` fails unless this account holds a fresh oracle price, so callers can
+/// check `OracleSanityCheck::is_stale` before simulating a mock mint.
+///
+/// # Arguments
+///
+/// * `mint` - The GM token mint address
+/// * `rpc_url` - A Solana RPC URL
+pub fn fetch_oracle_sanity_check(
+    mint: &Pubkey,
+    rpc_url: &str,
+) -> Result<OracleSanityCheck, GmSimulatorError> {
+    let json = get_account_info(&oracle_sanity_check_account(mint), rpc_url)?;
+    parse_oracle_sanity_check(&json)
+}
+
+/// Check that a mint's authority is the Ondo GM program's `mint_authority` PDA.
+///
+/// `mint_gm` can only mint tokens whose mint authority is that PDA, so a mock mint
+/// for any other mint fails. Fetches the mint and compares its `mint_authority` to
+/// `mint_authority_account()`; a mint without an authority is reported as `false`.
+///
+/// # Arguments
+///
+/// * `mint` - The GM token mint address
+/// * `rpc_url` - A Solana RPC URL
+pub fn verify_mint_authority(mint: &Pubkey, rpc_url: &str) -> Result<bool, GmSimulatorError> {
+    let json = get_account_info(mint, rpc_url)?;
+    parse_mint_authority(&json, mint).map(|authority| authority == Some(mint_authority_account()))
+}
+
+/// Get the extensions an associated token account for a mint is created with.
+///
+/// Token-2022 mints with extensions such as `TransferFeeConfig` or `TransferHook`
+/// require matching account extensions. The associated token account program sizes
+/// and initializes those itself, so the idempotent ATA creates in
+/// `build_mock_mint_transaction` already produce usable ATAs; this reports what they
+/// will carry, e.g. to check a mint before simulating or to price the ATA's rent.
+/// Token-2022 ATAs always include `ImmutableOwner`; SPL Token ATAs have none.
+///
+/// # Arguments
+///
+/// * `mint` - The token mint address
+/// * `rpc_url` - A Solana RPC URL
+pub fn fetch_ata_extensions(
+    mint: &Pubkey,
+    rpc_url: &str,
+) -> Result<Vec<ExtensionType>, GmSimulatorError> {
+    let json = get_account_info(mint, rpc_url)?;
+    parse_ata_extensions(&json, mint)
+}
+
+/// Fetch a confirmed transaction by signature with `getTransaction`
+///
+/// Legacy and v0 transactions are both returned, as `VersionedTransaction`s.
+pub(crate) fn fetch_transaction(
+    signature: &Signature,
+    rpc_url: &str,
+) -> Result<VersionedTransaction, GmSimulatorError> {
+    let json = rpc_request(
+        "getTransaction",
+        serde_json::json!([
+            signature.to_string(),
+            {
+                "encoding": "base64",
+                "commitment": "confirmed",
+                "maxSupportedTransactionVersion": 0
+            }
+        ]),
+        rpc_url,
+    )?;
+    parse_transaction_response(&json, signature)
+}
+
+/// Fetch the latest blockhash with `getLatestBlockhash`
+pub(crate) fn fetch_latest_blockhash(rpc_url: &str) -> Result<Hash, GmSimulatorError> {
+    let json = rpc_request("getLatestBlockhash", serde_json::json!([]), rpc_url)?;
+    parse_latest_blockhash(&json)
+}
+
+/// Send a base64 `getAccountInfo` request and return the JSON response
+fn get_account_info(
+    account: &Pubkey,
+    rpc_url: &str,
+) -> Result<serde_json::Value, GmSimulatorError> {
+    rpc_request(
+        "getAccountInfo",
+        serde_json::json!([account.to_string(), { "encoding": "base64" }]),
+        rpc_url,
+    )
+}
+
+/// Whether the node at `rpc_url` answers `getHealth` with "ok"
+#[cfg(feature = "test-validator")]
+pub(crate) fn is_healthy(rpc_url: &str) -> bool {
+    rpc_request("getHealth", serde_json::json!([]), rpc_url)
+        .is_ok_and(|json| rpc_result(&json).is_ok_and(|result| result == "ok"))
+}
+
+/// Send a JSON-RPC request and return the JSON response
+fn rpc_request(
+    method: &str,
+    params: serde_json::Value,
+    rpc_url: &str,
+) -> Result<serde_json::Value, GmSimulatorError> {
+    let request_body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params
+    });
+
+    let client = reqwest::blocking::Client::new();
+    let response = client
+        .post(rpc_url)
+        .header("Content-Type", "application/json")
+        .json(&request_body)
+        .send()
+        .map_err(|e| {
+            GmSimulatorError::InstructionParseError(format!("HTTP request failed: {}", e))
+        })?;
+
+    response.json().map_err(|e| {
+        GmSimulatorError::InstructionParseError(format!("Failed to parse JSON: {}", e))
+    })
+}
+
+/// Return the non-null `result.value` of a `getAccountInfo` response
+fn account_value(json: &serde_json::Value) -> Result<&serde_json::Value, GmSimulatorError> {
+    if let Some(error) = json.get("error") {
+        return Err(GmSimulatorError::InstructionParseError(format!(
+            "RPC error: {}",
+            error
+        )));
+    }
+
+    json.get("result")
+        .and_then(|r| r.get("value"))
+        .filter(|v| !v.is_null())
+        .ok_or(GmSimulatorError::MissingAccount)
+}
+
+/// Return the `result` of a JSON-RPC response, or its error
+fn rpc_result(json: &serde_json::Value) -> Result<&serde_json::Value, GmSimulatorError> {
+    if let Some(error) = json.get("error") {
+        return Err(GmSimulatorError::InstructionParseError(format!(
+            "RPC error: {}",
+            error
+        )));
+    }
+
+    json.get("result").ok_or_else(|| {
+        GmSimulatorError::InstructionParseError("Missing result in response".to_string())
+    })
+}
+
+/// Decode the transaction from a base64 `getTransaction` response
+fn parse_transaction_response(
+    json: &serde_json::Value,
+    signature: &Signature,
+) -> Result<VersionedTransaction, GmSimulatorError> {
+    let result = rpc_result(json)?;
+    if result.is_null() {
+        return Err(GmSimulatorError::TransactionDecodeError(format!(
+            "Transaction {} not found",
+            signature
+        )));
+    }
+
+    let encoded = result
+        .get("transaction")
+        .and_then(|t| t.get(0))
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| {
+            GmSimulatorError::TransactionDecodeError("Missing transaction data".to_string())
+        })?;
+    crate::transaction::decode_transaction(encoded)
+}
+
+/// Read the blockhash from a `getLatestBlockhash` response
+fn parse_latest_blockhash(json: &serde_json::Value) -> Result<Hash, GmSimulatorError> {
+    rpc_result(json)?
+        .get("value")
+        .and_then(|v| v.get("blockhash"))
+        .and_then(|b| b.as_str())
+        .and_then(|b| Hash::from_str(b).ok())
+        .ok_or_else(|| GmSimulatorError::InstructionParseError("Missing blockhash".to_string()))
+}
+
+/// Extract and validate the owner program from a `getAccountInfo` response
+fn parse_mint_owner(json: &serde_json::Value, mint: &Pubkey) -> Result<Pubkey, GmSimulatorError> {
+    let value = account_value(json)?;
+
+    let owner = value
+        .get("owner")
+        .and_then(|o| o.as_str())
+        .and_then(|o| Pubkey::from_str(o).ok())
+        .ok_or_else(|| {
+            GmSimulatorError::InstructionParseError("Missing account owner".to_string())
+        })?;
+
+    if owner != spl_token_program_id() && owner != token_2022_program_id() {
+        return Err(GmSimulatorError::InstructionParseError(format!(
+            "Mint {} is owned by {}, not a token program",
+            mint, owner
+        )));
+    }
+
+    Ok(owner)
+}
+
+/// Decode the base64 data of a `getAccountInfo` response's account
+fn account_data(value: &serde_json::Value) -> Result<Vec<u8>, GmSimulatorError> {
+    use base64::Engine;
+
+    value
+        .get("data")
+        .and_then(|d| d.get(0))
+        .and_then(|d| d.as_str())
+        .and_then(|d| base64::engine::general_purpose::STANDARD.decode(d).ok())
+        .ok_or_else(|| GmSimulatorError::InstructionParseError("Missing account data".to_string()))
+}
+
+/// Derive the ATA extensions for a mint from its `getAccountInfo` response
+fn parse_ata_extensions(
+    json: &serde_json::Value,
+    mint: &Pubkey,
+) -> Result<Vec<ExtensionType>, GmSimulatorError> {
+    if parse_mint_owner(json, mint)? != token_2022_program_id() {
+        return Ok(vec![]);
+    }
+
+    let data = account_data(account_value(json)?)?;
+    let state = StateWithExtensions::<Mint>::unpack(&data).map_err(|e| {
+        GmSimulatorError::InstructionParseError(format!("Invalid mint {}: {}", mint, e))
+    })?;
+    let mint_extensions = state.get_extension_types().map_err(|e| {
+        GmSimulatorError::InstructionParseError(format!("Invalid mint {}: {}", mint, e))
+    })?;
+
+    let mut extensions = vec![ExtensionType::ImmutableOwner];
+    extensions.extend(ExtensionType::get_required_init_account_extensions(
+        &mint_extensions,
+    ));
+    Ok(extensions)
+}
+
+/// Read a mint's authority from its `getAccountInfo` response
+fn parse_mint_authority(
+    json: &serde_json::Value,
+    mint: &Pubkey,
+) -> Result<Option<Pubkey>, GmSimulatorError> {
+    parse_mint_owner(json, mint)?;
+
+    let data = account_data(account_value(json)?)?;
+    let state = StateWithExtensions::<Mint>::unpack(&data).map_err(|e| {
+        GmSimulatorError::InstructionParseError(format!("Invalid mint {}: {}", mint, e))
+    })?;
+    Ok(state.base.mint_authority.into())
+}
+
+/// Decode an `oracle_sanity_check` account from a `getAccountInfo` response
+fn parse_oracle_sanity_check(
+    json: &serde_json::Value,
+) -> Result<OracleSanityCheck, GmSimulatorError> {
+    let value = account_value(json)?;
+
+    let owner = value.get("owner").and_then(|o| o.as_str());
+    if owner != Some(ONDO_GM_PROGRAM_ID) {
+        return Err(GmSimulatorError::InstructionParseError(format!(
+            "Oracle sanity check is owned by {}, not the GM program",
+            owner.unwrap_or("unknown")
+        )));
+    }
+
+    let data = account_data(value)?;
+
+    Ok(OracleSanityCheck {
+        context_slot: json
+            .get("result")
+            .and_then(|r| r.get("context"))
+            .and_then(|c| c.get("slot"))
+            .and_then(|s| s.as_u64())
+            .unwrap_or(0),
+        ..OracleSanityCheck::try_from_account_data(&data)?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account_info_response(owner: &Pubkey) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": { "slot": 1 },
+                "value": {
+                    "data": ["", "base64"],
+                    "executable": false,
+                    "lamports": 1461600,
+                    "owner": owner.to_string(),
+                    "rentEpoch": 0
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_transaction_response() {
+        use base64::Engine;
+        use solana_sdk::{
+            instruction::{AccountMeta, Instruction},
+            message::{Message, VersionedMessage},
+            signature::Keypair,
+            signer::Signer,
+            transaction::Transaction,
+        };
+
+        let payer = Keypair::new();
+        let ix = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(payer.pubkey(), true)],
+        );
+        let tx = Transaction::new(
+            &[&payer],
+            Message::new(&[ix], Some(&payer.pubkey())),
+            Hash::new_unique(),
+        );
+        let signature = tx.signatures[0];
+        let encoded =
+            base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&tx).unwrap());
+
+        let json = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "slot": 354830516,
+                "blockTime": 1704067200,
+                "meta": { "err": null },
+                "transaction": [encoded, "base64"],
+                "version": "legacy"
+            }
+        });
+        let fetched = parse_transaction_response(&json, &signature).unwrap();
+        assert_eq!(fetched.signatures, vec![signature]);
+        assert_eq!(fetched.message, VersionedMessage::Legacy(tx.message));
+
+        let not_found = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": null });
+        assert!(matches!(
+            parse_transaction_response(&not_found, &signature),
+            Err(GmSimulatorError::TransactionDecodeError(_))
+        ));
+
+        let error = serde_json::json!({ "error": { "code": -32602, "message": "Invalid param" } });
+        assert!(matches!(
+            parse_transaction_response(&error, &signature),
+            Err(GmSimulatorError::InstructionParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_latest_blockhash() {
+        let blockhash = Hash::new_unique();
+        let json = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": { "slot": 354830516 },
+                "value": {
+                    "blockhash": blockhash.to_string(),
+                    "lastValidBlockHeight": 332903843
+                }
+            }
+        });
+        assert_eq!(parse_latest_blockhash(&json), Ok(blockhash));
+
+        let json = serde_json::json!({ "result": { "value": {} } });
+        assert!(matches!(
+            parse_latest_blockhash(&json),
+            Err(GmSimulatorError::InstructionParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_mint_owner() {
+        let mint = Pubkey::new_unique();
+
+        let json = account_info_response(&token_2022_program_id());
+        assert_eq!(parse_mint_owner(&json, &mint), Ok(token_2022_program_id()));
+
+        let json = account_info_response(&spl_token_program_id());
+        assert_eq!(parse_mint_owner(&json, &mint), Ok(spl_token_program_id()));
+
+        let json = account_info_response(&Pubkey::new_unique());
+        assert!(matches!(
+            parse_mint_owner(&json, &mint),
+            Err(GmSimulatorError::InstructionParseError(_))
+        ));
+
+        let missing = serde_json::json!({ "result": { "context": { "slot": 1 }, "value": null } });
+        assert_eq!(
+            parse_mint_owner(&missing, &mint),
+            Err(GmSimulatorError::MissingAccount)
+        );
+    }
+
+    #[test]
+    fn test_parse_oracle_sanity_check() {
+        use base64::Engine;
+
+        let mint = Pubkey::new_unique();
+        let mut data = crate::mint_instruction::ORACLE_SANITY_CHECK_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(mint.as_ref());
+        data.extend_from_slice(&187_500_000u64.to_le_bytes());
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.extend_from_slice(&300i64.to_le_bytes());
+        data.extend_from_slice(&1_704_067_200i64.to_le_bytes());
+
+        let response = |data: &[u8]| {
+            let mut json = account_info_response(&crate::constants::ondo_gm_program_id());
+            json["result"]["context"]["slot"] = 250_000_000.into();
+            json["result"]["value"]["data"][0] = base64::engine::general_purpose::STANDARD
+                .encode(data)
+                .into();
+            json
+        };
+
+        let sanity = parse_oracle_sanity_check(&response(&data)).unwrap();
+        assert_eq!(sanity.mint, mint);
+        assert_eq!(sanity.last_price, 187_500_000);
+        assert_eq!(sanity.allowed_deviation_bps, 500);
+        assert_eq!(sanity.last_updated, 1_704_067_200);
+        assert_eq!(sanity.context_slot, 250_000_000);
+        assert!(!sanity.is_stale(1_704_067_200 + 300));
+        assert!(sanity.is_stale(1_704_067_200 + 301));
+
+        assert!(matches!(
+            parse_oracle_sanity_check(&response(&data[..64])),
+            Err(GmSimulatorError::InstructionParseError(_))
+        ));
+
+        let wrong_owner = account_info_response(&token_2022_program_id());
+        assert!(matches!(
+            parse_oracle_sanity_check(&wrong_owner),
+            Err(GmSimulatorError::InstructionParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_mint_authority() {
+        use base64::Engine;
+        use solana_sdk::{program_option::COption, program_pack::Pack};
+
+        let mint = Pubkey::new_unique();
+        let response = |mint_authority: COption<Pubkey>| {
+            let mut data = vec![0u8; Mint::LEN];
+            Mint::pack(
+                Mint {
+                    mint_authority,
+                    decimals: 9,
+                    is_initialized: true,
+                    ..Mint::default()
+                },
+                &mut data,
+            )
+            .unwrap();
+            let mut json = account_info_response(&token_2022_program_id());
+            json["result"]["value"]["data"][0] = base64::engine::general_purpose::STANDARD
+                .encode(&data)
+                .into();
+            json
+        };
+
+        let json = response(COption::Some(mint_authority_account()));
+        assert_eq!(
+            parse_mint_authority(&json, &mint),
+            Ok(Some(mint_authority_account()))
+        );
+
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            parse_mint_authority(&response(COption::Some(other)), &mint),
+            Ok(Some(other))
+        );
+        assert_eq!(
+            parse_mint_authority(&response(COption::None), &mint),
+            Ok(None)
+        );
+
+        // Not a mint at all
+        let json = account_info_response(&Pubkey::new_unique());
+        assert!(matches!(
+            parse_mint_authority(&json, &mint),
+            Err(GmSimulatorError::InstructionParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_ata_extensions() {
+        use base64::Engine;
+        use solana_sdk::program_pack::Pack;
+        use spl_token_2022::extension::{
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensionsMut, StateWithExtensionsMut,
+        };
+
+        let mint = Pubkey::new_unique();
+        let response = |owner: &Pubkey, data: &[u8]| {
+            let mut json = account_info_response(owner);
+            json["result"]["value"]["data"][0] = base64::engine::general_purpose::STANDARD
+                .encode(data)
+                .into();
+            json
+        };
+        let base = Mint {
+            decimals: 9,
+            is_initialized: true,
+            ..Mint::default()
+        };
+
+        // A Token-2022 mint with a transfer fee needs a TransferFeeAmount on each account
+        let len =
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        state.init_extension::<TransferFeeConfig>(true).unwrap();
+        state.base = base;
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        assert_eq!(
+            parse_ata_extensions(&response(&token_2022_program_id(), &data), &mint),
+            Ok(vec![
+                ExtensionType::ImmutableOwner,
+                ExtensionType::TransferFeeAmount
+            ])
+        );
+
+        // A plain Token-2022 mint only gets ImmutableOwner
+        let mut data = vec![0u8; Mint::LEN];
+        Mint::pack(base, &mut data).unwrap();
+        assert_eq!(
+            parse_ata_extensions(&response(&token_2022_program_id(), &data), &mint),
+            Ok(vec![ExtensionType::ImmutableOwner])
+        );
+
+        // SPL Token ATAs have no extensions
+        assert_eq!(
+            parse_ata_extensions(&response(&spl_token_program_id(), &data), &mint),
+            Ok(vec![])
+        );
+    }
+}
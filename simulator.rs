@@ -6,16 +6,31 @@
 
 use solana_sdk::{
     hash::Hash,
-    instruction::Instruction,
-    message::{Message, VersionedMessage},
+    instruction::{CompiledInstruction, Instruction},
+    message::{v0, AddressLookupTableAccount, Message, MessageHeader, VersionedMessage},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Signature,
     transaction::{Transaction, VersionedTransaction},
 };
+use solana_transaction_status_client_types::{
+    UiInnerInstructions, UiInstruction, UiTransactionStatusMeta,
+};
 
 use crate::{
-    constants::jupiter_order_engine_program_id,
-    mint_instruction::{build_mock_mint_gm_instruction, build_mock_mint_gm_instruction_with_ata},
-    parser::{is_jupiter_fill_instruction, parse_fill_for_gm_trade},
-    types::{GmCheckResult, GmSimulatorError, GmTradeInfo},
+    constants::ondo_gm_program_id,
+    mint_instruction::{
+        build_mint_gm_instruction, build_mock_mint_gm_instruction,
+        build_mock_mint_gm_instruction_with_ata, MintGmAccounts, MINT_GM_DISCRIMINATOR,
+    },
+    parser::{
+        classify_fill, fill_venues, is_nonidempotent_ata_create, parse_fill_with_layout,
+        readonly_header, FillLayout,
+    },
+    types::{
+        DetectionOutcome, GmCheckResult, GmSimulatorError, GmTradeInfo, MockMintOptions,
+        NoBundleReason, ResolvedAccount, TradeDirection,
+    },
 };
 
 /// Check if a transaction should use GM bundle simulation.
@@ -51,6 +66,29 @@ pub fn check_gm_trade(transaction: &Transaction) -> Result<GmCheckResult, GmSimu
     check_gm_trade_message(&transaction.message)
 }
 
+/// Check if a transaction should use GM bundle simulation, with a per-call instruction limit.
+///
+/// Same as `check_gm_trade`, but messages with more than `max_instructions`
+/// instructions are rejected instead of more than `GmConfig::max_instructions`, e.g.
+/// for a service that applies a tighter limit to untrusted callers.
+///
+/// # Arguments
+///
+/// * `transaction` - The transaction to check
+/// * `max_instructions` - The most instructions the message may have
+pub fn check_gm_trade_with_max_instructions(
+    transaction: &Transaction,
+    max_instructions: usize,
+) -> Result<GmCheckResult, GmSimulatorError> {
+    let message = &transaction.message;
+    check_instructions(
+        &message.instructions,
+        &message.account_keys,
+        &message.header,
+        max_instructions,
+    )
+}
+
 /// Check if a message should use GM bundle simulation.
 ///
 /// Same as `check_gm_trade` but operates on a `Message` instead of `Transaction`.
@@ -58,30 +96,358 @@ pub fn check_gm_trade(transaction: &Transaction) -> Result<GmCheckResult, GmSimu
 /// Note: GM trades typically include additional instructions like `createAssociatedTokenAccountIdempotent`
 /// to ensure the taker's ATA exists. We search for the Jupiter fill instruction among all instructions.
 pub fn check_gm_trade_message(message: &Message) -> Result<GmCheckResult, GmSimulatorError> {
-    let account_keys = &message.account_keys;
-    let jupiter_program_id = jupiter_order_engine_program_id();
+    check_instructions(
+        &message.instructions,
+        &message.account_keys,
+        &message.header,
+        global_max_instructions(),
+    )
+}
+
+/// Check a message for a GM trade, reporting why it isn't one.
+///
+/// Same detection as `check_gm_trade_message`, but distinguishes a message with no
+/// Jupiter fill (`NoFillFound`) from a GM SELL (`GmSell`) and a fill that isn't a GM
+/// trade at all (`FillButNotGm`), so callers can log why a transaction wasn't treated
+/// as a GM trade.
+///
+/// # Returns
+///
+/// * `Ok(DetectionOutcome)` describing what was found
+/// * `Err` if the message is malformed or the fill has an unauthorized maker
+pub fn check_gm_trade_detailed(message: &Message) -> Result<DetectionOutcome, GmSimulatorError> {
+    detect_gm_trade(
+        &message.instructions,
+        &message.account_keys,
+        &message.header,
+        global_max_instructions(),
+    )
+    .map(|(outcome, _)| outcome)
+}
+
+/// Check already-decoded account keys and instructions for a GM trade.
+///
+/// Same detection as `check_gm_trade_message`, for callers that decoded a transaction
+/// into its static account keys and compiled instructions and don't want to rebuild a
+/// `Message`. Without a message header the keys' writability is unknown, so the check
+/// that the fill's mints are readonly is skipped.
+///
+/// # Arguments
+///
+/// * `account_keys` - The message's static account keys
+/// * `instructions` - The message's compiled instructions, indexing into `account_keys`
+pub fn check_gm_trade_raw(
+    account_keys: &[Pubkey],
+    instructions: &[CompiledInstruction],
+) -> Result<GmCheckResult, GmSimulatorError> {
+    check_instructions(
+        instructions,
+        account_keys,
+        &readonly_header(account_keys),
+        global_max_instructions(),
+    )
+}
+
+/// Find a GM trade settled by a fill among a transaction's inner instructions.
+///
+/// For fills made by CPI (e.g. through an aggregator or a solver's own program), which
+/// top-level detection doesn't see. Walks `meta.innerInstructions`, as returned by
+/// `getTransaction` with `json` or `base64` encoding, in order and returns the first
+/// fill from a known venue that parses as a GM trade, BUY or SELL (see `direction`).
+/// `jsonParsed` inner instructions and fills that fail validation (e.g. from an
+/// unauthorized maker) are skipped. As in `check_gm_trade_raw`, the check that the
+/// fill's mints are readonly is skipped.
+///
+/// # Arguments
+///
+/// * `meta` - The transaction's status meta
+/// * `account_keys` - The keys inner instructions index into: the static account keys,
+///   then for a v0 transaction the loaded writable and readonly addresses
+pub fn extract_inner_fill(
+    meta: &UiTransactionStatusMeta,
+    account_keys: &[Pubkey],
+) -> Option<GmTradeInfo> {
+    let groups = Option::<&Vec<UiInnerInstructions>>::from(meta.inner_instructions.as_ref())?;
+    let header = readonly_header(account_keys);
+
+    groups
+        .iter()
+        .flat_map(|group| &group.instructions)
+        .find_map(|instruction| {
+            let UiInstruction::Compiled(compiled) = instruction else {
+                return None;
+            };
+            let instruction = CompiledInstruction {
+                program_id_index: compiled.program_id_index,
+                accounts: compiled.accounts.clone(),
+                data: bs58::decode(&compiled.data).into_vec().ok()?,
+            };
+            let (fill, layout) =
+                find_fill_instruction(std::slice::from_ref(&instruction), account_keys)?;
+            parse_fill_with_layout(fill, account_keys, &header, &layout)
+                .ok()
+                .flatten()
+        })
+}
+
+/// The global config's `max_instructions`, for entry points without a config of their own
+fn global_max_instructions() -> usize {
+    crate::constants::global_config().max_instructions
+}
+
+/// Shared detection for legacy and v0 messages, given the message's static account keys
+fn check_instructions(
+    instructions: &[CompiledInstruction],
+    account_keys: &[Pubkey],
+    header: &MessageHeader,
+    max_instructions: usize,
+) -> Result<GmCheckResult, GmSimulatorError> {
+    match detect_gm_trade(instructions, account_keys, header, max_instructions)? {
+        (DetectionOutcome::GmTrade(trade_info), _) => {
+            let mut result = GmCheckResult::gm_trade(*trade_info);
+            result.nonidempotent_ata_create = instructions
+                .iter()
+                .any(|ix| is_nonidempotent_ata_create(ix, account_keys));
+            result.noncanonical_maker_output = result
+                .trade_info
+                .as_ref()
+                .is_some_and(|info| !info.maker_output_is_ata());
+            Ok(result)
+        }
+        (_, reason) => Ok(GmCheckResult {
+            reason,
+            ..GmCheckResult::not_gm_trade()
+        }),
+    }
+}
+
+/// Find the first fill instruction from any of `fill_venues` and its layout
+///
+/// Jupiter Order Engine is the first venue; others come from the config's
+/// `extra_fill_venues`. An instruction with a fill discriminator but too little data
+/// for the fill's arguments can't be a real fill, so it is skipped rather than failing
+/// detection.
+fn find_fill_instruction<'a>(
+    instructions: &'a [CompiledInstruction],
+    account_keys: &[Pubkey],
+) -> Option<(&'a CompiledInstruction, FillLayout)> {
+    instructions.iter().find_map(|ix| {
+        let program_id = account_keys.get(ix.program_id_index as usize)?;
+        fill_venues()
+            .find(|venue| {
+                venue.program_id == *program_id
+                    && ix.data.starts_with(&venue.layout.discriminator)
+                    && ix.data.len() >= venue.layout.min_data_len()
+            })
+            .map(|venue| (ix, venue.layout))
+    })
+}
 
-    // Check 1: Must have at least one instruction
-    if message.instructions.is_empty() {
+/// Find and classify the Jupiter fill among a message's instructions
+///
+/// Along with the outcome, returns why bundle simulation isn't needed (`None` for a
+/// GM BUY).
+fn detect_gm_trade(
+    instructions: &[CompiledInstruction],
+    account_keys: &[Pubkey],
+    header: &MessageHeader,
+    max_instructions: usize,
+) -> Result<(DetectionOutcome, Option<NoBundleReason>), GmSimulatorError> {
+    // Check 1: Must have at least one instruction, and few enough that scanning them
+    // can't be used to tie up a service
+    if instructions.is_empty() {
         return Err(GmSimulatorError::EmptyTransaction);
     }
+    if instructions.len() > max_instructions {
+        return Err(GmSimulatorError::TooManyInstructions {
+            count: instructions.len(),
+            max: max_instructions,
+        });
+    }
 
     // Check 2: Find Jupiter Order Engine fill instruction
     // Note: Transaction may contain other instructions like createAssociatedTokenAccountIdempotent
-    let fill_instruction = message
+    let Some((instruction, layout)) = find_fill_instruction(instructions, account_keys) else {
+        let outcome = Ok((
+            DetectionOutcome::NoFillFound,
+            Some(NoBundleReason::NotAFill),
+        ));
+        record_detection(&outcome);
+        return outcome;
+    };
+
+    // Check 3 & 4: Parse and validate (maker must be authorized, output must be GM token)
+    // Only BUYs need a mock mint; a SELL is carried through with its trade info
+    let outcome =
+        classify_fill(instruction, account_keys, header, &layout).map(|parsed| match parsed {
+            Ok(trade_info) if trade_info.direction == TradeDirection::Buy => {
+                (DetectionOutcome::GmTrade(Box::new(trade_info)), None)
+            }
+            Ok(trade_info) => (
+                DetectionOutcome::GmSell(Box::new(trade_info)),
+                Some(NoBundleReason::SellDirection),
+            ),
+            Err(reason) => (DetectionOutcome::FillButNotGm, Some(reason)),
+        });
+    record_detection(&outcome);
+    outcome
+}
+
+/// Increment the `metrics` counter for a detection outcome
+///
+/// A fill selling a GM token counts as a SELL, whether or not it is for a stablecoin,
+/// and one whose maker isn't a solver (with `unauthorized_maker_is_error` off) as
+/// unauthorized. Errors other than an unauthorized maker are malformed transactions
+/// and aren't counted.
+#[cfg(feature = "metrics")]
+fn record_detection(
+    outcome: &Result<(DetectionOutcome, Option<NoBundleReason>), GmSimulatorError>,
+) {
+    use crate::constants::{
+        METRIC_DETECTED_BUY, METRIC_DETECTED_NON_GM, METRIC_DETECTED_SELL,
+        METRIC_DETECTED_UNAUTHORIZED,
+    };
+
+    let counter = match outcome {
+        Ok((DetectionOutcome::GmTrade(_), _)) => METRIC_DETECTED_BUY,
+        Ok((_, Some(NoBundleReason::UnauthorizedMaker))) => METRIC_DETECTED_UNAUTHORIZED,
+        Ok((_, Some(NoBundleReason::SellDirection))) => METRIC_DETECTED_SELL,
+        Ok(_) => METRIC_DETECTED_NON_GM,
+        Err(GmSimulatorError::UnauthorizedMaker(_)) => METRIC_DETECTED_UNAUTHORIZED,
+        Err(_) => return,
+    };
+    metrics::counter!(counter).increment(1);
+}
+
+#[cfg(not(feature = "metrics"))]
+#[inline(always)]
+fn record_detection(
+    _outcome: &Result<(DetectionOutcome, Option<NoBundleReason>), GmSimulatorError>,
+) {
+}
+
+/// Check if a message creates an ATA with the non-idempotent `createAssociatedTokenAccount`.
+///
+/// The mock mint idempotently creates the taker's and maker's ATAs before the fill runs,
+/// so a fill transaction that then calls the non-idempotent create on one of them fails
+/// in bundle simulation even though it would succeed on-chain.
+pub fn has_nonidempotent_ata_create(message: &Message) -> bool {
+    message
         .instructions
         .iter()
-        .find(|ix| is_jupiter_fill_instruction(ix, &jupiter_program_id, account_keys));
+        .any(|ix| is_nonidempotent_ata_create(ix, &message.account_keys))
+}
 
-    let Some(instruction) = fill_instruction else {
-        return Ok(GmCheckResult::not_gm_trade());
-    };
+/// Check if a message already contains a real `mint_gm` instruction.
+///
+/// A solver self-bundling the mint with its fill doesn't need a mock mint; adding one
+/// would mint the GM tokens twice.
+pub fn contains_mint_gm(message: &Message) -> bool {
+    let program_id = ondo_gm_program_id();
+
+    message.instructions.iter().any(|ix| {
+        message.account_keys.get(ix.program_id_index as usize) == Some(&program_id)
+            && ix.data.starts_with(&MINT_GM_DISCRIMINATOR)
+    })
+}
 
-    // Check 3 & 4: Parse and validate (maker must be authorized, output must be GM token)
-    match parse_fill_for_gm_trade(instruction, account_keys)? {
-        Some(trade_info) => Ok(GmCheckResult::gm_trade(trade_info)),
-        None => Ok(GmCheckResult::not_gm_trade()),
+/// List the GM token mints among a message's account keys.
+///
+/// A pure lookup against the built-in token list, in account-key order, so it finds
+/// GM mints referenced by any instruction (fills, transfers, ATA creates, ...). Other
+/// mints such as USDC aren't recognized without fetching account owners.
+pub fn gm_mints_in_message(message: &Message) -> Vec<Pubkey> {
+    message
+        .account_keys
+        .iter()
+        .filter(|key| crate::constants::is_gm_token(key))
+        .copied()
+        .collect()
+}
+
+/// Resolve a compiled instruction's accounts to pubkeys and their roles in the message.
+///
+/// Returns one entry per account index in `ix.accounts`, in order, with the signer and
+/// writable flags from the message header (writable program ids are demoted as the
+/// runtime does). Indices outside the message's account keys are skipped; a sanitized
+/// message never has any.
+///
+/// # Arguments
+///
+/// * `message` - The message `ix` belongs to
+/// * `ix` - One of the message's instructions
+pub fn resolve_instruction_accounts(
+    message: &Message,
+    ix: &CompiledInstruction,
+) -> Vec<ResolvedAccount> {
+    ix.accounts
+        .iter()
+        .filter_map(|&index| {
+            let index = index as usize;
+            let pubkey = *message.account_keys.get(index)?;
+            Some(ResolvedAccount {
+                pubkey,
+                is_signer: message.is_signer(index),
+                is_writable: message.is_maybe_writable(index, None),
+            })
+        })
+        .collect()
+}
+
+/// Expand a V0 message's account keys with addresses from its lookup tables.
+///
+/// Returns the keys in the order the runtime loads them, which is the order compiled
+/// instructions index into: the static keys, then the writable addresses of every
+/// lookup in turn, then the readonly addresses of every lookup in turn.
+///
+/// # Arguments
+///
+/// * `v0_msg` - The message to resolve
+/// * `tables` - The fetched lookup tables; extra tables are ignored
+///
+/// # Returns
+///
+/// * `Ok(keys)` with the full ordered account list
+/// * `Err(TransactionDecodeError)` if a lookup's table is missing from `tables` or an
+///   index is past the end of its table
+pub fn resolve_v0_accounts(
+    v0_msg: &v0::Message,
+    tables: &[AddressLookupTableAccount],
+) -> Result<Vec<Pubkey>, GmSimulatorError> {
+    let mut writable = Vec::new();
+    let mut readonly = Vec::new();
+    for lookup in &v0_msg.address_table_lookups {
+        let table = tables
+            .iter()
+            .find(|table| table.key == lookup.account_key)
+            .ok_or_else(|| {
+                GmSimulatorError::TransactionDecodeError(format!(
+                    "lookup table {} not provided",
+                    lookup.account_key
+                ))
+            })?;
+        let load = |indexes: &[u8], keys: &mut Vec<Pubkey>| {
+            for &index in indexes {
+                let address = table.addresses.get(index as usize).ok_or_else(|| {
+                    GmSimulatorError::TransactionDecodeError(format!(
+                        "index {} out of range for lookup table {} ({} addresses)",
+                        index,
+                        table.key,
+                        table.addresses.len()
+                    ))
+                })?;
+                keys.push(*address);
+            }
+            Ok(())
+        };
+        load(&lookup.writable_indexes, &mut writable)?;
+        load(&lookup.readonly_indexes, &mut readonly)?;
     }
+
+    let mut keys = v0_msg.account_keys.clone();
+    keys.extend(writable);
+    keys.extend(readonly);
+    Ok(keys)
 }
 
 /// Check if a versioned transaction should use GM bundle simulation.
@@ -104,6 +470,24 @@ pub fn check_gm_trade_versioned(
     check_gm_trade_versioned_message(&transaction.message)
 }
 
+/// Check an encoded transaction, e.g. from a webhook payload, for a GM trade.
+///
+/// Decodes with `decode_transaction` (base64, or base58, auto-detected) and checks the
+/// result with `check_gm_trade_versioned`, so legacy and v0 transactions both work.
+///
+/// # Arguments
+///
+/// * `encoded` - The serialized transaction; surrounding whitespace is ignored
+///
+/// # Returns
+///
+/// * `Ok(GmCheckResult)` as from `check_gm_trade_versioned`
+/// * `Err(TransactionDecodeError)` if the string isn't an encoded transaction
+/// * `Err` if the transaction is malformed or has an unauthorized maker
+pub fn check_gm_trade_base64(encoded: &str) -> Result<GmCheckResult, GmSimulatorError> {
+    check_gm_trade_versioned(&crate::transaction::decode_transaction(encoded)?)
+}
+
 /// Check if a versioned message should use GM bundle simulation.
 ///
 /// Same as `check_gm_trade_versioned` but operates on a `VersionedMessage` instead of `VersionedTransaction`.
@@ -111,38 +495,147 @@ pub fn check_gm_trade_versioned(
 /// Note: For V0 messages with address lookup tables, this function only checks the static
 /// account keys. If the Jupiter fill instruction references accounts from lookup tables,
 /// the check may not work correctly. In practice, the critical accounts (taker, maker,
-/// output_mint) are typically in the static keys.
+/// output_mint) are typically in the static keys; if the tables have been fetched,
+/// `check_gm_trade_v0_with_tables` checks the fully resolved accounts.
+///
+/// The empty-transaction check counts the message's compiled instructions, which is
+/// the same before and after lookup-table resolution. A V0 message whose only
+/// instruction has a program id outside the static keys is therefore not empty: it is
+/// reported as `NotAFill` rather than `EmptyTransaction`.
 pub fn check_gm_trade_versioned_message(
     message: &VersionedMessage,
 ) -> Result<GmCheckResult, GmSimulatorError> {
     match message {
         VersionedMessage::Legacy(legacy_msg) => check_gm_trade_message(legacy_msg),
         VersionedMessage::V0(v0_msg) => {
-            let account_keys = &v0_msg.account_keys;
-            let jupiter_program_id = jupiter_order_engine_program_id();
+            // Resolution only loads account keys, never instructions, so the empty check
+            // inside runs on the full instruction list either way
+            check_instructions(
+                &v0_msg.instructions,
+                &v0_msg.account_keys,
+                &v0_msg.header,
+                global_max_instructions(),
+            )
+        }
+    }
+}
 
-            // Check 1: Must have at least one instruction
-            if v0_msg.instructions.is_empty() {
-                return Err(GmSimulatorError::EmptyTransaction);
-            }
+/// Check a V0 message for a GM trade after resolving its lookup tables.
+///
+/// Same detection as `check_gm_trade_versioned_message`, but over the account list from
+/// `resolve_v0_accounts`, so fills whose maker, taker or mints are loaded from lookup
+/// tables are detected too. As in `check_gm_trade_raw`, the check that the fill's mints
+/// are readonly is skipped.
+///
+/// # Arguments
+///
+/// * `v0_msg` - The message to check
+/// * `tables` - The lookup tables the message references
+///
+/// # Returns
+///
+/// * `Ok(GmCheckResult)` as from `check_gm_trade_versioned_message`
+/// * `Err(TransactionDecodeError)` if the lookup tables can't be resolved
+/// * `Err` if the transaction is malformed or has an unauthorized maker
+pub fn check_gm_trade_v0_with_tables(
+    v0_msg: &v0::Message,
+    tables: &[AddressLookupTableAccount],
+) -> Result<GmCheckResult, GmSimulatorError> {
+    let account_keys = resolve_v0_accounts(v0_msg, tables)?;
+    check_instructions(
+        &v0_msg.instructions,
+        &account_keys,
+        &readonly_header(&account_keys),
+        global_max_instructions(),
+    )
+}
 
-            // Check 2: Find Jupiter Order Engine fill instruction
-            let fill_instruction = v0_msg
-                .instructions
-                .iter()
-                .find(|ix| is_jupiter_fill_instruction(ix, &jupiter_program_id, account_keys));
+/// Check many transactions for GM trades.
+///
+/// Detection is pure and CPU-bound, so with the `rayon` feature enabled the batch is
+/// checked in parallel. Results are returned in the same order as the input.
+///
+/// # Arguments
+///
+/// * `txs` - The transactions to check
+///
+/// # Returns
+///
+/// One `check_gm_trade` result per input transaction
+pub fn check_gm_trades_batch(txs: &[Transaction]) -> Vec<Result<GmCheckResult, GmSimulatorError>> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        txs.par_iter().map(check_gm_trade).collect()
+    }
 
-            let Some(instruction) = fill_instruction else {
-                return Ok(GmCheckResult::not_gm_trade());
-            };
+    #[cfg(not(feature = "rayon"))]
+    {
+        txs.iter().map(check_gm_trade).collect()
+    }
+}
+
+/// Lazily check a stream of messages for GM trades.
+///
+/// Each message is classified with `check_gm_trade_message` only when the next result
+/// is pulled, so nothing is buffered and a slow consumer throttles the producer. Use
+/// this for mempool-like streams; `check_gm_trades_batch` suits a slice already in
+/// memory.
+///
+/// # Arguments
+///
+/// * `messages` - The messages to check
+///
+/// # Returns
+///
+/// An iterator yielding one `check_gm_trade_message` result per message, in order
+pub fn detect_stream<'a>(
+    messages: impl Iterator<Item = &'a Message>,
+) -> impl Iterator<Item = Result<GmCheckResult, GmSimulatorError>> {
+    messages.map(check_gm_trade_message)
+}
 
-            // Check 3 & 4: Parse and validate (maker must be authorized, output must be GM token)
-            match parse_fill_for_gm_trade(instruction, account_keys)? {
-                Some(trade_info) => Ok(GmCheckResult::gm_trade(trade_info)),
-                None => Ok(GmCheckResult::not_gm_trade()),
+/// Check a Jito bundle for a GM trade.
+///
+/// A solver's bundle may spread the taker's ATA creation and the Jupiter fill across
+/// separate transactions, so every transaction is searched in order and the first GM
+/// trade found is returned. A transaction that fails the check (e.g. a tip transaction
+/// with no instructions) doesn't stop the search.
+///
+/// # Arguments
+///
+/// * `txs` - The transactions in the bundle, in execution order
+///
+/// # Returns
+///
+/// * `Ok(GmCheckResult)` with `use_gm_bundle_sim = true` and trade info if any transaction is a GM trade
+/// * `Ok(GmCheckResult)` with `use_gm_bundle_sim = false` if none are, with the reason of
+///   the last transaction that has a fill (`NotAFill` if none has)
+/// * `Err` if the bundle is empty, or with the first transaction's error if every
+///   transaction fails the check
+pub fn check_gm_trade_bundle(txs: &[Transaction]) -> Result<GmCheckResult, GmSimulatorError> {
+    let mut not_gm: Option<GmCheckResult> = None;
+    let mut first_error = None;
+
+    for tx in txs {
+        match check_gm_trade(tx) {
+            Ok(result) if result.use_gm_bundle_sim => return Ok(result),
+            Ok(result) => {
+                if not_gm.is_none() || result.reason != Some(NoBundleReason::NotAFill) {
+                    not_gm = Some(result);
+                }
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
             }
         }
     }
+
+    match (not_gm, first_error) {
+        (Some(result), _) => Ok(result),
+        (None, Some(e)) => Err(e),
+        (None, None) => Err(GmSimulatorError::EmptyTransaction),
+    }
 }
 
 /// Build a mock mint transaction for bundle simulation.
@@ -153,6 +646,11 @@ pub fn check_gm_trade_versioned_message(
 ///
 /// This matches the pattern used by actual solver transactions.
 ///
+/// The associated token account program initializes any account extensions the GM
+/// mint requires (e.g. `TransferFeeAmount`) along with `ImmutableOwner`, so the ATA
+/// creates need no extra extension instructions. Use `rpc::fetch_ata_extensions` to
+/// see which extensions the created ATAs will carry.
+///
 /// # Arguments
 ///
 /// * `trade_info` - The GM trade info from `check_gm_trade`
@@ -188,19 +686,129 @@ pub fn build_mock_mint_transaction(
     trade_info: &GmTradeInfo,
     recent_blockhash: Hash,
 ) -> Transaction {
+    build_mock_mint_transaction_with_options(
+        trade_info,
+        recent_blockhash,
+        &MockMintOptions::default(),
+    )
+}
+
+/// Build a mock mint transaction for bundle simulation with custom options.
+///
+/// Same as `build_mock_mint_transaction`, but allows leaving out instructions the
+/// bundle doesn't need via `MockMintOptions`.
+///
+/// # Arguments
+///
+/// * `trade_info` - The GM trade info from `check_gm_trade`
+/// * `recent_blockhash` - A recent blockhash for the transaction
+/// * `options` - Mock mint options (skipped ATA creates, a memo, ...)
+pub fn build_mock_mint_transaction_with_options(
+    trade_info: &GmTradeInfo,
+    recent_blockhash: Hash,
+    options: &MockMintOptions,
+) -> Transaction {
+    let minter = crate::constants::admin_minter();
+    let message = Message::new_with_blockhash(
+        &mock_mint_instructions(trade_info, options),
+        Some(&minter),
+        &recent_blockhash,
+    );
+    Transaction::new_unsigned(message)
+}
+
+/// Build a mock mint transaction that uses a durable nonce instead of a recent blockhash.
+///
+/// Same instructions as `build_mock_mint_transaction`, preceded by an
+/// `AdvanceNonceAccount` instruction, with `nonce_value` as the message's blockhash.
+/// Use this when the fill is signed against a durable nonce so both bundle
+/// transactions share it.
+///
+/// # Arguments
+///
+/// * `trade_info` - The GM trade info from `check_gm_trade`
+/// * `nonce_account` - The durable nonce account to advance
+/// * `nonce_authority` - The nonce account's authority (becomes a required signer)
+/// * `nonce_value` - The nonce currently stored in `nonce_account`
+pub fn build_mock_mint_transaction_with_nonce(
+    trade_info: &GmTradeInfo,
+    nonce_account: &Pubkey,
+    nonce_authority: &Pubkey,
+    nonce_value: Hash,
+) -> Transaction {
+    let minter = crate::constants::admin_minter();
+    let mut message = Message::new_with_nonce(
+        build_mock_mint_instructions(trade_info),
+        Some(&minter),
+        nonce_account,
+        nonce_authority,
+    );
+    message.recent_blockhash = nonce_value;
+    Transaction::new_unsigned(message)
+}
+
+/// Build the mock mint instructions without wrapping them in a transaction.
+///
+/// Returns the same five instructions as `build_mock_mint_transaction`, in order: the
+/// taker's and maker's GM ATA creates, the taker's and maker's ATA creates for the
+/// fill's quote mint (USDC, or e.g. USDT if the fill pays with that), then
+/// `mint_gm` into the fill's `maker_output_account`. Use this to merge the mock mint
+/// into a transaction of your own; the admin minter must be the fee payer (it pays for
+/// the ATAs and signs `mint_gm`).
+///
+/// # Arguments
+///
+/// * `trade_info` - The GM trade info from `check_gm_trade`
+pub fn build_mock_mint_instructions(trade_info: &GmTradeInfo) -> Vec<Instruction> {
+    mock_mint_instructions(trade_info, &MockMintOptions::default())
+}
+
+/// Estimate the compute units a mock mint needs, for `MockMintOptions::compute_unit_limit`.
+///
+/// Budgets `MOCK_MINT_ATA_CREATE_COMPUTE_UNITS` for each ATA create in the mock mint
+/// (four by default) plus `MOCK_MINT_GM_COMPUTE_UNITS` for the Token-2022 `mint_gm`.
+/// GM mints with unusual extensions may need more; check `unitsConsumed` in a
+/// simulation when in doubt.
+///
+/// # Arguments
+///
+/// * `trade_info` - The GM trade info from `check_gm_trade`
+pub fn recommended_compute_limit(trade_info: &GmTradeInfo) -> u32 {
+    let ata_creates = build_mock_mint_instructions(trade_info)
+        .iter()
+        .filter(|ix| ix.program_id == spl_associated_token_account::id())
+        .count();
+    compute_limit_for_ata_creates(ata_creates)
+}
+
+/// Compute units for a mock mint with `ata_creates` ATA creates and one `mint_gm`
+fn compute_limit_for_ata_creates(ata_creates: usize) -> u32 {
+    use crate::constants::{MOCK_MINT_ATA_CREATE_COMPUTE_UNITS, MOCK_MINT_GM_COMPUTE_UNITS};
+
+    let ata_creates = u32::try_from(ata_creates).unwrap_or(u32::MAX);
+    ata_creates
+        .saturating_mul(MOCK_MINT_ATA_CREATE_COMPUTE_UNITS)
+        .saturating_add(MOCK_MINT_GM_COMPUTE_UNITS)
+}
+
+/// The ATA creates and `mint_gm` instruction that make up a mock mint transaction
+fn mock_mint_instructions(trade_info: &GmTradeInfo, options: &MockMintOptions) -> Vec<Instruction> {
     use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
 
-    let token_program = crate::constants::token_2022_program_id();
-    let usdc_mint = crate::constants::usdc_mint();
+    let quote_mint = trade_info.quote_mint;
     let minter = crate::constants::admin_minter();
 
+    // Use the token programs the fill itself references for each leg
+    let gm_token_program = trade_info.output_token_program;
+    let quote_token_program = trade_info.input_token_program;
+
     // Build instructions in order:
     // 1. Create taker's GM ATA (idempotent - won't fail if it already exists)
     let create_taker_gm_ata_ix = create_associated_token_account_idempotent(
         &minter,                   // payer
         &trade_info.taker,         // wallet
         &trade_info.gm_token_mint, // mint
-        &token_program,            // token program (Token-2022)
+        &gm_token_program,         // token program (fill's output token program)
     );
 
     // 2. Create maker's GM ATA (idempotent - won't fail if it already exists)
@@ -208,44 +816,142 @@ pub fn build_mock_mint_transaction(
         &minter,                   // payer
         &trade_info.maker,         // wallet
         &trade_info.gm_token_mint, // mint
-        &token_program,            // token program (Token-2022)
+        &gm_token_program,         // token program (fill's output token program)
     );
 
-    // 3. Create taker's USDC ATA (idempotent - needed for Jupiter fill to send USDC)
-    let create_taker_usdc_ata_ix = create_associated_token_account_idempotent(
-        &minter,           // payer
-        &trade_info.taker, // wallet
-        &usdc_mint,        // USDC mint
-        &crate::constants::spl_token_program_id(),  // token program (SPL Token)
+    // 3. Create taker's quote ATA (idempotent - needed for Jupiter fill to send USDC/USDT)
+    let create_taker_quote_ata_ix = create_associated_token_account_idempotent(
+        &minter,              // payer
+        &trade_info.taker,    // wallet
+        &quote_mint,          // quote mint (the fill's input mint)
+        &quote_token_program, // token program (fill's input token program)
     );
 
-    // 4. Create maker's USDC ATA (idempotent - needed for Jupiter fill to receive USDC)
-    let create_maker_usdc_ata_ix = create_associated_token_account_idempotent(
-        &minter,           // payer
-        &trade_info.maker, // wallet
-        &usdc_mint,        // USDC mint
-        &crate::constants::spl_token_program_id(),  // token program (SPL Token)
-    );
+    // 4. Create maker's quote ATA (idempotent - needed for Jupiter fill to receive it)
+    //    Established solvers always have one, so this can be skipped to save CU
+    let create_maker_quote_ata_ix = (!options.skip_maker_quote_ata).then(|| {
+        create_associated_token_account_idempotent(
+            &minter,              // payer
+            &trade_info.maker,    // wallet
+            &quote_mint,          // quote mint (the fill's input mint)
+            &quote_token_program, // token program (fill's input token program)
+        )
+    });
 
-    // 5. Mint GM tokens to solver (maker)
-    let mint_ix = build_mock_mint_gm_instruction(
+    // 5. Mint GM tokens to solver (maker), into the account the fill pulls them from.
+    //    That is normally the GM ATA created above, but a fill may name another account
+    //    as `maker_output_account`; minting to the derived ATA would leave it empty
+    let mut mint_accounts = MintGmAccounts::new(
         &trade_info.gm_token_mint,
+        &trade_info.maker_output_account,
         &trade_info.maker, // Mint to the solver (maker)
-        trade_info.gm_token_amount,
     );
+    mint_accounts.token_program = gm_token_program;
+    let mint_ix = build_mint_gm_instruction(&mint_accounts, trade_info.gm_token_amount.0);
+
+    // The compute unit limit goes first, then an unsigned memo tagging the simulation,
+    // if requested
+    let compute_limit_ix = options
+        .compute_unit_limit
+        .map(solana_compute_budget_interface::ComputeBudgetInstruction::set_compute_unit_limit);
+    let memo_ix = options
+        .memo
+        .as_ref()
+        .map(|memo| spl_memo::build_memo(memo.as_bytes(), &[]));
+
+    [
+        compute_limit_ix,
+        memo_ix,
+        Some(create_taker_gm_ata_ix),
+        Some(create_maker_gm_ata_ix),
+        Some(create_taker_quote_ata_ix),
+        create_maker_quote_ata_ix,
+        Some(mint_ix),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
 
-    let message = Message::new_with_blockhash(
-        &[
-            create_taker_gm_ata_ix,
-            create_maker_gm_ata_ix,
-            create_taker_usdc_ata_ix,
-            create_maker_usdc_ata_ix,
-            mint_ix,
-        ],
-        Some(&minter),
-        &recent_blockhash,
-    );
-    Transaction::new_unsigned(message)
+/// Estimate the lamports the admin minter needs to fund the mock mint's ATAs.
+///
+/// `build_mock_mint_transaction` idempotently creates the taker's and maker's GM and
+/// USDC ATAs with the admin minter as payer. This sums the rent-exempt minimum of all
+/// four, assuming none exist yet, so it is an upper bound. Token-2022 ATAs carry the
+/// `ImmutableOwner` extension and are larger than SPL Token accounts.
+///
+/// # Arguments
+///
+/// * `trade_info` - The GM trade info from `check_gm_trade`
+/// * `rent` - The rent parameters to price account storage with
+pub fn estimate_mock_mint_rent(trade_info: &GmTradeInfo, rent: &Rent) -> u64 {
+    let ata_rent = |token_program: &Pubkey| rent.minimum_balance(ata_len(token_program));
+
+    // Taker and maker ATAs for each leg
+    2 * ata_rent(&trade_info.output_token_program) + 2 * ata_rent(&trade_info.input_token_program)
+}
+
+/// List the accounts the mock mint transaction writes to.
+///
+/// Derived from the account metas of `build_mock_mint_transaction`'s instructions (the
+/// admin minter as payer, the four ATAs, the GM mint, the `mint_gm` PDAs, ...), in
+/// first-use order without duplicates. Useful for lock-contention analysis and for
+/// building address lookup tables.
+///
+/// # Arguments
+///
+/// * `trade_info` - The GM trade info from `check_gm_trade`
+pub fn mock_mint_writable_accounts(trade_info: &GmTradeInfo) -> Vec<Pubkey> {
+    let mut writable = Vec::new();
+    for meta in build_mock_mint_instructions(trade_info)
+        .iter()
+        .flat_map(|ix| &ix.accounts)
+        .filter(|meta| meta.is_writable)
+    {
+        if !writable.contains(&meta.pubkey) {
+            writable.push(meta.pubkey);
+        }
+    }
+    writable
+}
+
+/// List every account the mock mint references, for an address lookup table.
+///
+/// The program ids and account metas of `build_mock_mint_instructions` (the ATAs,
+/// the GM mint, the `mint_gm` PDAs, the Ondo GM and token programs, ...), in first-use
+/// order without duplicates, ready for `extend_lookup_table`. Signers (the admin
+/// minter) are left out since a transaction must list them as static keys.
+///
+/// # Arguments
+///
+/// * `trade_info` - The GM trade info from `check_gm_trade`
+pub fn mock_mint_lookup_addresses(trade_info: &GmTradeInfo) -> Vec<Pubkey> {
+    let mut addresses = Vec::new();
+    for ix in build_mock_mint_instructions(trade_info) {
+        let non_signers = ix
+            .accounts
+            .iter()
+            .filter(|meta| !meta.is_signer)
+            .map(|meta| meta.pubkey);
+        for address in std::iter::once(ix.program_id).chain(non_signers) {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+    }
+    addresses
+}
+
+/// Size of an ATA created by the associated token account program under `token_program`
+fn ata_len(token_program: &Pubkey) -> usize {
+    use spl_token_2022::{extension::ExtensionType, state::Account};
+
+    if *token_program == crate::constants::token_2022_program_id() {
+        ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::ImmutableOwner])
+            .expect("ImmutableOwner fits in an account")
+    } else {
+        <Account as solana_sdk::program_pack::Pack>::LEN
+    }
 }
 
 /// Build a mock mint instruction for bundle simulation.
@@ -264,7 +970,7 @@ pub fn build_mock_mint_instruction(trade_info: &GmTradeInfo) -> Instruction {
     build_mock_mint_gm_instruction(
         &trade_info.gm_token_mint,
         &trade_info.maker,
-        trade_info.gm_token_amount,
+        trade_info.gm_token_amount.0,
     )
 }
 
@@ -285,7 +991,7 @@ pub fn build_mock_mint_instruction_to_ata(trade_info: &GmTradeInfo) -> Instructi
         &trade_info.gm_token_mint,
         &trade_info.maker_output_account,
         &trade_info.maker, // Pass maker as the destination owner
-        trade_info.gm_token_amount,
+        trade_info.gm_token_amount.0,
     )
 }
 
@@ -299,7 +1005,7 @@ pub fn build_mock_mint_instruction_to_ata(trade_info: &GmTradeInfo) -> Instructi
 /// # Returns
 ///
 /// * `Ok(Some(Transaction))` - A mock mint transaction if this is a GM trade
-/// * `Ok(None)` - If this is not a GM trade
+/// * `Ok(None)` - If this is not a GM trade, or the transaction already mints with `mint_gm`
 /// * `Err` - If there's an error parsing or validating
 ///
 /// # Example
@@ -320,6 +1026,11 @@ pub fn maybe_build_mock_mint(
     transaction: &Transaction,
     recent_blockhash: Hash,
 ) -> Result<Option<Transaction>, GmSimulatorError> {
+    // A transaction that already mints (and so writes the GM mint) needs no mock mint
+    if contains_mint_gm(&transaction.message) {
+        return Ok(None);
+    }
+
     let result = check_gm_trade(transaction)?;
 
     if let Some(trade_info) = result.trade_info {
@@ -332,9 +1043,55 @@ pub fn maybe_build_mock_mint(
     }
 }
 
-/// Simulate a bundle of transactions using Jito's simulateBundle RPC method.
+/// Same as `maybe_build_mock_mint`, but derives the mock mint's ATAs from each mint's
+/// on-chain token program.
 ///
-/// This function sends the transactions to a Jito-enabled RPC endpoint for bundle simulation,
+/// `maybe_build_mock_mint` trusts the token programs the fill references. This looks up
+/// the owners of the GM and quote mints with `token_program_for_mint` instead, so a GM
+/// mint owned by SPL Token gets SPL-derived ATAs even if the fill names Token-2022.
+///
+/// # Arguments
+///
+/// * `transaction` - The transaction to check
+/// * `recent_blockhash` - A recent blockhash for the mock mint transaction
+/// * `rpc_url` - A Solana RPC URL
+pub fn maybe_build_mock_mint_with_rpc(
+    transaction: &Transaction,
+    recent_blockhash: Hash,
+    rpc_url: &str,
+) -> Result<Option<Transaction>, GmSimulatorError> {
+    maybe_build_mock_mint_with_token_programs(transaction, recent_blockhash, |mint| {
+        crate::rpc::token_program_for_mint(mint, rpc_url)
+    })
+}
+
+/// `maybe_build_mock_mint` with the GM and quote token programs taken from `token_program`
+fn maybe_build_mock_mint_with_token_programs(
+    transaction: &Transaction,
+    recent_blockhash: Hash,
+    token_program: impl Fn(&Pubkey) -> Result<Pubkey, GmSimulatorError>,
+) -> Result<Option<Transaction>, GmSimulatorError> {
+    if contains_mint_gm(&transaction.message) {
+        return Ok(None);
+    }
+
+    let Some(trade_info) = check_gm_trade(transaction)?.trade_info else {
+        return Ok(None);
+    };
+    let trade_info = GmTradeInfo {
+        output_token_program: token_program(&trade_info.gm_token_mint)?,
+        input_token_program: token_program(&trade_info.quote_mint)?,
+        ..trade_info
+    };
+    Ok(Some(build_mock_mint_transaction(
+        &trade_info,
+        recent_blockhash,
+    )))
+}
+
+/// Simulate a bundle of transactions using Jito's simulateBundle RPC method.
+///
+/// This function sends the transactions to a Jito-enabled RPC endpoint for bundle simulation,
 /// and extracts balance changes for the taker account from the Jupiter RFQ fill transaction.
 ///
 /// # Arguments
@@ -351,6 +1108,10 @@ pub fn maybe_build_mock_mint(
 /// - `taker_balance_changes`: Balance changes for the taker's token accounts
 /// - `logs`: Optional simulation logs
 ///
+/// Returns `Err(GmSimulatorError::SerializationFailed)` instead of panicking if a
+/// transaction cannot be serialized. `Err(RpcUnreachable)` (connection or DNS failure)
+/// and `Err(RpcHttpStatus)` (non-2xx response) let callers fail over to another RPC.
+///
 /// # Example
 ///
 /// ```ignore
@@ -377,37 +1138,499 @@ pub fn simulate_as_bundle(
     trade_info: &crate::types::GmTradeInfo,
     rpc_url: &str,
 ) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
-    use base64::Engine;
-    use crate::types::BundleSimulationResult;
-    use crate::constants::{get_gm_token_symbol, usdc_mint};
+    simulate_as_bundle_with_options(
+        transactions,
+        trade_info,
+        rpc_url,
+        &crate::types::SimulateOptions::default(),
+    )
+}
+
+/// Simulate a bundle of transactions using Jito's simulateBundle RPC method with custom options.
+///
+/// Same as `simulate_as_bundle`, but allows injecting account state overrides and other
+/// request options via `SimulateOptions`.
+///
+/// # Arguments
+///
+/// * `transactions` - Vector of transactions to simulate as a bundle (typically [mock_mint_tx, fill_tx])
+/// * `trade_info` - The GM trade info containing taker and token information
+/// * `rpc_url` - The Jito-enabled RPC URL to use for simulation
+/// * `options` - Request options (account overrides, ...)
+pub fn simulate_as_bundle_with_options(
+    transactions: Vec<Transaction>,
+    trade_info: &crate::types::GmTradeInfo,
+    rpc_url: &str,
+    options: &crate::types::SimulateOptions,
+) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
+    simulate_as_bundle_with_transcript(transactions, trade_info, rpc_url, options)
+        .map(|transcript| transcript.result)
+}
 
+/// Simulate a bundle and keep the raw request and response alongside the result.
+///
+/// Same as `simulate_as_bundle_with_options`, but returns a `SimulationTranscript`
+/// holding the `simulateBundle` request body and the RPC's response, e.g. to store for
+/// later replay through `parse_simulate_bundle_response`.
+///
+/// # Arguments
+///
+/// * `transactions` - Vector of transactions to simulate as a bundle (typically [mock_mint_tx, fill_tx])
+/// * `trade_info` - The GM trade info containing taker and token information
+/// * `rpc_url` - The Jito-enabled RPC URL to use for simulation
+/// * `options` - Request options (account overrides, ...)
+pub fn simulate_as_bundle_with_transcript(
+    transactions: Vec<Transaction>,
+    trade_info: &crate::types::GmTradeInfo,
+    rpc_url: &str,
+    options: &crate::types::SimulateOptions,
+) -> Result<crate::types::SimulationTranscript, GmSimulatorError> {
     // Encode transactions as base64
-    let encoded_txs: Vec<String> = transactions
-        .iter()
-        .map(|tx| {
-            base64::engine::general_purpose::STANDARD.encode(
-                bincode::serialize(tx).expect("Failed to serialize transaction"),
-            )
-        })
-        .collect();
+    let encoded_txs = crate::transaction::encode_bundle(&transactions)?;
+
+    let request = build_simulate_bundle_request(encoded_txs, trade_info, options);
+    let response = send_simulate_bundle(&request, rpc_url, options)?;
+
+    bundle_transcript(request, response, &transactions, trade_info, options)
+}
+
+/// Parse a `simulateBundle` response into a transcript of the exchange
+fn bundle_transcript(
+    request: serde_json::Value,
+    response: serde_json::Value,
+    transactions: &[Transaction],
+    trade_info: &GmTradeInfo,
+    options: &crate::types::SimulateOptions,
+) -> Result<crate::types::SimulationTranscript, GmSimulatorError> {
+    let mut result = parse_bundle_response(&response, trade_info, options.max_logs)?;
+    result.priority_fee_lamports = mock_mint_priority_fee(transactions.first(), &response);
+    if !options.fetch_logs {
+        result.logs = None;
+    }
+    Ok(crate::types::SimulationTranscript {
+        request,
+        response,
+        result,
+    })
+}
+
+/// Simulate a bundle that settles several GM trades for one taker.
+///
+/// For fills of more than one GM token, e.g. one fill transaction with a fill per
+/// token. The bundle is the mock mints followed by the fill transaction, which must be
+/// last. The taker's quote (e.g. USDC) account and, for each distinct GM mint, the
+/// taker's GM account and the mint itself are requested before and after the fill.
+/// Each GM balance change uses the decimals read from its simulated mint account (9 if
+/// the RPC returns no mint data), so tokens of different precision aggregate correctly.
+///
+/// # Arguments
+///
+/// * `transactions` - The bundle, `[mock_mint_txs..., fill_tx]`
+/// * `trade_infos` - The trades the fill transaction settles
+/// * `rpc_url` - The Jito-enabled RPC URL to use for simulation
+/// * `options` - Request options (account overrides, ...)
+///
+/// # Returns
+///
+/// * `Ok(BundleSimulationResult)` with a quote change and one change per GM mint
+/// * `Err(InvalidBundle)` if there are no transactions or trades, or the trades have
+///   different takers
+pub fn simulate_trades_as_bundle(
+    transactions: Vec<Transaction>,
+    trade_infos: &[GmTradeInfo],
+    rpc_url: &str,
+    options: &crate::types::SimulateOptions,
+) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
+    if transactions.is_empty() {
+        return Err(GmSimulatorError::InvalidBundle(
+            "bundle has no transactions".to_string(),
+        ));
+    }
+    let Some(first) = trade_infos.first() else {
+        return Err(GmSimulatorError::InvalidBundle(
+            "no trades to simulate".to_string(),
+        ));
+    };
+    if trade_infos.iter().any(|t| t.taker != first.taker) {
+        return Err(GmSimulatorError::InvalidBundle(
+            "trades have different takers".to_string(),
+        ));
+    }
+
+    let fill_index = transactions.len() - 1;
+    let encoded_txs = crate::transaction::encode_bundle(&transactions)?;
+
+    let request_body = build_simulate_trades_request(encoded_txs, trade_infos, options);
+    let json = send_simulate_bundle(&request_body, rpc_url, options)?;
+
+    let mut result =
+        parse_trades_bundle_response(&json, trade_infos, fill_index, options.max_logs)?;
+    result.priority_fee_lamports = mock_mint_priority_fee(transactions.first(), &json);
+    if !options.fetch_logs {
+        result.logs = None;
+    }
+    Ok(result)
+}
+
+/// Simulate the mock mint transaction on its own.
+///
+/// Sends a single-transaction `simulateBundle` request, so a failing bundle can be
+/// narrowed down to the mock mint (stale oracle, wrong PDA, ...) or the fill. The
+/// result's `logs` and `bundle_error` describe the mock mint (transaction index 0);
+/// `taker_balance_changes` is always empty. Account overrides, `slot`, `max_logs` and
+/// `fetch_logs` from `options` are honored.
+///
+/// # Arguments
+///
+/// * `mock_mint_tx` - The mock mint transaction from `build_mock_mint_transaction`
+/// * `rpc_url` - The Jito-enabled RPC URL to use for simulation
+/// * `options` - Request options (account overrides, ...)
+pub fn simulate_mock_mint_only(
+    mock_mint_tx: &Transaction,
+    rpc_url: &str,
+    options: &crate::types::SimulateOptions,
+) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
+    let encoded_txs = crate::transaction::encode_bundle(std::slice::from_ref(mock_mint_tx))?;
+
+    let request_body = simulate_bundle_request(encoded_txs, vec![None], options);
+    let json = send_simulate_bundle(&request_body, rpc_url, options)?;
+
+    let mut result = parse_mock_mint_response(&json, options.max_logs)?;
+    result.priority_fee_lamports = mock_mint_priority_fee(Some(mock_mint_tx), &json);
+    if !options.fetch_logs {
+        result.logs = None;
+    }
+    Ok(result)
+}
+
+/// Priority fee the mock mint (transaction 0) pays at its compute-unit price
+///
+/// `None` if the transaction has no `SetComputeUnitPrice` instruction or the response
+/// has no `unitsConsumed` for it. The fee is the price in micro-lamports per unit times
+/// the units consumed, rounded up to whole lamports. The runtime charges for the
+/// requested compute-unit limit instead, so the fee actually paid can be higher.
+fn mock_mint_priority_fee(
+    mock_mint_tx: Option<&Transaction>,
+    json: &serde_json::Value,
+) -> Option<u64> {
+    let price = compute_unit_price(mock_mint_tx?)?;
+    let units = json
+        .get("result")?
+        .get("value")?
+        .get("transactionResults")?
+        .get(0)?
+        .get("unitsConsumed")?
+        .as_u64()?;
+
+    let micro_lamports = u128::from(price) * u128::from(units);
+    u64::try_from(micro_lamports.div_ceil(1_000_000)).ok()
+}
+
+/// The micro-lamport price set by a transaction's `SetComputeUnitPrice` instruction
+fn compute_unit_price(tx: &Transaction) -> Option<u64> {
+    // Borsh-encoded ComputeBudgetInstruction: variant 3 is SetComputeUnitPrice(u64)
+    const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+    tx.message.instructions.iter().find_map(|ix| {
+        let program_id = tx.message.account_keys.get(ix.program_id_index as usize)?;
+        if *program_id != solana_compute_budget_interface::id() {
+            return None;
+        }
+        match ix.data.split_first()? {
+            (&SET_COMPUTE_UNIT_PRICE, price) => Some(u64::from_le_bytes(price.try_into().ok()?)),
+            _ => None,
+        }
+    })
+}
+
+/// Fetch a transaction by signature and simulate it as a GM bundle.
+///
+/// Runs the whole BUY workflow in one call: fetches the confirmed transaction,
+/// detects the GM trade, builds the mock mint, refreshes both transactions with the
+/// latest blockhash, moves the fill's `expire_at` an hour into the future (the
+/// original quote has usually expired) and simulates `[mock_mint, fill]`.
+///
+/// # Arguments
+///
+/// * `signature` - Signature of the fill transaction
+/// * `rpc_url` - RPC URL used to fetch the transaction and blockhash, and to simulate
+/// * `options` - Request options for the simulation
+///
+/// # Returns
+///
+/// * `Ok(BundleSimulationResult)` from the simulation
+/// * `Err(NotGmBuy)` if the transaction is not a GM BUY fill
+/// * `Err(TransactionDecodeError)` if the transaction can't be fetched, or is a v0
+///   transaction that loads accounts from lookup tables
+pub fn simulate_by_signature(
+    signature: &Signature,
+    rpc_url: &str,
+    options: &crate::types::SimulateOptions,
+) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
+    let versioned = crate::rpc::fetch_transaction(signature, rpc_url)?;
+    let message = match versioned.message {
+        VersionedMessage::Legacy(message) => message,
+        VersionedMessage::V0(message) => {
+            // Accounts loaded from lookup tables can't be expressed in a legacy message
+            if !message.address_table_lookups.is_empty() {
+                return Err(GmSimulatorError::TransactionDecodeError(format!(
+                    "transaction {} loads accounts from address lookup tables",
+                    signature
+                )));
+            }
+            Message {
+                header: message.header,
+                account_keys: message.account_keys,
+                recent_blockhash: message.recent_blockhash,
+                instructions: message.instructions,
+            }
+        }
+    };
+    let fill_tx = Transaction {
+        signatures: versioned.signatures,
+        message,
+    };
+
+    let result = check_gm_trade(&fill_tx)?;
+    let mut trade_info = match result.trade_info {
+        Some(trade_info) if result.use_gm_bundle_sim => trade_info,
+        _ => {
+            let reason = result.reason.map_or("not a GM trade", |r| r.description());
+            return Err(GmSimulatorError::NotGmBuy(reason.to_string()));
+        }
+    };
+
+    let blockhash = crate::rpc::fetch_latest_blockhash(rpc_url)?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    trade_info.expire_at = now + 3600;
+
+    let fill_tx = crate::transaction::with_blockhash(
+        &with_fill_expire_at(&fill_tx, trade_info.expire_at),
+        blockhash,
+    );
+    let mock_mint_tx = build_mock_mint_transaction(&trade_info, blockhash);
+
+    simulate_as_bundle_with_options(vec![mock_mint_tx, fill_tx], &trade_info, rpc_url, options)
+}
+
+/// Rebuild a fill transaction with a different `expire_at` in its fill instruction
+///
+/// The returned transaction is unsigned. Transactions without a fill are returned
+/// unchanged apart from their signatures.
+fn with_fill_expire_at(tx: &Transaction, expire_at: i64) -> Transaction {
+    let mut message = tx.message.clone();
+    let fill = find_fill_instruction(&tx.message.instructions, &tx.message.account_keys);
+    if let Some((fill, layout)) = fill {
+        let index = tx
+            .message
+            .instructions
+            .iter()
+            .position(|ix| std::ptr::eq(ix, fill))
+            .expect("fill is one of the message's instructions");
+        let offset = layout.expire_at_offset;
+        message.instructions[index].data[offset..offset + 8]
+            .copy_from_slice(&expire_at.to_le_bytes());
+    }
+    Transaction::new_unsigned(message)
+}
+
+/// POST a simulateBundle request and parse the JSON response body
+///
+/// Each attempt times out after `options.timeout`; failed sends are retried up to
+/// `options.max_retries` times.
+fn send_simulate_bundle(
+    request_body: &serde_json::Value,
+    rpc_url: &str,
+    options: &crate::types::SimulateOptions,
+) -> Result<serde_json::Value, GmSimulatorError> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(options.timeout)
+        .build()
+        .map_err(|e| {
+            GmSimulatorError::InstructionParseError(format!("HTTP client failed: {}", e))
+        })?;
+
+    let send = || {
+        let mut request = client
+            .post(rpc_url)
+            .header("Content-Type", "application/json");
+        for (name, value) in &options.headers {
+            request = request.header(name, value);
+        }
+        request.json(request_body).send()
+    };
+    let mut result = send();
+    for _ in 0..options.max_retries {
+        if result.is_ok() {
+            break;
+        }
+        result = send();
+    }
+    let response = result.map_err(|e| {
+        if e.is_connect() {
+            // DNS failures and refused connections: worth failing over to another RPC
+            GmSimulatorError::RpcUnreachable(e.to_string())
+        } else {
+            GmSimulatorError::InstructionParseError(format!("HTTP request failed: {}", e))
+        }
+    })?;
+    if !response.status().is_success() {
+        return Err(GmSimulatorError::RpcHttpStatus(response.status().as_u16()));
+    }
+
+    let response_text = response.text().map_err(|e| {
+        GmSimulatorError::InstructionParseError(format!("Failed to read response: {}", e))
+    })?;
+
+    serde_json::from_str(&response_text).map_err(|e| {
+        GmSimulatorError::InstructionParseError(format!("Failed to parse JSON: {}", e))
+    })
+}
+
+/// Build the Jito simulateBundle JSON-RPC request body
+fn build_simulate_bundle_request(
+    encoded_txs: Vec<String>,
+    trade_info: &GmTradeInfo,
+    options: &crate::types::SimulateOptions,
+) -> serde_json::Value {
+    use crate::types::AccountsConfig;
 
     // Derive the taker's token accounts for pre/post balance checking
     // For the fill transaction (second tx), we want to track:
-    // - Taker's input token account (USDC for BUY, GM for SELL)
-    // - Taker's output token account (GM for BUY, USDC for SELL)
-    let taker_usdc_ata = spl_associated_token_account::get_associated_token_address(
-        &trade_info.taker,
-        &usdc_mint(),
-    );
+    // - Taker's quote token account (USDC or USDT it pays with)
+    // - Taker's GM token account
+    let taker_quote_ata =
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &trade_info.taker,
+            &trade_info.quote_mint,
+            &trade_info.input_token_program,
+        );
     let taker_gm_ata = spl_associated_token_account::get_associated_token_address_with_program_id(
         &trade_info.taker,
         &trade_info.gm_token_mint,
-        &crate::constants::token_2022_program_id(),
+        &trade_info.output_token_program,
+    );
+
+    // One accounts config per transaction: nothing for the mock mint (index 0),
+    // the taker's token accounts for the fill (index 1)
+    let accounts_configs = vec![
+        None,
+        Some(AccountsConfig {
+            addresses: vec![taker_quote_ata, taker_gm_ata],
+        }),
+    ];
+
+    simulate_bundle_request(encoded_txs, accounts_configs, options)
+}
+
+/// The taker's account for the first trade's quote mint and each distinct GM mint with
+/// the taker's account for it
+///
+/// `trade_infos` must be non-empty and share a taker.
+fn trade_accounts(trade_infos: &[GmTradeInfo]) -> (Pubkey, Vec<(Pubkey, Pubkey)>) {
+    use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+    let first = &trade_infos[0];
+    let taker_quote_ata = get_associated_token_address_with_program_id(
+        &first.taker,
+        &first.quote_mint,
+        &first.input_token_program,
     );
 
+    let mut gm_accounts: Vec<(Pubkey, Pubkey)> = Vec::new();
+    for trade_info in trade_infos {
+        if gm_accounts
+            .iter()
+            .all(|(mint, _)| *mint != trade_info.gm_token_mint)
+        {
+            let taker_gm_ata = get_associated_token_address_with_program_id(
+                &trade_info.taker,
+                &trade_info.gm_token_mint,
+                &trade_info.output_token_program,
+            );
+            gm_accounts.push((trade_info.gm_token_mint, taker_gm_ata));
+        }
+    }
+
+    (taker_quote_ata, gm_accounts)
+}
+
+/// Build the simulateBundle request body for `simulate_trades_as_bundle`
+///
+/// Accounts are requested for the last transaction only: the taker's quote account,
+/// then the taker's GM account and the mint for each distinct GM mint.
+fn build_simulate_trades_request(
+    encoded_txs: Vec<String>,
+    trade_infos: &[GmTradeInfo],
+    options: &crate::types::SimulateOptions,
+) -> serde_json::Value {
+    use crate::types::AccountsConfig;
+
+    let (taker_quote_ata, gm_accounts) = trade_accounts(trade_infos);
+    let mut addresses = vec![taker_quote_ata];
+    for (mint, taker_gm_ata) in gm_accounts {
+        addresses.push(taker_gm_ata);
+        addresses.push(mint);
+    }
+
+    let mut accounts_configs = vec![None; encoded_txs.len().saturating_sub(1)];
+    accounts_configs.push(Some(AccountsConfig { addresses }));
+
+    simulate_bundle_request(encoded_txs, accounts_configs, options)
+}
+
+/// Build a simulateBundle request body with one accounts config per transaction
+fn simulate_bundle_request(
+    encoded_txs: Vec<String>,
+    accounts_configs: Vec<Option<crate::types::AccountsConfig>>,
+    options: &crate::types::SimulateOptions,
+) -> serde_json::Value {
+    use base64::Engine;
+
     // Build the Jito simulateBundle request with pre/post execution account configs
-    // We want post-execution accounts for the fill transaction (index 1)
-    let request_body = serde_json::json!({
+    let mut config = serde_json::json!({
+        "preExecutionAccountsConfigs": accounts_configs,
+        "postExecutionAccountsConfigs": accounts_configs,
+        "replaceRecentBlockhash": options.replace_recent_blockhash,
+        "skipSigVerify": options.skip_sig_verify,
+        "simulationBank": {
+            "commitment": {
+                "commitment": options.commitment
+            }
+        }
+    });
+
+    // Inject account state overrides keyed by address
+    if !options.account_overrides.is_empty() {
+        let overrides: serde_json::Map<String, serde_json::Value> = options
+            .account_overrides
+            .iter()
+            .map(|(address, account)| {
+                (
+                    address.to_string(),
+                    serde_json::json!({
+                        "lamports": account.lamports,
+                        "data": [
+                            base64::engine::general_purpose::STANDARD.encode(&account.data),
+                            "base64"
+                        ],
+                        "owner": account.owner.to_string(),
+                    }),
+                )
+            })
+            .collect();
+        config["accountOverrides"] = serde_json::Value::Object(overrides);
+    }
+
+    // Pin the simulation to a specific slot instead of the processed bank
+    if let Some(slot) = options.slot {
+        config["simulationBank"] = serde_json::json!({ "slot": slot });
+    }
+
+    serde_json::json!({
         "jsonrpc": "2.0",
         "id": 1,
         "method": "simulateBundle",
@@ -415,50 +1638,179 @@ pub fn simulate_as_bundle(
             {
                 "encodedTransactions": encoded_txs
             },
-            {
-                "preExecutionAccountsConfigs": [
-                    null,  // Don't need pre for mock mint
-                    { "addresses": [taker_usdc_ata.to_string(), taker_gm_ata.to_string()] }
-                ],
-                "postExecutionAccountsConfigs": [
-                    null,  // Don't need post for mock mint
-                    { "addresses": [taker_usdc_ata.to_string(), taker_gm_ata.to_string()] }
-                ],
-                "replaceRecentBlockhash": true,
-                "skipSigVerify": true,
-                "simulationBank": {
-                    "commitment": {
-                        "commitment": "processed"
-                    }
-                }
-            }
+            config
         ]
-    });
+    })
+}
+
+/// Parse a Jito `simulateBundle` JSON-RPC response into a `BundleSimulationResult`.
+///
+/// Bundle-level failures (an RPC error or a failed bundle summary) and transaction-level
+/// failures (the fill returning an error) are both surfaced through `bundle_error`.
+/// Balance changes are extracted for the taker's USDC and GM token accounts from the
+/// fill transaction (index 1).
+///
+/// # Arguments
+///
+/// * `json` - The full JSON-RPC response body
+/// * `trade_info` - The GM trade info containing taker and token information
+///
+/// # Returns
+///
+/// * `Ok(BundleSimulationResult)` for any response Jito produced, successful or not
+/// * `Err` if the response is missing required fields
+pub fn parse_simulate_bundle_response(
+    json: &serde_json::Value,
+    trade_info: &GmTradeInfo,
+) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
+    parse_bundle_response(json, trade_info, None)
+}
+
+/// Parse a `simulateBundle` response, keeping at most `max_logs` fill logs
+fn parse_bundle_response(
+    json: &serde_json::Value,
+    trade_info: &GmTradeInfo,
+    max_logs: Option<usize>,
+) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
+    use crate::constants::{get_gm_token_symbol, stable_mint_symbol, STABLE_DECIMALS};
+
+    parse_fill_response(json, 1, max_logs, |pre_accounts, post| {
+        let pre = |index: usize| pre_accounts.and_then(|pre| pre.get(index));
+
+        // Derive the taker's token accounts requested in the pre/post execution configs
+        let taker_quote_ata =
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                &trade_info.taker,
+                &trade_info.quote_mint,
+                &trade_info.input_token_program,
+            );
+        let taker_gm_ata =
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                &trade_info.taker,
+                &trade_info.gm_token_mint,
+                &trade_info.output_token_program,
+            );
+
+        let mut taker_balance_changes = Vec::new();
+
+        // Process quote (USDC/USDT) balance change (index 0)
+        if let Some(post_quote) = post.first() {
+            if let Some(change) = parse_token_balance_change(
+                pre(0),
+                post_quote,
+                &trade_info.quote_mint,
+                stable_mint_symbol(&trade_info.quote_mint).map(str::to_string),
+                &trade_info.taker,
+                &taker_quote_ata,
+                STABLE_DECIMALS,
+            ) {
+                taker_balance_changes.push(change);
+            }
+        }
+
+        // Process GM token balance change (index 1)
+        if let Some(post_gm) = post.get(1) {
+            if let Some(change) = parse_token_balance_change(
+                pre(1),
+                post_gm,
+                &trade_info.gm_token_mint,
+                Some(
+                    get_gm_token_symbol(&trade_info.gm_token_mint)
+                        .unwrap_or("GM")
+                        .to_string(),
+                ),
+                &trade_info.taker,
+                &taker_gm_ata,
+                9, // GM tokens have 9 decimals
+            ) {
+                taker_balance_changes.push(change);
+            }
+        }
 
-    // Send the request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .post(rpc_url)
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .map_err(|e| GmSimulatorError::InstructionParseError(format!("HTTP request failed: {}", e)))?;
+        taker_balance_changes
+    })
+}
+
+/// Parse a `simulate_trades_as_bundle` response, keeping at most `max_logs` fill logs
+fn parse_trades_bundle_response(
+    json: &serde_json::Value,
+    trade_infos: &[GmTradeInfo],
+    fill_index: usize,
+    max_logs: Option<usize>,
+) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
+    use crate::constants::{get_gm_token_symbol, stable_mint_symbol, STABLE_DECIMALS};
+
+    let taker = trade_infos[0].taker;
+    let quote_mint = trade_infos[0].quote_mint;
+    let (taker_quote_ata, gm_accounts) = trade_accounts(trade_infos);
+
+    parse_fill_response(json, fill_index, max_logs, |pre_accounts, post| {
+        let pre = |index: usize| pre_accounts.and_then(|pre| pre.get(index));
+        let mut taker_balance_changes = Vec::new();
+
+        // Quote (USDC/USDT) balance change (index 0)
+        if let Some(change) = post.first().and_then(|post_quote| {
+            parse_token_balance_change(
+                pre(0),
+                post_quote,
+                &quote_mint,
+                stable_mint_symbol(&quote_mint).map(str::to_string),
+                &taker,
+                &taker_quote_ata,
+                STABLE_DECIMALS,
+            )
+        }) {
+            taker_balance_changes.push(change);
+        }
+
+        // One GM token account (index 1 + 2k) and its mint (index 2 + 2k) per GM mint
+        for (k, (mint, taker_gm_ata)) in gm_accounts.iter().enumerate() {
+            let ata_index = 1 + 2 * k;
+            let decimals = post
+                .get(ata_index + 1)
+                .and_then(parse_mint_decimals)
+                .or_else(|| pre(ata_index + 1).and_then(parse_mint_decimals))
+                .unwrap_or(9);
+            let symbol = get_gm_token_symbol(mint).unwrap_or("GM").to_string();
+
+            if let Some(change) = post.get(ata_index).and_then(|post_gm| {
+                parse_token_balance_change(
+                    pre(ata_index),
+                    post_gm,
+                    mint,
+                    Some(symbol),
+                    &taker,
+                    taker_gm_ata,
+                    decimals,
+                )
+            }) {
+                taker_balance_changes.push(change);
+            }
+        }
 
-    let response_text = response
-        .text()
-        .map_err(|e| GmSimulatorError::InstructionParseError(format!("Failed to read response: {}", e)))?;
+        taker_balance_changes
+    })
+}
 
-    let json: serde_json::Value = serde_json::from_str(&response_text)
-        .map_err(|e| GmSimulatorError::InstructionParseError(format!("Failed to parse JSON: {}", e)))?;
+/// Parse a `simulateBundle` response whose fill is transaction `fill_index`
+///
+/// Handles RPC errors, bundle failures and the fill's own error, and collects up to
+/// `max_logs` fill logs. `balance_changes` is called with the fill's pre-execution
+/// accounts (`None` for post-only responses) and post-execution accounts.
+fn parse_fill_response(
+    json: &serde_json::Value,
+    fill_index: usize,
+    max_logs: Option<usize>,
+    balance_changes: impl FnOnce(
+        Option<&[serde_json::Value]>,
+        &[serde_json::Value],
+    ) -> Vec<crate::types::BalanceChange>,
+) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
+    use crate::types::{BundleError, BundleSimulationResult};
 
     // Check for RPC-level errors
     if let Some(error) = json.get("error") {
-        return Ok(BundleSimulationResult {
-            success: false,
-            error: Some(format!("RPC error: {}", error)),
-            taker_balance_changes: vec![],
-            logs: None,
-        });
+        return Ok(BundleSimulationResult::failed(classify_rpc_error(error)));
     }
 
     // Parse the result
@@ -478,27 +1830,43 @@ pub fn simulate_as_bundle(
             GmSimulatorError::InstructionParseError("Missing transactionResults".to_string())
         })?;
 
-    // Check if the fill transaction (index 1) succeeded
-    let fill_result = tx_results.get(1).ok_or_else(|| {
-        GmSimulatorError::InstructionParseError("Missing fill transaction result".to_string())
-    })?;
+    // Check for a bundle-level failure in the summary ("succeeded" or {"failed": {...}})
+    let summary_error = value
+        .get("summary")
+        .and_then(|s| s.get("failed"))
+        .map(|failure| classify_bundle_failure(failure, tx_results));
+
+    // Check if the fill transaction succeeded
+    let Some(fill_result) = tx_results.get(fill_index) else {
+        // The bundle stopped before the fill executed
+        return match summary_error {
+            Some(bundle_error) => Ok(BundleSimulationResult::failed(bundle_error)),
+            None => Err(GmSimulatorError::InstructionParseError(
+                "Missing fill transaction result".to_string(),
+            )),
+        };
+    };
 
-    let fill_error = fill_result.get("err");
-    let success = fill_error.map_or(true, |v| v.is_null());
+    let bundle_error = match fill_result.get("err").filter(|v| !v.is_null()) {
+        Some(err) => Some(BundleError::TransactionFailed {
+            index: fill_index,
+            error: err.to_string(),
+        }),
+        None => summary_error,
+    };
+    let success = bundle_error.is_none();
 
-    // Collect logs from the fill transaction
+    // Collect logs from the fill transaction, up to `max_logs`
     let logs = fill_result
         .get("logs")
         .and_then(|l| l.as_array())
         .map(|arr| {
             arr.iter()
                 .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .take(max_logs.unwrap_or(usize::MAX))
                 .collect()
         });
 
-    // Extract balance changes from pre/post execution accounts
-    let mut taker_balance_changes = Vec::new();
-
     // Get pre-execution accounts for the fill tx
     let pre_accounts = fill_result
         .get("preExecutionAccounts")
@@ -509,55 +1877,243 @@ pub fn simulate_as_bundle(
         .get("postExecutionAccounts")
         .and_then(|v| v.as_array());
 
-    if let (Some(pre), Some(post)) = (pre_accounts, post_accounts) {
-        // Process USDC balance change (index 0)
-        if let (Some(pre_usdc), Some(post_usdc)) = (pre.get(0), post.get(0)) {
-            if let Some(change) = parse_token_balance_change(
-                pre_usdc,
-                post_usdc,
-                &usdc_mint(),
-                Some("USDC".to_string()),
-                &trade_info.taker,
-                &taker_usdc_ata,
-                6, // USDC has 6 decimals
-            ) {
-                taker_balance_changes.push(change);
-            }
-        }
-
-        // Process GM token balance change (index 1)
-        if let (Some(pre_gm), Some(post_gm)) = (pre.get(1), post.get(1)) {
-            if let Some(change) = parse_token_balance_change(
-                pre_gm,
-                post_gm,
-                &trade_info.gm_token_mint,
-                Some(get_gm_token_symbol(&trade_info.gm_token_mint)
-                    .unwrap_or("GM")
-                    .to_string()),
-                &trade_info.taker,
-                &taker_gm_ata,
-                9, // GM tokens have 9 decimals
-            ) {
-                taker_balance_changes.push(change);
-            }
-        }
-    }
+    // Some RPCs return only the post-execution accounts; the changes are still reported,
+    // flagged as having an unknown pre-balance
+    let taker_balance_changes = match post_accounts {
+        Some(post) => balance_changes(pre_accounts.map(Vec::as_slice), post),
+        None => Vec::new(),
+    };
 
     Ok(BundleSimulationResult {
         success,
-        error: if success {
-            None
-        } else {
-            Some(format!("Fill transaction failed: {:?}", fill_error))
-        },
+        error: bundle_error.as_ref().map(|e| e.to_string()),
+        bundle_error,
         taker_balance_changes,
         logs,
+        priority_fee_lamports: None,
+    })
+}
+
+/// Parse a single-transaction `simulateBundle` response for `simulate_mock_mint_only`
+fn parse_mock_mint_response(
+    json: &serde_json::Value,
+    max_logs: Option<usize>,
+) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
+    use crate::types::{BundleError, BundleSimulationResult};
+
+    if let Some(error) = json.get("error") {
+        return Ok(BundleSimulationResult::failed(classify_rpc_error(error)));
+    }
+
+    let value = json
+        .get("result")
+        .and_then(|r| r.get("value"))
+        .ok_or_else(|| {
+            GmSimulatorError::InstructionParseError("Missing result value in response".to_string())
+        })?;
+
+    let tx_results = value
+        .get("transactionResults")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| {
+            GmSimulatorError::InstructionParseError("Missing transactionResults".to_string())
+        })?;
+
+    let summary_error = value
+        .get("summary")
+        .and_then(|s| s.get("failed"))
+        .map(|failure| classify_bundle_failure(failure, tx_results));
+
+    let Some(mint_result) = tx_results.first() else {
+        return match summary_error {
+            Some(bundle_error) => Ok(BundleSimulationResult::failed(bundle_error)),
+            None => Err(GmSimulatorError::InstructionParseError(
+                "Missing mock mint transaction result".to_string(),
+            )),
+        };
+    };
+
+    let bundle_error = match mint_result.get("err").filter(|v| !v.is_null()) {
+        Some(err) => Some(BundleError::TransactionFailed {
+            index: 0,
+            error: err.to_string(),
+        }),
+        None => summary_error,
+    };
+
+    let logs = mint_result
+        .get("logs")
+        .and_then(|l| l.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .take(max_logs.unwrap_or(usize::MAX))
+                .collect()
+        });
+
+    Ok(BundleSimulationResult {
+        success: bundle_error.is_none(),
+        error: bundle_error.as_ref().map(|e| e.to_string()),
+        bundle_error,
+        taker_balance_changes: vec![],
+        logs,
+        priority_fee_lamports: None,
+    })
+}
+
+/// Parse a `simulateBundle` response from a provider that pre-decodes token balances.
+///
+/// Some providers (e.g. Helius, Triton) add a `tokenBalanceChanges` array to each
+/// transaction result. When the fill transaction (index 1) has one, the taker's entries
+/// are used directly instead of decoding raw account data; otherwise this falls back to
+/// `parse_simulate_bundle_response`. Each entry is expected to look like:
+///
+/// ```json
+/// { "tokenAccount": "...", "mint": "...", "owner": "...",
+///   "preAmount": "200000000", "postAmount": "0", "decimals": 6 }
+/// ```
+///
+/// Amounts may be JSON strings or numbers. Entries owned by other accounts are ignored.
+///
+/// # Arguments
+///
+/// * `json` - The full JSON-RPC response body
+/// * `trade_info` - The GM trade info containing taker and token information
+pub fn parse_enhanced_simulation(
+    json: &serde_json::Value,
+    trade_info: &GmTradeInfo,
+) -> Result<crate::types::BundleSimulationResult, GmSimulatorError> {
+    let mut result = parse_simulate_bundle_response(json, trade_info)?;
+
+    let enhanced = json
+        .pointer("/result/value/transactionResults/1/tokenBalanceChanges")
+        .and_then(|v| v.as_array());
+
+    if let Some(changes) = enhanced {
+        result.taker_balance_changes = changes
+            .iter()
+            .filter_map(|entry| parse_enhanced_balance_change(entry, trade_info))
+            .collect();
+    }
+
+    Ok(result)
+}
+
+/// Convert one provider `tokenBalanceChanges` entry into a taker `BalanceChange`
+fn parse_enhanced_balance_change(
+    entry: &serde_json::Value,
+    trade_info: &GmTradeInfo,
+) -> Option<crate::types::BalanceChange> {
+    use crate::constants::{get_gm_token_symbol, stable_mint_symbol};
+    use std::str::FromStr;
+
+    let pubkey = |key: &str| Pubkey::from_str(entry.get(key)?.as_str()?).ok();
+    let amount = |key: &str| match entry.get(key)? {
+        serde_json::Value::String(s) => s.parse::<u64>().ok(),
+        v => v.as_u64(),
+    };
+
+    let owner = pubkey("owner")?;
+    if owner != trade_info.taker {
+        return None;
+    }
+
+    let mint = pubkey("mint")?;
+    let pre_balance = amount("preAmount")?;
+    let post_balance = amount("postAmount")?;
+    let symbol = stable_mint_symbol(&mint)
+        .or_else(|| get_gm_token_symbol(&mint))
+        .map(|s| s.to_string());
+
+    Some(crate::types::BalanceChange {
+        mint,
+        symbol,
+        owner,
+        token_account: pubkey("tokenAccount")?,
+        pre_balance,
+        pre_balance_known: true,
+        post_balance,
+        change: post_balance as i128 - pre_balance as i128,
+        decimals: entry.get("decimals")?.as_u64()?.try_into().ok()?,
     })
 }
 
+/// Map a JSON-RPC `error` object onto a bundle-level `BundleError`
+fn classify_rpc_error(error: &serde_json::Value) -> crate::types::BundleError {
+    let message = error
+        .get("message")
+        .and_then(|m| m.as_str())
+        .map(|m| m.to_string())
+        .unwrap_or_else(|| error.to_string());
+    classify_bundle_message(&message)
+}
+
+/// Map the `summary.failed` object of a simulateBundle response onto a `BundleError`
+///
+/// Jito reports transaction failures as `{"TransactionFailure": [signature, message]}`
+/// and other bundle rejections as a bare variant name or an object keyed by it.
+fn classify_bundle_failure(
+    failure: &serde_json::Value,
+    tx_results: &[serde_json::Value],
+) -> crate::types::BundleError {
+    use crate::types::BundleError;
+
+    let error = failure.get("error").unwrap_or(failure);
+
+    if let Some(tx_failure) = error.get("TransactionFailure") {
+        let message = tx_failure
+            .get(1)
+            .and_then(|m| m.as_str())
+            .map(|m| m.to_string())
+            .unwrap_or_else(|| tx_failure.to_string());
+
+        if let BundleError::BlockhashNotFound = classify_bundle_message(&message) {
+            return BundleError::BlockhashNotFound;
+        }
+
+        // The failing transaction is the first one with an error, or the first one
+        // that never produced a result
+        let index = tx_results
+            .iter()
+            .position(|r| r.get("err").is_some_and(|e| !e.is_null()))
+            .unwrap_or(tx_results.len());
+
+        return BundleError::TransactionFailed {
+            index,
+            error: message,
+        };
+    }
+
+    let message = error
+        .as_str()
+        .map(|m| m.to_string())
+        .unwrap_or_else(|| error.to_string());
+    classify_bundle_message(&message)
+}
+
+/// Classify a free-form bundle failure message by its known Jito/RPC wording
+fn classify_bundle_message(message: &str) -> crate::types::BundleError {
+    use crate::types::BundleError;
+
+    let lower = message.to_ascii_lowercase();
+    if lower.contains("blockhash not found") || lower.contains("blockhashnotfound") {
+        BundleError::BlockhashNotFound
+    } else if lower.contains("simulation bank")
+        || lower.contains("bank not found")
+        || lower.contains("bank unavailable")
+    {
+        BundleError::SimulationBankUnavailable(message.to_string())
+    } else {
+        BundleError::BundleRejected(message.to_string())
+    }
+}
+
 /// Helper function to parse token balance change from Jito response
+///
+/// `pre_account` is `None` when the response has no pre-execution entry for the account,
+/// in which case the change is reported with `pre_balance_known` unset.
 fn parse_token_balance_change(
-    pre_account: &serde_json::Value,
+    pre_account: Option<&serde_json::Value>,
     post_account: &serde_json::Value,
     mint: &solana_sdk::pubkey::Pubkey,
     symbol: Option<String>,
@@ -565,27 +2121,45 @@ fn parse_token_balance_change(
     token_account: &solana_sdk::pubkey::Pubkey,
     decimals: u8,
 ) -> Option<crate::types::BalanceChange> {
-    // Parse pre-balance from the account data
-    let pre_balance = parse_token_account_balance(pre_account).unwrap_or(0);
-    let post_balance = parse_token_account_balance(post_account).unwrap_or(0);
+    // Parse balances from the account data (None if the account has no token data)
+    let pre = pre_account.and_then(parse_token_account_balance);
+    let post = parse_token_account_balance(post_account);
+
+    // Only return None when neither side has data, so an existing account holding
+    // zero is still reported
+    if pre.is_none() && post.is_none() {
+        return None;
+    }
 
+    let pre_balance = pre.unwrap_or(0);
+    let post_balance = post.unwrap_or(0);
     let change = post_balance as i128 - pre_balance as i128;
 
-    // Only return if there was a change or we have valid data
-    if pre_balance != 0 || post_balance != 0 || change != 0 {
-        Some(crate::types::BalanceChange {
-            mint: *mint,
-            symbol,
-            owner: *owner,
-            token_account: *token_account,
-            pre_balance,
-            post_balance,
-            change,
-            decimals,
-        })
-    } else {
-        None
-    }
+    Some(crate::types::BalanceChange {
+        mint: *mint,
+        symbol,
+        owner: *owner,
+        token_account: *token_account,
+        pre_balance,
+        pre_balance_known: pre_account.is_some(),
+        post_balance,
+        change,
+        decimals,
+    })
+}
+
+/// Parse the decimals from a Jito account response holding a mint
+fn parse_mint_decimals(account: &serde_json::Value) -> Option<u8> {
+    use base64::Engine;
+
+    let data_str = account.get("data")?.as_array()?.first()?.as_str()?;
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(data_str)
+        .ok()?;
+
+    // Mint layout: mint_authority (36) + supply (8) + decimals (1) + ..., the same
+    // for SPL Token and Token-2022 mints
+    data.get(44).copied()
 }
 
 /// Parse token balance from a Jito account response
@@ -594,7 +2168,7 @@ fn parse_token_account_balance(account: &serde_json::Value) -> Option<u64> {
     // Token account data layout: mint (32) + owner (32) + amount (8) + ...
     use base64::Engine;
 
-    let data_str = account.get("data")?.as_array()?.get(0)?.as_str()?;
+    let data_str = account.get("data")?.as_array()?.first()?.as_str()?;
     let data = base64::engine::general_purpose::STANDARD.decode(data_str).ok()?;
 
     // Token account amount is at bytes 64-72 (after mint and owner)
@@ -609,13 +2183,17 @@ fn parse_token_account_balance(account: &serde_json::Value) -> Option<u64> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants::usdc_mint;
+    use crate::constants::{jupiter_order_engine_program_id, usdc_mint};
+    use crate::test_utils::sample_trade_info;
+    use crate::types::BundleError;
     use solana_sdk::{
         instruction::{AccountMeta, Instruction},
         pubkey::Pubkey,
         signature::Keypair,
         signer::Signer,
     };
+    use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+    use spl_associated_token_account::get_associated_token_address_with_program_id;
     use std::str::FromStr;
 
     fn create_mock_jupiter_fill(
@@ -628,214 +2206,2337 @@ mod tests {
     ) -> Instruction {
         let jupiter_program_id = jupiter_order_engine_program_id();
 
-        // Build instruction data: discriminator + input_amount + output_amount + expire_at
-        let fill_discriminator = crate::instruction_discriminator("fill");
-        let mut data = fill_discriminator.to_vec();
-        data.extend_from_slice(&input_amount.to_le_bytes());
-        data.extend_from_slice(&output_amount.to_le_bytes());
-        // Add a mock expire_at timestamp (e.g., 1 hour from now in unix time)
-        let expire_at: i64 = 1704067200; // Mock timestamp
-        data.extend_from_slice(&expire_at.to_le_bytes());
+        // Build instruction data: discriminator + input_amount + output_amount + expire_at
+        let fill_discriminator = crate::instruction_discriminator("fill");
+        let mut data = fill_discriminator.to_vec();
+        data.extend_from_slice(&input_amount.to_le_bytes());
+        data.extend_from_slice(&output_amount.to_le_bytes());
+        // Add a mock expire_at timestamp (e.g., 1 hour from now in unix time)
+        let expire_at: i64 = 1704067200; // Mock timestamp
+        data.extend_from_slice(&expire_at.to_le_bytes());
+
+        let taker_input_ata = Pubkey::new_unique();
+        let maker_input_ata = Pubkey::new_unique();
+        let taker_output_ata = Pubkey::new_unique();
+        let maker_output_ata = Pubkey::new_unique();
+
+        // Account order matches actual Jupiter RFQ fill layout:
+        // taker, maker, taker_input_ata, maker_input_ata, taker_output_ata, maker_output_ata,
+        // input_mint, input_token_program, output_mint
+        Instruction {
+            program_id: jupiter_program_id,
+            accounts: vec![
+                AccountMeta::new(*taker, true),                // 0: taker
+                AccountMeta::new(*maker, true),                // 1: maker
+                AccountMeta::new(taker_input_ata, false),      // 2: taker_input_ata
+                AccountMeta::new(maker_input_ata, false),      // 3: maker_input_ata
+                AccountMeta::new(taker_output_ata, false),     // 4: taker_output_ata
+                AccountMeta::new(maker_output_ata, false),     // 5: maker_output_ata
+                AccountMeta::new_readonly(*input_mint, false), // 6: input_mint
+                AccountMeta::new_readonly(crate::constants::token_2022_program_id(), false), // 7: input_token_program
+                AccountMeta::new_readonly(*output_mint, false), // 8: output_mint
+            ],
+            data,
+        }
+    }
+
+    #[test]
+    fn test_check_gm_trade_buy() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let ix = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+
+        let message = Message::new(&[ix], Some(&user.pubkey()));
+        let result = check_gm_trade_message(&message).unwrap();
+
+        assert!(result.use_gm_bundle_sim);
+        let info = result.trade_info.unwrap();
+        assert_eq!(info.maker, solver);
+        assert_eq!(info.taker, user.pubkey());
+        assert_eq!(info.gm_token_mint, aapl);
+        assert_eq!(info.gm_token_symbol, "AAPLon");
+        assert_eq!(info.gm_token_amount.0, 1_500_000_000);
+        assert_eq!(info.expire_at, 1704067200); // Verify expire_at is parsed
+    }
+
+    #[test]
+    fn test_check_gm_trade_buy_with_usdt() {
+        use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdt = crate::constants::usdt_mint().unwrap();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let ix = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdt,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let message = Message::new(&[ix], Some(&user.pubkey()));
+        let result = check_gm_trade_message(&message).unwrap();
+
+        assert!(result.use_gm_bundle_sim);
+        let info = result.trade_info.unwrap();
+        assert_eq!(info.quote_mint, usdt);
+
+        // The mock mint creates USDT ATAs for the taker and maker, not USDC ones
+        let instructions = build_mock_mint_instructions(&info);
+        let minter = crate::constants::admin_minter();
+        for wallet in [&info.taker, &info.maker] {
+            let create_ata = create_associated_token_account_idempotent(
+                &minter,
+                wallet,
+                &usdt,
+                &info.input_token_program,
+            );
+            assert!(instructions.contains(&create_ata));
+        }
+        assert!(!instructions
+            .iter()
+            .flat_map(|ix| &ix.accounts)
+            .any(|meta| meta.pubkey == usdc_mint()));
+
+        // The fill's pre/post balances track the taker's USDT account
+        let taker_usdt_ata = get_associated_token_address_with_program_id(
+            &info.taker,
+            &usdt,
+            &info.input_token_program,
+        );
+        let request =
+            build_simulate_bundle_request(vec![], &info, &crate::types::SimulateOptions::default());
+        assert_eq!(
+            request["params"][1]["postExecutionAccountsConfigs"][1]["addresses"][0],
+            taker_usdt_ata.to_string()
+        );
+    }
+
+    #[test]
+    fn test_with_fill_expire_at() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let fill = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc_mint(),
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let memo = Instruction::new_with_bytes(Pubkey::new_unique(), b"memo", vec![]);
+        let message = Message::new(&[memo, fill], Some(&user.pubkey()));
+        let mut tx = Transaction::new_unsigned(message);
+        tx.signatures = vec![Signature::new_unique(); 2];
+
+        let rebuilt = with_fill_expire_at(&tx, 1_800_000_000);
+
+        assert_eq!(rebuilt.signatures, vec![Signature::default(); 2]);
+        assert_eq!(rebuilt.message.instructions[0], tx.message.instructions[0]);
+        let info = check_gm_trade(&rebuilt).unwrap().trade_info.unwrap();
+        assert_eq!(info.expire_at, 1_800_000_000);
+        assert_eq!(info.gm_token_amount.0, 1_500_000_000);
+        // The original is untouched
+        let info = check_gm_trade(&tx).unwrap().trade_info.unwrap();
+        assert_eq!(info.expire_at, 1704067200);
+    }
+
+    #[test]
+    fn test_check_gm_trade_unauthorized_maker() {
+        let unauthorized_maker = Pubkey::new_unique();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let ix = create_mock_jupiter_fill(
+            &unauthorized_maker,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+
+        let message = Message::new(&[ix], Some(&user.pubkey()));
+        let result = check_gm_trade_message(&message);
+
+        assert!(matches!(
+            result,
+            Err(GmSimulatorError::UnauthorizedMaker(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_gm_trade_taker_maker_same() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let ix =
+            create_mock_jupiter_fill(&solver, &solver, &usdc, &aapl, 200_000_000, 1_500_000_000);
+
+        let message = Message::new(&[ix], Some(&solver));
+        let result = check_gm_trade_message(&message);
+
+        assert_eq!(result, Err(GmSimulatorError::TakerMakerSame(solver)));
+
+        // The check guards the mock mint, so a SELL by the solver is still a plain SELL
+        let ix =
+            create_mock_jupiter_fill(&solver, &solver, &aapl, &usdc, 1_500_000_000, 200_000_000);
+        let message = Message::new(&[ix], Some(&solver));
+        assert_eq!(
+            check_gm_trade_message(&message).unwrap().reason,
+            Some(NoBundleReason::SellDirection)
+        );
+    }
+
+    #[test]
+    fn test_check_gm_trade_zero_output_amount() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let ix = create_mock_jupiter_fill(&solver, &user.pubkey(), &usdc, &aapl, 200_000_000, 0);
+
+        let message = Message::new(&[ix], Some(&user.pubkey()));
+        let result = check_gm_trade_message(&message);
+
+        assert_eq!(result, Err(GmSimulatorError::ZeroAmount));
+    }
+
+    #[test]
+    fn test_check_gm_trade_not_gm_token() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let random_token = Pubkey::new_unique();
+
+        let ix = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &random_token,
+            200_000_000,
+            1_000_000_000,
+        );
+
+        let message = Message::new(&[ix], Some(&user.pubkey()));
+        let result = check_gm_trade_message(&message).unwrap();
+
+        assert!(!result.use_gm_bundle_sim);
+        assert!(result.trade_info.is_none());
+        assert_eq!(result.reason, Some(NoBundleReason::OutputNotGm));
+    }
+
+    #[test]
+    fn test_check_gm_trade_buy_with_non_stable_quote() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        // Balance tracking assumes a stablecoin quote, so a GM token bought with
+        // anything else is not simulated
+        let ix = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &Pubkey::new_unique(),
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+
+        let message = Message::new(&[ix], Some(&user.pubkey()));
+        let result = check_gm_trade_message(&message).unwrap();
+
+        assert!(!result.use_gm_bundle_sim);
+        assert_eq!(result.reason, Some(NoBundleReason::QuoteNotStable));
+    }
+
+    #[test]
+    fn test_check_gm_trade_skips_short_fill_data() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        // Fill discriminator followed by only 8 bytes of arguments
+        let mut short_fill = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc_mint(),
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        short_fill.data.truncate(16);
+        let message = Message::new(std::slice::from_ref(&short_fill), Some(&user.pubkey()));
+
+        let result = check_gm_trade_message(&message).unwrap();
+        assert!(!result.use_gm_bundle_sim);
+        assert_eq!(result.reason, Some(NoBundleReason::NotAFill));
+
+        // The parser itself still rejects it
+        assert!(matches!(
+            crate::parser::parse_fill_for_gm_trade(&message.instructions[0], &message.account_keys),
+            Err(GmSimulatorError::InstructionParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_inner_fill() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        // An aggregator instruction whose CPI is the Jupiter fill: the fill's accounts are
+        // in the message, but only the aggregator is a top-level instruction
+        let fill = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc_mint(),
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let mut accounts = fill.accounts.clone();
+        accounts.push(AccountMeta::new_readonly(fill.program_id, false));
+        let aggregator = Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts,
+            data: vec![7],
+        };
+        let message = Message::new(&[aggregator], Some(&user.pubkey()));
+        assert_eq!(
+            check_gm_trade_message(&message).unwrap().reason,
+            Some(NoBundleReason::NotAFill)
+        );
+
+        let index = |key: &Pubkey| message.account_keys.iter().position(|k| k == key).unwrap();
+        let inner_fill = serde_json::json!({
+            "programIdIndex": index(&fill.program_id),
+            "accounts": fill.accounts.iter().map(|meta| index(&meta.pubkey)).collect::<Vec<_>>(),
+            "data": bs58::encode(&fill.data).into_string(),
+            "stackHeight": 2
+        });
+        // Captured from getTransaction (json encoding), trimmed to the fields used
+        let meta: UiTransactionStatusMeta = serde_json::from_value(serde_json::json!({
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [],
+            "postBalances": [],
+            "innerInstructions": [{
+                "index": 0,
+                "instructions": [
+                    {
+                        "parsed": { "type": "transfer", "info": {} },
+                        "program": "spl-token",
+                        "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                        "stackHeight": 2
+                    },
+                    inner_fill
+                ]
+            }]
+        }))
+        .unwrap();
+
+        let info = extract_inner_fill(&meta, &message.account_keys).unwrap();
+        assert_eq!(info.direction, TradeDirection::Buy);
+        assert_eq!(info.maker, solver);
+        assert_eq!(info.taker, user.pubkey());
+        assert_eq!(info.gm_token_symbol, "AAPLon");
+        assert_eq!(info.gm_token_amount.0, 1_500_000_000);
+
+        // No inner instructions recorded
+        let meta = UiTransactionStatusMeta {
+            inner_instructions: OptionSerializer::None,
+            ..meta
+        };
+        assert_eq!(extract_inner_fill(&meta, &message.account_keys), None);
+    }
+
+    #[test]
+    fn test_check_gm_trade_base64() {
+        use base64::Engine;
+
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let ix = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc_mint(),
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let message = Message::new_with_blockhash(&[ix], Some(&user.pubkey()), &Hash::new_unique());
+        let tx = Transaction::new_unsigned(message);
+        let encoded =
+            base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&tx).unwrap());
+
+        let result = check_gm_trade_base64(&encoded).unwrap();
+        assert_eq!(Ok(result.clone()), check_gm_trade(&tx));
+        assert!(result.use_gm_bundle_sim);
+        assert_eq!(result.trade_info.unwrap().gm_token_symbol, "AAPLon");
+
+        assert!(matches!(
+            check_gm_trade_base64("not a transaction!"),
+            Err(GmSimulatorError::TransactionDecodeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_gm_trade_raw() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let buy = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let message = Message::new(&[buy], Some(&user.pubkey()));
+        let result = check_gm_trade_raw(&message.account_keys, &message.instructions).unwrap();
+        assert_eq!(Ok(result.clone()), check_gm_trade_message(&message));
+        assert!(result.use_gm_bundle_sim);
+        assert_eq!(result.trade_info.unwrap().gm_token_amount.0, 1_500_000_000);
+
+        let sell = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &aapl,
+            &usdc,
+            1_500_000_000,
+            200_000_000,
+        );
+        let message = Message::new(&[sell], Some(&user.pubkey()));
+        assert_eq!(
+            check_gm_trade_raw(&message.account_keys, &message.instructions)
+                .unwrap()
+                .reason,
+            Some(NoBundleReason::SellDirection)
+        );
+
+        assert_eq!(
+            check_gm_trade_raw(&message.account_keys, &[]),
+            Err(GmSimulatorError::EmptyTransaction)
+        );
+    }
+
+    #[test]
+    fn test_check_gm_trade_max_instructions() {
+        let max = crate::constants::global_config().max_instructions;
+        assert_eq!(max, crate::constants::DEFAULT_MAX_INSTRUCTIONS);
+
+        let payer = Pubkey::new_unique();
+        let memo = spl_memo::build_memo(b"", &[]);
+        let message = |count: usize| Message::new(&vec![memo.clone(); count], Some(&payer));
+
+        // At the limit the message is scanned as usual
+        let result = check_gm_trade_message(&message(max)).unwrap();
+        assert_eq!(result.reason, Some(NoBundleReason::NotAFill));
+
+        assert_eq!(
+            check_gm_trade_message(&message(max + 1)),
+            Err(GmSimulatorError::TooManyInstructions {
+                count: max + 1,
+                max,
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_gm_trade_with_max_instructions() {
+        let payer = Pubkey::new_unique();
+        let memo = spl_memo::build_memo(b"", &[]);
+        let transaction = |count: usize| {
+            Transaction::new_unsigned(Message::new(&vec![memo.clone(); count], Some(&payer)))
+        };
+
+        // The per-call limit applies, not the global one
+        let result = check_gm_trade_with_max_instructions(&transaction(3), 3).unwrap();
+        assert_eq!(result.reason, Some(NoBundleReason::NotAFill));
+        assert_eq!(
+            check_gm_trade_with_max_instructions(&transaction(4), 3),
+            Err(GmSimulatorError::TooManyInstructions { count: 4, max: 3 })
+        );
+        assert!(check_gm_trade(&transaction(4)).is_ok());
+    }
+
+    #[test]
+    fn test_check_gm_trade_detailed() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        // No Jupiter fill at all
+        let transfer =
+            solana_system_interface::instruction::transfer(&user.pubkey(), &solver, 1_000);
+        let message = Message::new(&[transfer], Some(&user.pubkey()));
+        assert_eq!(
+            check_gm_trade_detailed(&message),
+            Ok(DetectionOutcome::NoFillFound)
+        );
+        assert_eq!(
+            check_gm_trade_message(&message).unwrap().reason,
+            Some(NoBundleReason::NotAFill)
+        );
+
+        // A GM SELL, carried through with its trade info
+        let sell = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &aapl,
+            &usdc,
+            1_500_000_000,
+            200_000_000,
+        );
+        let message = Message::new(&[sell], Some(&user.pubkey()));
+        let Ok(DetectionOutcome::GmSell(info)) = check_gm_trade_detailed(&message) else {
+            panic!("expected a GM SELL");
+        };
+        assert_eq!(info.direction, TradeDirection::Sell);
+        assert_eq!(info.gm_token_amount.0, 1_500_000_000);
+        assert_eq!(
+            check_gm_trade_message(&message),
+            Ok(GmCheckResult::not_gm_trade_because(
+                NoBundleReason::SellDirection
+            ))
+        );
+
+        // A fill that doesn't involve a GM token
+        let other = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &Pubkey::new_unique(),
+            200_000_000,
+            1_000_000_000,
+        );
+        let message = Message::new(&[other], Some(&user.pubkey()));
+        assert_eq!(
+            check_gm_trade_detailed(&message),
+            Ok(DetectionOutcome::FillButNotGm)
+        );
+
+        // A GM BUY
+        let buy = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let message = Message::new(&[buy], Some(&user.pubkey()));
+        let Ok(DetectionOutcome::GmTrade(info)) = check_gm_trade_detailed(&message) else {
+            panic!("expected a GM trade");
+        };
+        assert_eq!(info.gm_token_mint, aapl);
+        assert_eq!(info.gm_token_amount.0, 1_500_000_000);
+    }
+
+    #[test]
+    fn test_check_gm_trade_with_create_ata() {
+        use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        // Create ATA instruction (typically included by solvers)
+        let create_ata_ix = create_associated_token_account_idempotent(
+            &solver,
+            &user.pubkey(),
+            &aapl,
+            &crate::constants::token_2022_program_id(),
+        );
+
+        // Jupiter fill instruction
+        let fill_ix = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+
+        // Transaction with both instructions (realistic scenario)
+        let message = Message::new(&[create_ata_ix, fill_ix], Some(&user.pubkey()));
+        let result = check_gm_trade_message(&message).unwrap();
+
+        // Should still detect as GM trade despite multiple instructions
+        assert!(result.use_gm_bundle_sim);
+        let info = result.trade_info.unwrap();
+        assert_eq!(info.gm_token_mint, aapl);
+        assert_eq!(info.gm_token_amount.0, 1_500_000_000);
+    }
+
+    #[test]
+    fn test_check_gm_trade_multiple_fills() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let ix1 = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let ix2 = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            100_000_000,
+            750_000_000,
+        );
+
+        let message = Message::new(&[ix1, ix2], Some(&user.pubkey()));
+        let result = check_gm_trade_message(&message).unwrap();
+
+        // With multiple fill instructions, we detect the first one as a GM trade
+        // This is an edge case - in practice, transactions typically have one fill
+        assert!(result.use_gm_bundle_sim);
+    }
+
+    #[test]
+    fn test_check_gm_trades_batch() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let gm_fill = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let unauthorized_fill = create_mock_jupiter_fill(
+            &Pubkey::new_unique(),
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let txs: Vec<Transaction> = [gm_fill, unauthorized_fill]
+            .into_iter()
+            .map(|ix| Transaction::new_unsigned(Message::new(&[ix], Some(&user.pubkey()))))
+            .collect();
+
+        let results = check_gm_trades_batch(&txs);
+
+        // Results keep the input order
+        assert_eq!(results.len(), 2);
+        assert!(results[0].as_ref().unwrap().use_gm_bundle_sim);
+        assert!(matches!(
+            results[1],
+            Err(GmSimulatorError::UnauthorizedMaker(_))
+        ));
+    }
+
+    #[test]
+    fn test_detect_stream() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let fill = |maker: &Pubkey| {
+            create_mock_jupiter_fill(
+                maker,
+                &user.pubkey(),
+                &usdc_mint(),
+                &aapl,
+                200_000_000,
+                1_500_000_000,
+            )
+        };
+        let memo = Instruction::new_with_bytes(Pubkey::new_unique(), b"memo", vec![]);
+        let messages: Vec<Message> = [fill(&solver), fill(&Pubkey::new_unique()), memo]
+            .into_iter()
+            .map(|ix| Message::new(&[ix], Some(&user.pubkey())))
+            .collect();
+
+        let results: Vec<_> = detect_stream(messages.iter()).collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].as_ref().unwrap().use_gm_bundle_sim);
+        assert!(matches!(
+            results[1],
+            Err(GmSimulatorError::UnauthorizedMaker(_))
+        ));
+        assert_eq!(
+            results[2].as_ref().unwrap().reason,
+            Some(NoBundleReason::NotAFill)
+        );
+
+        // Messages are only pulled as results are consumed
+        let mut pulled = 0;
+        let first = detect_stream(messages.iter().inspect(|_| pulled += 1)).next();
+        assert!(first.unwrap().unwrap().use_gm_bundle_sim);
+        assert_eq!(pulled, 1);
+    }
+
+    #[test]
+    fn test_check_gm_trade_nonidempotent_ata_create() {
+        use spl_associated_token_account::instruction::{
+            create_associated_token_account, create_associated_token_account_idempotent,
+        };
+
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let token_2022 = crate::constants::token_2022_program_id();
+
+        let fill = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let payer = user.pubkey();
+        let idempotent =
+            create_associated_token_account_idempotent(&payer, &payer, &aapl, &token_2022);
+        let nonidempotent = create_associated_token_account(&payer, &payer, &aapl, &token_2022);
+
+        let message = Message::new(&[idempotent, fill.clone()], Some(&payer));
+        assert!(!has_nonidempotent_ata_create(&message));
+        let result = check_gm_trade_message(&message).unwrap();
+        assert!(!result.nonidempotent_ata_create);
+
+        let message = Message::new(&[nonidempotent, fill], Some(&payer));
+        assert!(has_nonidempotent_ata_create(&message));
+        let result = check_gm_trade_message(&message).unwrap();
+        assert!(result.use_gm_bundle_sim);
+        assert!(result.nonidempotent_ata_create);
+
+        let versioned = VersionedMessage::Legacy(message);
+        let result = check_gm_trade_versioned_message(&versioned).unwrap();
+        assert!(result.nonidempotent_ata_create);
+    }
+
+    #[test]
+    fn test_check_gm_trade_noncanonical_maker_output() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        // The mock fill's maker output account is a random address
+        let mut fill = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let message = Message::new(&[fill.clone()], Some(&user.pubkey()));
+        let result = check_gm_trade_message(&message).unwrap();
+        assert!(result.use_gm_bundle_sim);
+        assert!(!result.trade_info.as_ref().unwrap().maker_output_is_ata());
+        assert!(result.noncanonical_maker_output);
+        assert!(result
+            .summary_string()
+            .contains("maker output account is not the maker's GM ATA"));
+
+        // The maker's Token-2022 ATA for the GM token is canonical
+        fill.accounts[5].pubkey = crate::mint_instruction::get_gm_token_ata(&solver, &aapl);
+        let message = Message::new(&[fill], Some(&user.pubkey()));
+        let result = check_gm_trade_message(&message).unwrap();
+        assert!(result.trade_info.as_ref().unwrap().maker_output_is_ata());
+        assert!(!result.noncanonical_maker_output);
+    }
+
+    #[test]
+    fn test_check_gm_trade_v0_lookup_table_program_id() {
+        use solana_sdk::message::v0::{self, MessageAddressTableLookup};
+
+        let payer = Pubkey::new_unique();
+        let v0_message = |instructions: Vec<CompiledInstruction>| {
+            VersionedMessage::V0(v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 0,
+                },
+                account_keys: vec![payer],
+                recent_blockhash: Hash::new_unique(),
+                instructions,
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![],
+                    readonly_indexes: vec![0],
+                }],
+            })
+        };
+
+        // The only instruction's program id (index 1) is loaded from the lookup table
+        let message = v0_message(vec![CompiledInstruction::new_from_raw_parts(
+            1,
+            vec![1, 2, 3],
+            vec![0],
+        )]);
+        let result = check_gm_trade_versioned_message(&message).unwrap();
+        assert!(!result.use_gm_bundle_sim);
+        assert_eq!(result.reason, Some(NoBundleReason::NotAFill));
+
+        assert_eq!(
+            check_gm_trade_versioned_message(&v0_message(vec![])),
+            Err(GmSimulatorError::EmptyTransaction)
+        );
+    }
+
+    #[test]
+    fn test_resolve_v0_accounts() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let taker = Pubkey::new_unique();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let fill = create_mock_jupiter_fill(
+            &solver,
+            &taker,
+            &usdc_mint(),
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+
+        // Put two token accounts and the mints in separate tables
+        let accounts: Vec<Pubkey> = fill.accounts.iter().map(|meta| meta.pubkey).collect();
+        let first = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![accounts[2], aapl, accounts[3]],
+        };
+        let second = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![usdc_mint(), accounts[4]],
+        };
+        let tables = [first.clone(), second.clone()];
+        let instructions = std::slice::from_ref(&fill);
+        let message =
+            v0::Message::try_compile(&taker, instructions, &tables, Hash::new_unique()).unwrap();
+        assert_eq!(message.address_table_lookups.len(), 2);
+
+        // Static keys, then each table's writable addresses, then each table's readonly ones
+        let keys = resolve_v0_accounts(&message, &tables).unwrap();
+        let num_static = message.account_keys.len();
+        assert_eq!(keys[..num_static], message.account_keys[..]);
+        assert_eq!(
+            keys[num_static..],
+            [accounts[2], accounts[3], accounts[4], aapl, usdc_mint()]
+        );
+
+        // Every instruction account index resolves to the original key
+        let ix = &message.instructions[0];
+        assert_eq!(keys[ix.program_id_index as usize], fill.program_id);
+        let resolved: Vec<Pubkey> = ix.accounts.iter().map(|&i| keys[i as usize]).collect();
+        assert_eq!(resolved, accounts);
+
+        // Detection sees the accounts loaded from the tables, which static keys don't cover
+        assert_eq!(
+            check_gm_trade_versioned_message(&VersionedMessage::V0(message.clone())),
+            Err(GmSimulatorError::MissingAccount)
+        );
+        let result = check_gm_trade_v0_with_tables(&message, &tables).unwrap();
+        assert!(result.use_gm_bundle_sim);
+        assert_eq!(result.trade_info.unwrap().gm_token_mint, aapl);
+
+        // Tables must all be provided, and cover every index
+        assert!(matches!(
+            resolve_v0_accounts(&message, &[first]),
+            Err(GmSimulatorError::TransactionDecodeError(_))
+        ));
+        let truncated = AddressLookupTableAccount {
+            addresses: vec![usdc_mint()],
+            ..second
+        };
+        assert!(matches!(
+            resolve_v0_accounts(&message, &[tables[0].clone(), truncated]),
+            Err(GmSimulatorError::TransactionDecodeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_instruction_accounts() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let fill = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc_mint(),
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let message = Message::new(std::slice::from_ref(&fill), Some(&user.pubkey()));
+
+        let resolved = resolve_instruction_accounts(&message, &message.instructions[0]);
+
+        assert_eq!(resolved.len(), fill.accounts.len());
+        for (account, meta) in resolved.iter().zip(&fill.accounts) {
+            assert_eq!(account.pubkey, meta.pubkey);
+            assert_eq!(account.is_signer, meta.is_signer);
+            assert_eq!(account.is_writable, meta.is_writable);
+        }
+
+        // Out-of-range indices are skipped
+        let mut ix = message.instructions[0].clone();
+        ix.accounts.push(u8::MAX);
+        assert_eq!(
+            resolve_instruction_accounts(&message, &ix).len(),
+            fill.accounts.len()
+        );
+    }
+
+    #[test]
+    fn test_gm_mints_in_message() {
+        use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+        let payer = Pubkey::new_unique();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let tsla = Pubkey::from_str("KeGv7bsfR4MheC1CkmnAVceoApjrkvBhHYjWb67ondo").unwrap();
+
+        let instructions: Vec<Instruction> = [aapl, tsla, aapl]
+            .iter()
+            .map(|mint| {
+                create_associated_token_account_idempotent(
+                    &payer,
+                    &payer,
+                    mint,
+                    &crate::constants::token_2022_program_id(),
+                )
+            })
+            .chain(std::iter::once(create_associated_token_account_idempotent(
+                &payer,
+                &payer,
+                &usdc_mint(),
+                &crate::constants::spl_token_program_id(),
+            )))
+            .collect();
+        let message = Message::new(&instructions, Some(&payer));
+
+        let mints = gm_mints_in_message(&message);
+        assert_eq!(mints.len(), 2);
+        assert!(mints.contains(&aapl));
+        assert!(mints.contains(&tsla));
+        assert!(!mints.contains(&usdc_mint()));
+
+        let message = Message::new(&instructions[3..], Some(&payer));
+        assert!(gm_mints_in_message(&message).is_empty());
+    }
+
+    #[test]
+    fn test_contains_mint_gm() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let fill = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc_mint(),
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+
+        let message = Message::new(std::slice::from_ref(&fill), Some(&user.pubkey()));
+        let tx = Transaction::new_unsigned(message);
+        assert!(!contains_mint_gm(&tx.message));
+        assert!(maybe_build_mock_mint(&tx, Hash::default())
+            .unwrap()
+            .is_some());
+
+        // The solver mints the GM tokens itself in the same transaction
+        let mint_gm = build_mock_mint_gm_instruction(&aapl, &solver, 1_500_000_000);
+        let tx = Transaction::new_unsigned(Message::new(&[mint_gm, fill], Some(&user.pubkey())));
+        assert!(contains_mint_gm(&tx.message));
+        assert_eq!(maybe_build_mock_mint(&tx, Hash::default()), Ok(None));
+    }
+
+    #[test]
+    fn test_maybe_build_mock_mint_with_token_programs() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let spl_token = crate::constants::spl_token_program_id();
+        let fill = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc_mint(),
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let tx = Transaction::new_unsigned(Message::new(&[fill], Some(&user.pubkey())));
+
+        // The fill names Token-2022, but the GM mint turns out to be owned by SPL Token
+        let mock_mint =
+            maybe_build_mock_mint_with_token_programs(&tx, Hash::default(), |_| Ok(spl_token))
+                .unwrap()
+                .unwrap();
+        let instructions = &mock_mint.message.instructions;
+        let create_taker_gm_ata = &instructions[0];
+        let taker_gm_ata = mock_mint.message.account_keys[create_taker_gm_ata.accounts[1] as usize];
+        assert_eq!(
+            taker_gm_ata,
+            crate::mint_instruction::get_gm_token_ata_with_program(
+                &user.pubkey(),
+                &aapl,
+                &spl_token
+            )
+        );
+        assert_ne!(
+            taker_gm_ata,
+            crate::mint_instruction::get_gm_token_ata(&user.pubkey(), &aapl)
+        );
+
+        // A failed lookup fails the build instead of falling back to the fill's program
+        let result = maybe_build_mock_mint_with_token_programs(&tx, Hash::default(), |_| {
+            Err(GmSimulatorError::RpcHttpStatus(503))
+        });
+        assert_eq!(result, Err(GmSimulatorError::RpcHttpStatus(503)));
+    }
+
+    #[test]
+    fn test_check_gm_trade_bundle() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        // First transaction only creates the taker's GM ATA
+        let create_ata =
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &user.pubkey(),
+                &user.pubkey(),
+                &aapl,
+                &crate::constants::token_2022_program_id(),
+            );
+        let fill = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let txs: Vec<Transaction> = [create_ata, fill]
+            .into_iter()
+            .map(|ix| Transaction::new_unsigned(Message::new(&[ix], Some(&user.pubkey()))))
+            .collect();
+
+        let result = check_gm_trade_bundle(&txs).unwrap();
+        assert!(result.use_gm_bundle_sim);
+        let info = result.trade_info.unwrap();
+        assert_eq!(info.taker, user.pubkey());
+        assert_eq!(info.gm_token_amount.0, 1_500_000_000);
+
+        assert!(!check_gm_trade_bundle(&txs[..1]).unwrap().use_gm_bundle_sim);
+        assert!(matches!(
+            check_gm_trade_bundle(&[]),
+            Err(GmSimulatorError::EmptyTransaction)
+        ));
+
+        // A transaction that fails the check doesn't hide a later GM trade
+        let empty = Transaction::new_unsigned(Message::new(&[], Some(&user.pubkey())));
+        let with_empty = [empty.clone(), txs[1].clone()];
+        assert!(
+            check_gm_trade_bundle(&with_empty)
+                .unwrap()
+                .use_gm_bundle_sim
+        );
+        assert_eq!(
+            check_gm_trade_bundle(std::slice::from_ref(&empty)),
+            Err(GmSimulatorError::EmptyTransaction)
+        );
+
+        // Without a GM trade, the reason comes from the transaction with the fill
+        let sell = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &aapl,
+            &usdc,
+            1_500_000_000,
+            200_000_000,
+        );
+        let sell_tx = Transaction::new_unsigned(Message::new(&[sell], Some(&user.pubkey())));
+        let result = check_gm_trade_bundle(&[sell_tx, empty, txs[0].clone()]).unwrap();
+        assert!(!result.use_gm_bundle_sim);
+        assert_eq!(result.reason, Some(NoBundleReason::SellDirection));
+        assert_eq!(
+            check_gm_trade_bundle(&txs[..1]).unwrap().reason,
+            Some(NoBundleReason::NotAFill)
+        );
+    }
+
+    #[test]
+    fn test_build_mock_mint_transaction() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+
+        let trade_info = GmTradeInfo {
+            maker: solver,
+            taker: Pubkey::new_unique(),
+            gm_token_mint: aapl,
+            gm_token_symbol: "AAPLon".to_string(),
+            gm_token_amount: 1_500_000_000.into(),
+            input_amount: 200_000_000.into(),
+            maker_output_account: Pubkey::new_unique(),
+            expire_at: 1704067200,
+            input_token_program: crate::constants::spl_token_program_id(),
+            output_token_program: crate::constants::token_2022_program_id(),
+            direction: TradeDirection::Buy,
+            quote_mint: crate::constants::usdc_mint(),
+        };
+
+        let mock_tx = build_mock_mint_transaction(&trade_info, Hash::default());
+
+        // Verify the transaction structure
+        // Should have 5 instructions: create taker GM ATA + create maker GM ATA + create taker USDC ATA + create maker USDC ATA + mint
+        assert_eq!(mock_tx.message.instructions.len(), 5);
+        // Transaction has 1 signature slot (for the minter/fee payer), but it's not signed yet
+        assert_eq!(mock_tx.signatures.len(), 1);
+        // All signatures should be default (all zeros) since it's unsigned
+        assert!(mock_tx
+            .signatures
+            .iter()
+            .all(|sig| sig.as_ref().iter().all(|&b| b == 0)));
+    }
+
+    #[test]
+    fn test_build_mock_mint_instructions() {
+        let trade_info = sample_trade_info();
+
+        let instructions = build_mock_mint_instructions(&trade_info);
+        let mock_tx = build_mock_mint_transaction(&trade_info, Hash::default());
+
+        assert_eq!(instructions.len(), 5);
+        // The last instruction is the mint; the rest are ATA creates
+        assert_eq!(instructions[4].program_id, ondo_gm_program_id());
+        assert!(instructions[..4]
+            .iter()
+            .all(|ix| ix.program_id == spl_associated_token_account::id()));
+        // Same instructions the transaction wraps
+        let message = Message::new_with_blockhash(
+            &instructions,
+            Some(&crate::constants::admin_minter()),
+            &Hash::default(),
+        );
+        assert_eq!(message, mock_tx.message);
+    }
+
+    #[test]
+    fn test_build_mock_mint_transaction_skip_maker_quote_ata() {
+        let trade_info = sample_trade_info();
+        let options = MockMintOptions {
+            skip_maker_quote_ata: true,
+            ..MockMintOptions::default()
+        };
+
+        let mock_tx =
+            build_mock_mint_transaction_with_options(&trade_info, Hash::default(), &options);
+        let message = &mock_tx.message;
+
+        // Taker GM ATA + maker GM ATA + taker USDC ATA + mint
+        assert_eq!(message.instructions.len(), 4);
+        let maker_usdc_ata = get_associated_token_address_with_program_id(
+            &trade_info.maker,
+            &usdc_mint(),
+            &trade_info.input_token_program,
+        );
+        assert!(!message.account_keys.contains(&maker_usdc_ata));
+
+        // The default options keep all five instructions
+        let mock_tx = build_mock_mint_transaction_with_options(
+            &trade_info,
+            Hash::default(),
+            &MockMintOptions::default(),
+        );
+        assert_eq!(mock_tx.message.instructions.len(), 5);
+        assert!(mock_tx.message.account_keys.contains(&maker_usdc_ata));
+    }
+
+    #[test]
+    fn test_recommended_compute_limit() {
+        use solana_compute_budget_interface::ComputeBudgetInstruction;
+
+        let trade_info = sample_trade_info();
+
+        // Four ATA creates cost four times the per-create budget more than none
+        assert_eq!(compute_limit_for_ata_creates(0), 30_000);
+        assert_eq!(compute_limit_for_ata_creates(4), 90_000);
+        assert_eq!(recommended_compute_limit(&trade_info), 90_000);
+
+        let options = MockMintOptions {
+            compute_unit_limit: Some(recommended_compute_limit(&trade_info)),
+            ..MockMintOptions::default()
+        };
+        let instructions = mock_mint_instructions(&trade_info, &options);
+        assert_eq!(instructions.len(), 6);
+        assert_eq!(
+            instructions[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(90_000)
+        );
+        // The mint itself is unchanged
+        assert_eq!(
+            instructions[1..],
+            build_mock_mint_instructions(&trade_info)[..]
+        );
+    }
+
+    #[test]
+    fn test_build_mock_mint_transaction_memo() {
+        let trade_info = sample_trade_info();
+        let options = MockMintOptions {
+            memo: Some("gm-sim request 42".to_string()),
+            ..MockMintOptions::default()
+        };
+
+        let mock_tx =
+            build_mock_mint_transaction_with_options(&trade_info, Hash::default(), &options);
+        let message = &mock_tx.message;
+
+        // The memo comes first, ahead of the usual five instructions
+        assert_eq!(message.instructions.len(), 6);
+        let memo_ix = &message.instructions[0];
+        assert_eq!(
+            message.account_keys[memo_ix.program_id_index as usize],
+            spl_memo::id()
+        );
+        assert!(memo_ix.accounts.is_empty());
+        assert_eq!(std::str::from_utf8(&memo_ix.data), Ok("gm-sim request 42"));
+        // Only the admin minter signs
+        assert_eq!(message.header.num_required_signatures, 1);
+
+        // The mock mint is still detected after the memo
+        assert!(contains_mint_gm(message));
+    }
+
+    #[test]
+    fn test_build_mock_mint_transaction_with_nonce() {
+        let trade_info = sample_trade_info();
+        let nonce_account = Pubkey::new_unique();
+        let nonce_authority = Pubkey::new_unique();
+        let nonce_value = Hash::new_unique();
+
+        let mock_tx = build_mock_mint_transaction_with_nonce(
+            &trade_info,
+            &nonce_account,
+            &nonce_authority,
+            nonce_value,
+        );
+        let message = &mock_tx.message;
+
+        // Advance nonce first, then the usual 5 mock mint instructions
+        assert_eq!(message.instructions.len(), 6);
+        assert_eq!(message.recent_blockhash, nonce_value);
+        assert_eq!(message.account_keys[0], crate::constants::admin_minter());
+
+        let advance = &message.instructions[0];
+        assert_eq!(
+            message.account_keys[advance.program_id_index as usize],
+            solana_system_interface::program::id()
+        );
+        // SystemInstruction::AdvanceNonceAccount
+        assert_eq!(advance.data, 4u32.to_le_bytes());
+        assert_eq!(
+            message.account_keys[advance.accounts[0] as usize],
+            nonce_account
+        );
+        assert_eq!(
+            message.account_keys[advance.accounts[2] as usize],
+            nonce_authority
+        );
+        // Fee payer and nonce authority both sign
+        assert_eq!(mock_tx.signatures.len(), 2);
+    }
+
+    #[test]
+    fn test_mock_mint_writable_accounts() {
+        let trade_info = sample_trade_info();
+        let writable = mock_mint_writable_accounts(&trade_info);
+
+        let destination_ata = get_associated_token_address_with_program_id(
+            &trade_info.maker,
+            &trade_info.gm_token_mint,
+            &trade_info.output_token_program,
+        );
+        assert!(writable.contains(&trade_info.gm_token_mint));
+        assert!(writable.contains(&destination_ata));
+        assert!(!writable.contains(&trade_info.output_token_program));
+
+        // Every account listed is writable in the compiled mock mint message
+        let message = build_mock_mint_transaction(&trade_info, Hash::default()).message;
+        for account in &writable {
+            let index = message.account_keys.iter().position(|key| key == account);
+            assert!(message.is_maybe_writable(index.unwrap(), None));
+        }
+    }
+
+    #[test]
+    fn test_estimate_mock_mint_rent() {
+        let rent = Rent::default();
+        let mut trade_info = sample_trade_info();
+        trade_info.input_token_program = crate::constants::spl_token_program_id();
+        trade_info.output_token_program = crate::constants::token_2022_program_id();
+
+        // 165-byte SPL Token accounts, 170-byte Token-2022 accounts with ImmutableOwner
+        assert_eq!(rent.minimum_balance(165), 2_039_280);
+        assert_eq!(rent.minimum_balance(170), 2_074_080);
+        assert_eq!(
+            estimate_mock_mint_rent(&trade_info, &rent),
+            2 * 2_074_080 + 2 * 2_039_280
+        );
+
+        trade_info.input_token_program = crate::constants::token_2022_program_id();
+        assert_eq!(estimate_mock_mint_rent(&trade_info, &rent), 4 * 2_074_080);
+    }
+
+    #[test]
+    fn test_mock_mint_lookup_addresses() {
+        use crate::mint_instruction::MintGmAccounts;
+
+        let trade_info = sample_trade_info();
+        let addresses = mock_mint_lookup_addresses(&trade_info);
+
+        let mut deduped = addresses.clone();
+        deduped.sort();
+        deduped.dedup();
+        assert_eq!(deduped.len(), addresses.len());
+
+        let destination_ata = get_associated_token_address_with_program_id(
+            &trade_info.maker,
+            &trade_info.gm_token_mint,
+            &trade_info.output_token_program,
+        );
+        let accounts = MintGmAccounts::new(
+            &trade_info.gm_token_mint,
+            &destination_ata,
+            &trade_info.maker,
+        );
+        for address in [
+            ondo_gm_program_id(),
+            spl_associated_token_account::id(),
+            accounts.mint_authority,
+            accounts.usdon_manager_state,
+            accounts.oracle_sanity_check,
+            trade_info.gm_token_mint,
+            destination_ata,
+        ] {
+            assert!(addresses.contains(&address), "missing {}", address);
+        }
+        // The fee payer must stay a static key
+        assert!(!addresses.contains(&crate::constants::admin_minter()));
+    }
+
+    #[test]
+    fn test_build_mock_mint_transaction_uses_fill_token_programs() {
+        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+        let user = Keypair::new();
+        let usdc = usdc_mint();
+        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let token_2022 = crate::constants::token_2022_program_id();
+
+        // The mock fill lists Token-2022 as the input token program
+        let ix = create_mock_jupiter_fill(
+            &solver,
+            &user.pubkey(),
+            &usdc,
+            &aapl,
+            200_000_000,
+            1_500_000_000,
+        );
+        let message = Message::new(&[ix], Some(&user.pubkey()));
+        let info = check_gm_trade_message(&message)
+            .unwrap()
+            .trade_info
+            .unwrap();
+
+        assert_eq!(info.input_token_program, token_2022);
+        // No output token program in the 9-account layout: GM tokens default to Token-2022
+        assert_eq!(info.output_token_program, token_2022);
+
+        // The USDC ATAs are created under the fill's input token program
+        let mock_tx = build_mock_mint_transaction(&info, Hash::default());
+        let keys = &mock_tx.message.account_keys;
+        let expected_taker_usdc_ata =
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                &user.pubkey(),
+                &usdc,
+                &token_2022,
+            );
+        let create_taker_usdc_ata = &mock_tx.message.instructions[2];
+        assert_eq!(
+            keys[create_taker_usdc_ata.accounts[1] as usize],
+            expected_taker_usdc_ata
+        );
+        assert_eq!(keys[create_taker_usdc_ata.accounts[5] as usize], token_2022);
+    }
+
+    #[test]
+    fn test_build_mock_mint_transaction_uses_fill_gm_token_program() {
+        let spl_token = crate::constants::spl_token_program_id();
+        // A fill that (unlike every real GM token) lists SPL Token as the output program
+        let sample = sample_trade_info();
+        let maker_gm_ata = get_associated_token_address_with_program_id(
+            &sample.maker,
+            &sample.gm_token_mint,
+            &spl_token,
+        );
+        let trade_info = GmTradeInfo {
+            output_token_program: spl_token,
+            maker_output_account: maker_gm_ata,
+            ..sample
+        };
+
+        let instructions = build_mock_mint_instructions(&trade_info);
+
+        // The maker's GM ATA is created under the fill's program...
+        let create_maker_gm_ata = &instructions[1];
+        assert_eq!(create_maker_gm_ata.accounts[1].pubkey, maker_gm_ata);
+        assert_eq!(create_maker_gm_ata.accounts[5].pubkey, spl_token);
+
+        // ...and mint_gm mints into that same ATA with the same program
+        let mint_ix = &instructions[4];
+        assert_eq!(mint_ix.accounts[7].pubkey, maker_gm_ata);
+        assert_eq!(mint_ix.accounts[9].pubkey, spl_token);
+    }
+
+    #[test]
+    fn test_build_mock_mint_transaction_mints_to_maker_output_account() {
+        let trade_info = GmTradeInfo {
+            maker_output_account: Pubkey::new_unique(),
+            ..sample_trade_info()
+        };
+        let maker_gm_ata = get_associated_token_address_with_program_id(
+            &trade_info.maker,
+            &trade_info.gm_token_mint,
+            &trade_info.output_token_program,
+        );
+        // The fill pulls the GM tokens from an account other than the maker's ATA
+        assert_ne!(trade_info.maker_output_account, maker_gm_ata);
+
+        let instructions = build_mock_mint_instructions(&trade_info);
+
+        // The maker's GM ATA is still created, but the mint goes where the fill looks
+        assert_eq!(instructions[1].accounts[1].pubkey, maker_gm_ata);
+        let mint_ix = &instructions[4];
+        assert_eq!(mint_ix.accounts[7].pubkey, trade_info.maker_output_account);
+        assert_eq!(mint_ix.accounts[2].pubkey, trade_info.maker);
+    }
+
+    /// Build a Jito account entry holding a token account with the given amount
+    fn token_account_json(amount: u64) -> serde_json::Value {
+        use base64::Engine;
+
+        let mut data = vec![0u8; 165];
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        serde_json::json!({
+            "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+            "executable": false,
+            "lamports": 2039280,
+            "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            "rentEpoch": 18446744073709551615u64
+        })
+    }
+
+    #[test]
+    fn test_parse_simulate_bundle_response_success() {
+        let trade_info = sample_trade_info();
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": { "apiVersion": "2.2.16", "slot": 354830516 },
+                "value": {
+                    "summary": "succeeded",
+                    "transactionResults": [
+                        { "err": null, "logs": [], "unitsConsumed": 61234 },
+                        {
+                            "err": null,
+                            "logs": ["Program 61DFfeTKM7trxYcPQCM78bJ794ddZprZpAwAnLiwTpYH success"],
+                            "preExecutionAccounts": [token_account_json(200_000_000), token_account_json(0)],
+                            "postExecutionAccounts": [token_account_json(0), token_account_json(1_500_000_000)],
+                            "unitsConsumed": 48211
+                        }
+                    ]
+                }
+            }
+        });
+
+        let result = parse_simulate_bundle_response(&response, &trade_info).unwrap();
+
+        // Parsing is deterministic, so results compare equal
+        assert_eq!(
+            result,
+            parse_simulate_bundle_response(&response, &trade_info).unwrap()
+        );
+        assert!(result.success);
+        assert!(result.error.is_none());
+        assert!(result.bundle_error.is_none());
+        assert_eq!(result.logs.as_ref().map(Vec::len), Some(1));
+        assert_eq!(result.taker_balance_changes.len(), 2);
+        assert_eq!(result.taker_balance_changes[0].change, -200_000_000);
+        assert_eq!(result.taker_balance_changes[1].change, 1_500_000_000);
+        assert!(result
+            .taker_balance_changes
+            .iter()
+            .all(|change| change.pre_balance_known));
+    }
+
+    #[test]
+    fn test_bundle_transcript_replay() {
+        let trade_info = sample_trade_info();
+        let mock_mint_tx = build_mock_mint_transaction(&trade_info, Hash::default());
+        let transactions = vec![mock_mint_tx];
+        let options = crate::types::SimulateOptions::default();
+        let request = build_simulate_bundle_request(
+            crate::transaction::encode_bundle(&transactions).unwrap(),
+            &trade_info,
+            &options,
+        );
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "value": {
+                    "summary": "succeeded",
+                    "transactionResults": [
+                        { "err": null, "logs": [], "unitsConsumed": 61234 },
+                        {
+                            "err": null,
+                            "logs": [],
+                            "preExecutionAccounts": [token_account_json(200_000_000), token_account_json(0)],
+                            "postExecutionAccounts": [token_account_json(0), token_account_json(1_500_000_000)],
+                            "unitsConsumed": 48211
+                        }
+                    ]
+                }
+            }
+        });
+
+        let transcript = bundle_transcript(
+            request.clone(),
+            response.clone(),
+            &transactions,
+            &trade_info,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(transcript.request, request);
+        assert_eq!(transcript.response, response);
+        assert_eq!(transcript.result.taker_balance_changes.len(), 2);
+
+        // A stored transcript replays to the same result
+        let stored = serde_json::to_string(&transcript.response).unwrap();
+        let replayed =
+            parse_simulate_bundle_response(&serde_json::from_str(&stored).unwrap(), &trade_info)
+                .unwrap();
+        assert_eq!(replayed, transcript.result);
+
+        // The request holds the bundle as sent
+        let encoded = transcript.request["params"][0]["encodedTransactions"][0]
+            .as_str()
+            .unwrap();
+        let decoded = crate::transaction::decode_transaction(encoded).unwrap();
+        assert_eq!(
+            decoded.message,
+            VersionedMessage::Legacy(transactions[0].message.clone())
+        );
+    }
+
+    /// Serve one HTTP request on a local port with `response`, returning the URL
+    fn serve_once(response: &'static str) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 4096];
+            let _ = stream.read(&mut request);
+            let _ = stream.write_all(response.as_bytes());
+        });
+        url
+    }
+
+    #[test]
+    fn test_simulate_as_bundle_rpc_errors() {
+        let trade_info = sample_trade_info();
+        let options = crate::types::SimulateOptions::default();
+        let simulate =
+            |url: &str| simulate_as_bundle_with_options(vec![], &trade_info, url, &options);
+
+        let url = serve_once(
+            "HTTP/1.1 500 Internal Server Error\r\ncontent-type: application/json\r\n\
+             content-length: 2\r\nconnection: close\r\n\r\n{}",
+        );
+        assert_eq!(simulate(&url), Err(GmSimulatorError::RpcHttpStatus(500)));
+
+        // The .invalid TLD never resolves
+        assert!(matches!(
+            simulate("http://gm-simulator.invalid"),
+            Err(GmSimulatorError::RpcUnreachable(_))
+        ));
+    }
+
+    #[test]
+    fn test_mock_mint_priority_fee() {
+        use solana_compute_budget_interface::ComputeBudgetInstruction;
+
+        let trade_info = sample_trade_info();
+        let response = serde_json::json!({
+            "result": {
+                "value": {
+                    "summary": "succeeded",
+                    "transactionResults": [
+                        { "err": null, "logs": [], "unitsConsumed": 61234 },
+                        { "err": null, "logs": [], "unitsConsumed": 48211 }
+                    ]
+                }
+            }
+        });
+
+        let mut instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(80_000),
+            ComputeBudgetInstruction::set_compute_unit_price(25_000),
+        ];
+        instructions.extend(build_mock_mint_instructions(&trade_info));
+        let message = Message::new(&instructions, Some(&crate::constants::admin_minter()));
+        let mock_mint_tx = Transaction::new_unsigned(message);
+
+        assert_eq!(compute_unit_price(&mock_mint_tx), Some(25_000));
+        // 25,000 micro-lamports x 61,234 units = 1,530.85 lamports, rounded up
+        assert_eq!(
+            mock_mint_priority_fee(Some(&mock_mint_tx), &response),
+            Some(1_531)
+        );
+
+        // No price set
+        let plain = build_mock_mint_transaction(&trade_info, Hash::default());
+        assert_eq!(compute_unit_price(&plain), None);
+        assert_eq!(mock_mint_priority_fee(Some(&plain), &response), None);
+
+        // No units reported
+        let response = serde_json::json!({
+            "result": { "value": { "transactionResults": [{ "err": null }] } }
+        });
+        assert_eq!(mock_mint_priority_fee(Some(&mock_mint_tx), &response), None);
+    }
+
+    #[test]
+    fn test_parse_simulate_bundle_response_post_only() {
+        let trade_info = sample_trade_info();
+        let response = serde_json::json!({
+            "result": {
+                "value": {
+                    "summary": "succeeded",
+                    "transactionResults": [
+                        { "err": null, "logs": [] },
+                        {
+                            "err": null,
+                            "logs": [],
+                            "postExecutionAccounts": [token_account_json(0), token_account_json(1_500_000_000)]
+                        }
+                    ]
+                }
+            }
+        });
+
+        let result = parse_simulate_bundle_response(&response, &trade_info).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.taker_balance_changes.len(), 2);
+        let gm_change = &result.taker_balance_changes[1];
+        assert_eq!(gm_change.mint, trade_info.gm_token_mint);
+        assert!(!gm_change.pre_balance_known);
+        assert_eq!(gm_change.pre_balance, 0);
+        assert_eq!(gm_change.post_balance, 1_500_000_000);
+        assert!(!result.taker_balance_changes[0].pre_balance_known);
+    }
+
+    /// Build a Jito account entry holding a mint with the given decimals
+    fn mint_account_json(decimals: u8) -> serde_json::Value {
+        use base64::Engine;
+
+        let mut data = vec![0u8; 82];
+        data[44] = decimals;
+        data[45] = 1; // is_initialized
+        serde_json::json!({
+            "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+            "executable": false,
+            "lamports": 1461600,
+            "owner": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+            "rentEpoch": 18446744073709551615u64
+        })
+    }
+
+    #[test]
+    fn test_simulate_trades_request_and_response() {
+        use crate::types::SimulateOptions;
+
+        let aapl = sample_trade_info();
+        let six_decimals = GmTradeInfo {
+            gm_token_mint: Pubkey::new_unique(),
+            gm_token_amount: 2_500_000.into(),
+            ..aapl.clone()
+        };
+        // A second fill of the same mint shares its accounts
+        let trade_infos = vec![aapl.clone(), six_decimals.clone(), aapl.clone()];
+
+        let request = build_simulate_trades_request(
+            vec!["mint_a".into(), "mint_b".into(), "fill".into()],
+            &trade_infos,
+            &SimulateOptions::default(),
+        );
+        let configs = &request["params"][1]["preExecutionAccountsConfigs"];
+        assert_eq!(configs[0], serde_json::Value::Null);
+        assert_eq!(configs[1], serde_json::Value::Null);
+        let ata = |info: &GmTradeInfo| {
+            get_associated_token_address_with_program_id(
+                &info.taker,
+                &info.gm_token_mint,
+                &info.output_token_program,
+            )
+            .to_string()
+        };
+        assert_eq!(
+            configs[2]["addresses"],
+            serde_json::json!([
+                get_associated_token_address_with_program_id(
+                    &aapl.taker,
+                    &usdc_mint(),
+                    &aapl.input_token_program
+                )
+                .to_string(),
+                ata(&aapl),
+                aapl.gm_token_mint.to_string(),
+                ata(&six_decimals),
+                six_decimals.gm_token_mint.to_string(),
+            ])
+        );
+
+        let response = serde_json::json!({
+            "result": {
+                "value": {
+                    "summary": "succeeded",
+                    "transactionResults": [
+                        { "err": null, "logs": [] },
+                        { "err": null, "logs": [] },
+                        {
+                            "err": null,
+                            "logs": [],
+                            "preExecutionAccounts": [
+                                token_account_json(500_000_000),
+                                token_account_json(0),
+                                mint_account_json(9),
+                                token_account_json(0),
+                                mint_account_json(6)
+                            ],
+                            "postExecutionAccounts": [
+                                token_account_json(100_000_000),
+                                token_account_json(3_000_000_000),
+                                mint_account_json(9),
+                                token_account_json(2_500_000),
+                                mint_account_json(6)
+                            ]
+                        }
+                    ]
+                }
+            }
+        });
+
+        let result = parse_trades_bundle_response(&response, &trade_infos, 2, None).unwrap();
+        assert!(result.success);
+        let changes = &result.taker_balance_changes;
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].mint, usdc_mint());
+        assert_eq!(changes[0].change, -400_000_000);
+        assert_eq!(changes[1].mint, aapl.gm_token_mint);
+        assert_eq!(changes[1].decimals, 9);
+        assert_eq!(changes[1].change_decimal_string(), "3.000000000");
+        assert_eq!(changes[2].mint, six_decimals.gm_token_mint);
+        assert_eq!(changes[2].decimals, 6);
+        assert_eq!(changes[2].change_decimal_string(), "2.500000");
+
+        // A failing fill is reported at its own index
+        let response = serde_json::json!({
+            "result": {
+                "value": {
+                    "summary": { "failed": { "error": "fill failed", "tx_signature": null } },
+                    "transactionResults": [
+                        { "err": null, "logs": [] },
+                        { "err": null, "logs": [] },
+                        { "err": { "InstructionError": [0, "Custom"] }, "logs": [] }
+                    ]
+                }
+            }
+        });
+        let result = parse_trades_bundle_response(&response, &trade_infos, 2, None).unwrap();
+        assert!(matches!(
+            result.bundle_error,
+            Some(crate::types::BundleError::TransactionFailed { index: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_simulate_trades_as_bundle_invalid() {
+        use crate::types::SimulateOptions;
+
+        let trade_info = sample_trade_info();
+        let other_taker = GmTradeInfo {
+            taker: Pubkey::new_unique(),
+            ..trade_info.clone()
+        };
+        let tx = build_mock_mint_transaction(&trade_info, Hash::default());
+        let options = SimulateOptions::default();
+
+        for (transactions, trade_infos) in [
+            (vec![], vec![trade_info.clone()]),
+            (vec![tx.clone()], vec![]),
+            (vec![tx], vec![trade_info, other_taker]),
+        ] {
+            assert!(matches!(
+                simulate_trades_as_bundle(transactions, &trade_infos, "http://unused", &options),
+                Err(GmSimulatorError::InvalidBundle(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_simulate_bundle_response_max_logs() {
+        let response = serde_json::json!({
+            "result": {
+                "value": {
+                    "summary": "succeeded",
+                    "transactionResults": [
+                        { "err": null, "logs": [] },
+                        {
+                            "err": null,
+                            "logs": [
+                                "Program 61DFfeTKM7trxYcPQCM78bJ794ddZprZpAwAnLiwTpYH invoke [1]",
+                                "Program log: Instruction: Fill",
+                                "Program 61DFfeTKM7trxYcPQCM78bJ794ddZprZpAwAnLiwTpYH success"
+                            ]
+                        }
+                    ]
+                }
+            }
+        });
+        let trade_info = sample_trade_info();
+
+        let result = parse_bundle_response(&response, &trade_info, Some(2)).unwrap();
+        assert_eq!(
+            result.logs.unwrap(),
+            vec![
+                "Program 61DFfeTKM7trxYcPQCM78bJ794ddZprZpAwAnLiwTpYH invoke [1]",
+                "Program log: Instruction: Fill"
+            ]
+        );
+
+        // Unlimited by default
+        let result = parse_bundle_response(&response, &trade_info, None).unwrap();
+        assert_eq!(result.logs.map(|l| l.len()), Some(3));
+
+        // Dropped entirely without fetch_logs, leaving the balance changes
+        let options = crate::types::SimulateOptions::default().with_fetch_logs(false);
+        let transcript =
+            bundle_transcript(serde_json::json!({}), response, &[], &trade_info, &options).unwrap();
+        assert_eq!(transcript.result.logs, None);
+        assert!(transcript.result.success);
+    }
+
+    #[test]
+    fn test_parse_enhanced_simulation() {
+        let trade_info = sample_trade_info();
+        let taker_usdc_ata = Pubkey::new_unique();
+        let taker_gm_ata = Pubkey::new_unique();
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": { "apiVersion": "2.2.16", "slot": 354830516 },
+                "value": {
+                    "summary": "succeeded",
+                    "transactionResults": [
+                        { "err": null, "logs": [], "unitsConsumed": 61234 },
+                        {
+                            "err": null,
+                            "logs": [],
+                            "preExecutionAccounts": [token_account_json(1), token_account_json(1)],
+                            "postExecutionAccounts": [token_account_json(1), token_account_json(1)],
+                            "tokenBalanceChanges": [
+                                {
+                                    "tokenAccount": taker_usdc_ata.to_string(),
+                                    "mint": usdc_mint().to_string(),
+                                    "owner": trade_info.taker.to_string(),
+                                    "preAmount": "200000000",
+                                    "postAmount": "0",
+                                    "decimals": 6
+                                },
+                                {
+                                    "tokenAccount": taker_gm_ata.to_string(),
+                                    "mint": trade_info.gm_token_mint.to_string(),
+                                    "owner": trade_info.taker.to_string(),
+                                    "preAmount": 0,
+                                    "postAmount": 1_500_000_000u64,
+                                    "decimals": 9
+                                },
+                                {
+                                    "tokenAccount": Pubkey::new_unique().to_string(),
+                                    "mint": usdc_mint().to_string(),
+                                    "owner": trade_info.maker.to_string(),
+                                    "preAmount": "0",
+                                    "postAmount": "200000000",
+                                    "decimals": 6
+                                }
+                            ]
+                        }
+                    ]
+                }
+            }
+        });
+
+        let result = parse_enhanced_simulation(&response, &trade_info).unwrap();
+
+        assert!(result.success);
+        // The maker's entry is dropped and the raw (unchanged) account data is ignored
+        let changes = &result.taker_balance_changes;
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].token_account, taker_usdc_ata);
+        assert_eq!(changes[0].symbol.as_deref(), Some("USDC"));
+        assert_eq!(changes[0].change, -200_000_000);
+        assert_eq!(changes[1].token_account, taker_gm_ata);
+        assert_eq!(changes[1].symbol.as_deref(), Some("AAPLon"));
+        assert_eq!(changes[1].change, 1_500_000_000);
+        assert_eq!(changes[1].decimals, 9);
+    }
+
+    #[test]
+    fn test_parse_enhanced_simulation_falls_back_to_raw_accounts() {
+        let trade_info = sample_trade_info();
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": { "apiVersion": "2.2.16", "slot": 354830516 },
+                "value": {
+                    "summary": "succeeded",
+                    "transactionResults": [
+                        { "err": null, "logs": [], "unitsConsumed": 61234 },
+                        {
+                            "err": null,
+                            "logs": [],
+                            "preExecutionAccounts": [token_account_json(200_000_000), token_account_json(0)],
+                            "postExecutionAccounts": [token_account_json(0), token_account_json(1_500_000_000)],
+                            "unitsConsumed": 48211
+                        }
+                    ]
+                }
+            }
+        });
+
+        assert_eq!(
+            parse_enhanced_simulation(&response, &trade_info).unwrap(),
+            parse_simulate_bundle_response(&response, &trade_info).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_token_balance_change_zero_balances() {
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+
+        // Account exists on both sides holding zero: still reported
+        let change = parse_token_balance_change(
+            Some(&token_account_json(0)),
+            &token_account_json(0),
+            &mint,
+            None,
+            &owner,
+            &token_account,
+            9,
+        )
+        .expect("existing zero-balance account should be reported");
+        assert_eq!(change.pre_balance, 0);
+        assert_eq!(change.post_balance, 0);
+        assert_eq!(change.change, 0);
+
+        // No account data on either side: nothing to report
+        let missing = serde_json::json!({ "data": ["", "base64"], "lamports": 0 });
+        assert!(parse_token_balance_change(
+            Some(&missing),
+            &missing,
+            &mint,
+            None,
+            &owner,
+            &token_account,
+            9,
+        )
+        .is_none());
+    }
 
-        let taker_input_ata = Pubkey::new_unique();
-        let maker_input_ata = Pubkey::new_unique();
-        let taker_output_ata = Pubkey::new_unique();
-        let maker_output_ata = Pubkey::new_unique();
+    #[test]
+    fn test_parse_simulate_bundle_response_blockhash_not_found() {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": {
+                "code": -32002,
+                "message": "Transaction simulation failed: Blockhash not found"
+            }
+        });
 
-        // Account order matches actual Jupiter RFQ fill layout:
-        // taker, maker, taker_input_ata, maker_input_ata, taker_output_ata, maker_output_ata,
-        // input_mint, input_token_program, output_mint
-        Instruction {
-            program_id: jupiter_program_id,
-            accounts: vec![
-                AccountMeta::new(*taker, true),                // 0: taker
-                AccountMeta::new(*maker, true),                // 1: maker
-                AccountMeta::new(taker_input_ata, false),      // 2: taker_input_ata
-                AccountMeta::new(maker_input_ata, false),      // 3: maker_input_ata
-                AccountMeta::new(taker_output_ata, false),     // 4: taker_output_ata
-                AccountMeta::new(maker_output_ata, false),     // 5: maker_output_ata
-                AccountMeta::new_readonly(*input_mint, false), // 6: input_mint
-                AccountMeta::new_readonly(crate::constants::token_2022_program_id(), false), // 7: input_token_program
-                AccountMeta::new_readonly(*output_mint, false), // 8: output_mint
-            ],
-            data,
-        }
+        let result = parse_simulate_bundle_response(&response, &sample_trade_info()).unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.bundle_error, Some(BundleError::BlockhashNotFound));
     }
 
     #[test]
-    fn test_check_gm_trade_buy() {
-        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
-        let user = Keypair::new();
-        let usdc = usdc_mint();
-        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+    fn test_parse_simulate_bundle_response_bank_unavailable() {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": -32602, "message": "bank not found" }
+        });
 
-        let ix = create_mock_jupiter_fill(
-            &solver,
-            &user.pubkey(),
-            &usdc,
-            &aapl,
-            200_000_000,
-            1_500_000_000,
-        );
+        let result = parse_simulate_bundle_response(&response, &sample_trade_info()).unwrap();
 
-        let message = Message::new(&[ix], Some(&user.pubkey()));
-        let result = check_gm_trade_message(&message).unwrap();
+        assert!(!result.success);
+        assert!(matches!(
+            result.bundle_error,
+            Some(BundleError::SimulationBankUnavailable(_))
+        ));
+    }
 
-        assert!(result.use_gm_bundle_sim);
-        let info = result.trade_info.unwrap();
-        assert_eq!(info.maker, solver);
-        assert_eq!(info.taker, user.pubkey());
-        assert_eq!(info.gm_token_mint, aapl);
-        assert_eq!(info.gm_token_symbol, "AAPLon");
-        assert_eq!(info.gm_token_amount, 1_500_000_000);
-        assert_eq!(info.expire_at, 1704067200); // Verify expire_at is parsed
+    #[test]
+    fn test_parse_simulate_bundle_response_bundle_rejected() {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": { "apiVersion": "2.2.16", "slot": 354830516 },
+                "value": {
+                    "summary": {
+                        "failed": {
+                            "error": { "BundleLockError": "account in use" },
+                            "tx_signature": null
+                        }
+                    },
+                    "transactionResults": []
+                }
+            }
+        });
+
+        let result = parse_simulate_bundle_response(&response, &sample_trade_info()).unwrap();
+
+        assert!(!result.success);
+        assert!(matches!(
+            result.bundle_error,
+            Some(BundleError::BundleRejected(_))
+        ));
+        assert!(result.taker_balance_changes.is_empty());
     }
 
     #[test]
-    fn test_check_gm_trade_unauthorized_maker() {
-        let unauthorized_maker = Pubkey::new_unique();
-        let user = Keypair::new();
-        let usdc = usdc_mint();
-        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+    fn test_parse_simulate_bundle_response_mock_mint_failed() {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": { "apiVersion": "2.2.16", "slot": 354830516 },
+                "value": {
+                    "summary": {
+                        "failed": {
+                            "error": {
+                                "TransactionFailure": [
+                                    [1, 2, 3],
+                                    "Error processing Instruction 4: custom program error: 0x1771"
+                                ]
+                            },
+                            "tx_signature": "1111111111111111111111111111111111111111111111111111111111111111"
+                        }
+                    },
+                    "transactionResults": [
+                        { "err": { "InstructionError": [4, { "Custom": 6001 }] }, "logs": [] }
+                    ]
+                }
+            }
+        });
 
-        let ix = create_mock_jupiter_fill(
-            &unauthorized_maker,
-            &user.pubkey(),
-            &usdc,
-            &aapl,
-            200_000_000,
-            1_500_000_000,
-        );
+        let result = parse_simulate_bundle_response(&response, &sample_trade_info()).unwrap();
 
-        let message = Message::new(&[ix], Some(&user.pubkey()));
-        let result = check_gm_trade_message(&message);
+        assert!(!result.success);
+        assert!(matches!(
+            result.bundle_error,
+            Some(BundleError::TransactionFailed { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_simulate_bundle_response_fill_failed() {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": { "apiVersion": "2.2.16", "slot": 354830516 },
+                "value": {
+                    "summary": {
+                        "failed": {
+                            "error": {
+                                "TransactionFailure": [
+                                    [1, 2, 3],
+                                    "Error processing Instruction 3: custom program error: 0x1"
+                                ]
+                            },
+                            "tx_signature": "1111111111111111111111111111111111111111111111111111111111111111"
+                        }
+                    },
+                    "transactionResults": [
+                        { "err": null, "logs": [] },
+                        {
+                            "err": { "InstructionError": [3, { "Custom": 1 }] },
+                            "logs": ["Program log: Error: insufficient funds"]
+                        }
+                    ]
+                }
+            }
+        });
+
+        let result = parse_simulate_bundle_response(&response, &sample_trade_info()).unwrap();
 
+        assert!(!result.success);
         assert!(matches!(
-            result,
-            Err(GmSimulatorError::UnauthorizedMaker(_))
+            result.bundle_error,
+            Some(BundleError::TransactionFailed { index: 1, .. })
         ));
+        assert!(result.logs.is_some());
     }
 
     #[test]
-    fn test_check_gm_trade_not_gm_token() {
-        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
-        let user = Keypair::new();
-        let usdc = usdc_mint();
-        let random_token = Pubkey::new_unique();
+    fn test_simulate_bundle_request_account_overrides() {
+        use crate::types::{AccountOverride, SimulateOptions};
 
-        let ix = create_mock_jupiter_fill(
-            &solver,
-            &user.pubkey(),
-            &usdc,
-            &random_token,
-            200_000_000,
-            1_000_000_000,
+        let trade_info = sample_trade_info();
+        let taker_usdc_ata = spl_associated_token_account::get_associated_token_address(
+            &trade_info.taker,
+            &usdc_mint(),
         );
+        let options = SimulateOptions {
+            account_overrides: vec![(
+                taker_usdc_ata,
+                AccountOverride {
+                    lamports: 2039280,
+                    data: vec![1, 2, 3],
+                    owner: crate::constants::spl_token_program_id(),
+                },
+            )],
+            ..SimulateOptions::default()
+        };
 
-        let message = Message::new(&[ix], Some(&user.pubkey()));
-        let result = check_gm_trade_message(&message).unwrap();
+        let request = build_simulate_bundle_request(vec![], &trade_info, &options);
+        let entry = &request["params"][1]["accountOverrides"][taker_usdc_ata.to_string()];
 
-        assert!(!result.use_gm_bundle_sim);
-        assert!(result.trade_info.is_none());
+        assert_eq!(entry["lamports"], 2039280);
+        assert_eq!(entry["data"][0], "AQID");
+        assert_eq!(entry["data"][1], "base64");
+        assert_eq!(entry["owner"], crate::constants::SPL_TOKEN_PROGRAM_ID);
+
+        // Accounts are only requested for the fill
+        assert_eq!(
+            request["params"][1]["postExecutionAccountsConfigs"][0],
+            serde_json::Value::Null
+        );
+        assert_eq!(
+            request["params"][1]["preExecutionAccountsConfigs"][1]["addresses"][0],
+            taker_usdc_ata.to_string()
+        );
+
+        // No overrides requested: the field is omitted entirely
+        let request =
+            build_simulate_bundle_request(vec![], &trade_info, &SimulateOptions::default());
+        assert!(request["params"][1].get("accountOverrides").is_none());
     }
 
     #[test]
-    fn test_check_gm_trade_with_create_ata() {
-        use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+    fn test_simulate_bundle_request_slot() {
+        use crate::types::SimulateOptions;
 
-        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
-        let user = Keypair::new();
-        let usdc = usdc_mint();
-        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+        let trade_info = sample_trade_info();
 
-        // Create ATA instruction (typically included by solvers)
-        let create_ata_ix = create_associated_token_account_idempotent(
-            &solver,
-            &user.pubkey(),
-            &aapl,
-            &crate::constants::token_2022_program_id(),
+        let request =
+            build_simulate_bundle_request(vec![], &trade_info, &SimulateOptions::default());
+        assert_eq!(
+            request["params"][1]["simulationBank"],
+            serde_json::json!({ "commitment": { "commitment": "processed" } })
         );
 
-        // Jupiter fill instruction
-        let fill_ix = create_mock_jupiter_fill(
-            &solver,
-            &user.pubkey(),
-            &usdc,
-            &aapl,
-            200_000_000,
-            1_500_000_000,
+        let options = SimulateOptions {
+            slot: Some(312_000_000),
+            ..SimulateOptions::default()
+        };
+        let request = build_simulate_bundle_request(vec![], &trade_info, &options);
+        assert_eq!(
+            request["params"][1]["simulationBank"],
+            serde_json::json!({ "slot": 312_000_000 })
         );
+    }
 
-        // Transaction with both instructions (realistic scenario)
-        let message = Message::new(&[create_ata_ix, fill_ix], Some(&user.pubkey()));
-        let result = check_gm_trade_message(&message).unwrap();
+    #[test]
+    fn test_simulate_bundle_request_skip_sig_verify() {
+        use crate::types::SimulateOptions;
 
-        // Should still detect as GM trade despite multiple instructions
-        assert!(result.use_gm_bundle_sim);
-        let info = result.trade_info.unwrap();
-        assert_eq!(info.gm_token_mint, aapl);
-        assert_eq!(info.gm_token_amount, 1_500_000_000);
+        let trade_info = sample_trade_info();
+
+        let request =
+            build_simulate_bundle_request(vec![], &trade_info, &SimulateOptions::default());
+        assert_eq!(request["params"][1]["skipSigVerify"], true);
+
+        let options = SimulateOptions {
+            skip_sig_verify: false,
+            ..SimulateOptions::default()
+        };
+        let request = build_simulate_bundle_request(vec![], &trade_info, &options);
+        assert_eq!(request["params"][1]["skipSigVerify"], false);
     }
 
     #[test]
-    fn test_check_gm_trade_multiple_fills() {
-        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
-        let user = Keypair::new();
-        let usdc = usdc_mint();
-        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
-
-        let ix1 = create_mock_jupiter_fill(
-            &solver,
-            &user.pubkey(),
-            &usdc,
-            &aapl,
-            200_000_000,
-            1_500_000_000,
+    fn test_simulate_options_builder() {
+        use crate::types::SimulateOptions;
+        use solana_sdk::commitment_config::CommitmentLevel;
+        use std::time::Duration;
+
+        let trade_info = sample_trade_info();
+
+        // The defaults reproduce the historical request
+        let options = SimulateOptions::default();
+        assert_eq!(options.timeout, Duration::from_secs(30));
+        assert_eq!(options.max_retries, 0);
+        let request = build_simulate_bundle_request(vec![], &trade_info, &options);
+        assert_eq!(request["params"][1]["replaceRecentBlockhash"], true);
+        assert_eq!(request["params"][1]["skipSigVerify"], true);
+        assert_eq!(
+            request["params"][1]["simulationBank"],
+            serde_json::json!({ "commitment": { "commitment": "processed" } })
         );
-        let ix2 = create_mock_jupiter_fill(
-            &solver,
-            &user.pubkey(),
-            &usdc,
-            &aapl,
-            100_000_000,
-            750_000_000,
+
+        let options = SimulateOptions::default()
+            .with_commitment(CommitmentLevel::Confirmed)
+            .with_replace_recent_blockhash(false)
+            .with_skip_sig_verify(false)
+            .with_timeout(Duration::from_secs(5))
+            .with_max_retries(2)
+            .with_max_logs(10)
+            .with_header("x-api-key", "secret");
+        assert_eq!(options.timeout, Duration::from_secs(5));
+        assert_eq!(options.max_retries, 2);
+        assert_eq!(options.max_logs, Some(10));
+        assert_eq!(
+            options.headers,
+            vec![("x-api-key".to_string(), "secret".to_string())]
         );
 
-        let message = Message::new(&[ix1, ix2], Some(&user.pubkey()));
-        let result = check_gm_trade_message(&message).unwrap();
+        let request = build_simulate_bundle_request(vec![], &trade_info, &options);
+        assert_eq!(request["params"][1]["replaceRecentBlockhash"], false);
+        assert_eq!(request["params"][1]["skipSigVerify"], false);
+        assert_eq!(
+            request["params"][1]["simulationBank"],
+            serde_json::json!({ "commitment": { "commitment": "confirmed" } })
+        );
 
-        // With multiple fill instructions, we detect the first one as a GM trade
-        // This is an edge case - in practice, transactions typically have one fill
-        assert!(result.use_gm_bundle_sim);
+        // A pinned slot takes precedence over the commitment
+        let request =
+            build_simulate_bundle_request(vec![], &trade_info, &options.with_slot(312_000_000));
+        assert_eq!(
+            request["params"][1]["simulationBank"],
+            serde_json::json!({ "slot": 312_000_000 })
+        );
     }
 
     #[test]
-    fn test_build_mock_mint_transaction() {
-        let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
-        let aapl = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
-
-        let trade_info = GmTradeInfo {
-            maker: solver,
-            taker: Pubkey::new_unique(),
-            gm_token_mint: aapl,
-            gm_token_symbol: "AAPLon".to_string(),
-            gm_token_amount: 1_500_000_000,
-            maker_output_account: Pubkey::new_unique(),
-            expire_at: 1704067200,
-        };
+    fn test_parse_mock_mint_response() {
+        use crate::types::{BundleError, SimulateOptions};
+
+        let json = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": { "slot": 1 },
+                "value": {
+                    "summary": { "failed": {
+                        "error": { "TransactionFailure": ["sig", "custom program error: 0x1771"] },
+                        "tx_signature": "sig"
+                    } },
+                    "transactionResults": [{
+                        "err": { "InstructionError": [4, { "Custom": 6001 }] },
+                        "logs": ["Program log: Instruction: MintGm", "Program log: stale"]
+                    }]
+                }
+            }
+        });
 
-        let mock_tx = build_mock_mint_transaction(&trade_info, Hash::default());
+        let result = parse_mock_mint_response(&json, Some(1)).unwrap();
+        assert!(!result.success);
+        assert!(matches!(
+            result.bundle_error,
+            Some(BundleError::TransactionFailed { index: 0, .. })
+        ));
+        assert_eq!(
+            result.logs,
+            Some(vec!["Program log: Instruction: MintGm".to_string()])
+        );
 
-        // Verify the transaction structure
-        // Should have 5 instructions: create taker GM ATA + create maker GM ATA + create taker USDC ATA + create maker USDC ATA + mint
-        assert_eq!(mock_tx.message.instructions.len(), 5);
-        // Transaction has 1 signature slot (for the minter/fee payer), but it's not signed yet
-        assert_eq!(mock_tx.signatures.len(), 1);
-        // All signatures should be default (all zeros) since it's unsigned
-        assert!(mock_tx
-            .signatures
-            .iter()
-            .all(|sig| sig.as_ref().iter().all(|&b| b == 0)));
+        let json = serde_json::json!({
+            "result": { "value": {
+                "summary": "succeeded",
+                "transactionResults": [{ "err": null, "logs": [] }]
+            } }
+        });
+        let result = parse_mock_mint_response(&json, None).unwrap();
+        assert!(result.success);
+        assert!(result.taker_balance_changes.is_empty());
+
+        let request = simulate_bundle_request(vec![], vec![None], &SimulateOptions::default());
+        assert_eq!(
+            request["params"][1]["preExecutionAccountsConfigs"],
+            serde_json::json!([null])
+        );
     }
 
     /// Comprehensive test with hardcoded transactions for both BUY and SELL scenarios.
@@ -1085,8 +4786,9 @@ mod tests {
         let sell_result = check_gm_trade(&sell_tx).expect("Failed to check GM trade");
 
         if !sell_result.use_gm_bundle_sim {
+            assert_eq!(sell_result.reason, Some(NoBundleReason::SellDirection));
             println!("✓ Correctly identified as GM SELL trade (bundle simulation NOT required)");
-            println!("  Reason: Solver already has USDC, no minting needed");
+            println!("  Reason: {}", NoBundleReason::SellDirection.description());
         } else {
             panic!("SELL transaction incorrectly identified as requiring bundle simulation");
         }
@@ -1155,52 +4857,32 @@ mod tests {
 
         // Extract the transaction - it comes as base64 encoded
         let encoded_tx = tx_with_meta.transaction.transaction;
-        let tx_data = match encoded_tx {
-            solana_transaction_status::EncodedTransaction::Binary(data, encoding) => {
-                use base64::Engine;
-                match encoding {
-                    solana_transaction_status::TransactionBinaryEncoding::Base58 => {
-                        bs58::decode(&data)
-                            .into_vec()
-                            .expect("Failed to decode base58")
-                    }
-                    solana_transaction_status::TransactionBinaryEncoding::Base64 => {
-                        base64::engine::general_purpose::STANDARD
-                            .decode(&data)
-                            .expect("Failed to decode base64")
-                    }
-                }
+        let versioned_tx = match encoded_tx {
+            solana_transaction_status::EncodedTransaction::Binary(data, _) => {
+                crate::decode_transaction(&data).expect("Failed to decode transaction")
             }
             _ => panic!("Transaction not in binary format"),
         };
 
-        // Try to deserialize as VersionedTransaction first (v0 transactions)
-        let mut original_tx: Transaction = if let Ok(versioned_tx) =
-            bincode::deserialize::<solana_sdk::transaction::VersionedTransaction>(&tx_data)
-        {
-            // Convert VersionedTransaction to legacy Transaction for our API
-            match versioned_tx.message {
-                solana_sdk::message::VersionedMessage::Legacy(legacy_msg) => {
-                    let mut tx = Transaction::new_unsigned(legacy_msg);
-                    tx.signatures = versioned_tx.signatures;
-                    tx
-                }
-                solana_sdk::message::VersionedMessage::V0(v0_msg) => {
-                    // Convert v0 message to legacy format
-                    let legacy_msg = Message {
-                        header: v0_msg.header,
-                        account_keys: v0_msg.account_keys,
-                        recent_blockhash: v0_msg.recent_blockhash,
-                        instructions: v0_msg.instructions,
-                    };
-                    let mut tx = Transaction::new_unsigned(legacy_msg);
-                    tx.signatures = versioned_tx.signatures;
-                    tx
-                }
+        // Convert VersionedTransaction to legacy Transaction for our API
+        let mut original_tx: Transaction = match versioned_tx.message {
+            solana_sdk::message::VersionedMessage::Legacy(legacy_msg) => {
+                let mut tx = Transaction::new_unsigned(legacy_msg);
+                tx.signatures = versioned_tx.signatures;
+                tx
+            }
+            solana_sdk::message::VersionedMessage::V0(v0_msg) => {
+                // Convert v0 message to legacy format
+                let legacy_msg = Message {
+                    header: v0_msg.header,
+                    account_keys: v0_msg.account_keys,
+                    recent_blockhash: v0_msg.recent_blockhash,
+                    instructions: v0_msg.instructions,
+                };
+                let mut tx = Transaction::new_unsigned(legacy_msg);
+                tx.signatures = versioned_tx.signatures;
+                tx
             }
-        } else {
-            // Fall back to legacy transaction deserialization
-            bincode::deserialize(&tx_data).expect("Failed to deserialize transaction")
         };
 
         println!("✓ Fetched transaction successfully");
@@ -1232,12 +4914,11 @@ mod tests {
                         get_gm_token_symbol, is_authorized_solver, is_gm_token,
                     };
 
-                    let taker = original_tx.message.account_keys[instruction.accounts[0] as usize];
-                    let maker = original_tx.message.account_keys[instruction.accounts[1] as usize];
-                    let input_mint =
-                        original_tx.message.account_keys[instruction.accounts[6] as usize];
-                    let output_mint =
-                        original_tx.message.account_keys[instruction.accounts[8] as usize];
+                    let accounts = resolve_instruction_accounts(&original_tx.message, instruction);
+                    let taker = accounts[0].pubkey;
+                    let maker = accounts[1].pubkey;
+                    let input_mint = accounts[6].pubkey;
+                    let output_mint = accounts[8].pubkey;
 
                     println!("\n      Trade Analysis:");
                     println!("        Taker (user): {}", taker);
@@ -1344,15 +5025,8 @@ mod tests {
 
         if result.use_gm_bundle_sim {
             // This is a BUY transaction - proceed with full bundle simulation test
+            println!("\n{}", result.summary_string());
             let trade_info = result.trade_info.expect("Expected trade info");
-            println!("\n✓ GM BUY Trade Confirmed:");
-            println!("  Maker (solver): {}", trade_info.maker);
-            println!("  Taker (user): {}", trade_info.taker);
-            println!(
-                "  GM Token: {} ({})",
-                trade_info.gm_token_symbol, trade_info.gm_token_mint
-            );
-            println!("  Amount: {}", trade_info.gm_token_amount);
             println!("  Expire At: {}", trade_info.expire_at);
 
             // Build the mock mint transaction
@@ -1383,34 +5057,30 @@ mod tests {
             };
 
             // Reconstruct transactions with fresh blockhash
-            let mock_mint_tx_fresh = {
-                let mut msg = mock_mint_tx.message.clone();
-                msg.recent_blockhash = fresh_blockhash;
-                Transaction::new_unsigned(msg)
-            };
+            let mock_mint_tx_fresh = crate::with_blockhash(&mock_mint_tx, fresh_blockhash);
 
             let original_tx_fresh = {
-                let mut msg = original_tx.message.clone();
-                msg.recent_blockhash = fresh_blockhash;
+                let mut tx = crate::with_blockhash(&original_tx, fresh_blockhash);
+                let msg = &mut tx.message;
 
                 // Update the expire_at field in the Jupiter fill instruction to prevent expiration errors
                 for instruction in &mut msg.instructions {
                     let program_id = msg.account_keys[instruction.program_id_index as usize];
-                    if program_id == jupiter_order_engine_program_id() {
-                        if instruction.data.len() >= 32 {
-                            // Set expire_at to 1 hour from now
-                            let future_expire = std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs() as i64
-                                + 3600;
-                            instruction.data[24..32].copy_from_slice(&future_expire.to_le_bytes());
-                            println!("  Updated expire_at to: {}", future_expire);
-                        }
+                    if program_id == jupiter_order_engine_program_id()
+                        && instruction.data.len() >= 32
+                    {
+                        // Set expire_at to 1 hour from now
+                        let future_expire = std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .unwrap()
+                            .as_secs() as i64
+                            + 3600;
+                        instruction.data[24..32].copy_from_slice(&future_expire.to_le_bytes());
+                        println!("  Updated expire_at to: {}", future_expire);
                     }
                 }
 
-                Transaction::new_unsigned(msg)
+                tx
             };
 
             // Use Jito bundle simulation via simulate_as_bundle
@@ -1495,9 +5165,9 @@ mod tests {
         }
     }
 
-    /// Test a base64-encoded transaction payload from a file.
+    /// Test a base58- or base64-encoded transaction payload from a file.
     ///
-    /// This test reads a base64-encoded transaction from `./payload` file and:
+    /// This test reads an encoded transaction from `./payload` file and:
     /// 1. Decodes and deserializes the transaction
     /// 2. Checks if it's a GM trade
     /// 3. If yes, builds mock mint and simulates the bundle
@@ -1506,8 +5176,6 @@ mod tests {
     #[test]
     #[ignore]
     fn test_payload_file() {
-        use base64::Engine;
-
         println!("{}", "=".repeat(80));
         println!("PAYLOAD FILE TEST");
         println!("{}", "=".repeat(80));
@@ -1516,22 +5184,8 @@ mod tests {
         let payload_path = std::env::var("PAYLOAD_FILE").unwrap_or_else(|_| "payload".to_string());
         println!("Reading payload from: {}", payload_path);
 
-        let base64_payload = std::fs::read_to_string(&payload_path)
-            .expect("Failed to read payload file")
-            .trim()
-            .to_string();
-
-        println!("Payload length: {} bytes (base64)", base64_payload.len());
-
-        // Decode base64
-        let tx_data = base64::engine::general_purpose::STANDARD
-            .decode(&base64_payload)
-            .expect("Failed to decode base64 payload");
-        println!("Decoded transaction: {} bytes", tx_data.len());
-
-        // Try to deserialize as VersionedTransaction first
         let versioned_tx: VersionedTransaction =
-            bincode::deserialize(&tx_data).expect("Failed to deserialize as VersionedTransaction");
+            crate::load_transaction_from_file(&payload_path).expect("Failed to load payload");
 
         println!(
             "Transaction type: {}",
@@ -1550,7 +5204,13 @@ mod tests {
                 println!("✓ GM BUY trade detected - bundle simulation REQUIRED");
             }
             Ok(check_result) if !check_result.use_gm_bundle_sim => {
-                println!("✓ GM SELL trade or non-GM trade - bundle simulation NOT required");
+                let reason = check_result
+                    .reason
+                    .map_or("SELL or non-GM", |reason| reason.description());
+                println!(
+                    "✓ Not a GM BUY ({}) - bundle simulation NOT required",
+                    reason
+                );
             }
             Ok(_) => {}
             Err(e) => {
@@ -1562,20 +5222,8 @@ mod tests {
         let result = result.expect("Failed to check GM trade");
 
         if result.use_gm_bundle_sim {
+            println!("\n{}", result.summary_string());
             let trade_info = result.trade_info.expect("Expected trade info");
-            println!("\n✓ GM BUY Trade Details:");
-            println!("  Maker (solver): {}", trade_info.maker);
-            println!("  Taker (user): {}", trade_info.taker);
-            println!(
-                "  GM Token: {} ({})",
-                trade_info.gm_token_symbol, trade_info.gm_token_mint
-            );
-            println!(
-                "  Amount: {} ({:.6} {})",
-                trade_info.gm_token_amount,
-                trade_info.gm_token_amount as f64 / 1_000_000_000.0,
-                trade_info.gm_token_symbol
-            );
             println!("  Expire At: {}", trade_info.expire_at);
 
             // Initialize RPC client
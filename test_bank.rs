@@ -0,0 +1,430 @@
+//! In-memory bundle simulation using `solana-program-test`.
+//!
+//! The Ondo GM and Jupiter Order Engine programs are not available as local binaries,
+//! so they are replaced with native stubs that implement just enough of `mint_gm` and
+//! `fill` to move tokens: `mint_gm` mints to the destination via the real Token-2022
+//! program, and `fill` swaps the input and output legs via the fill's token programs.
+//! SPL Token, Token-2022 and the ATA program are the real programs bundled with
+//! `solana-program-test`.
+//!
+//! Enabled with the `test-bank` feature.
+
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account, program_option::COption, program_pack::Pack, pubkey::Pubkey, rent::Rent,
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program::invoke,
+        program::invoke_signed, program_error::ProgramError,
+    },
+    state::{Account as TokenAccount, AccountState, Mint},
+};
+
+use crate::{
+    constants::{
+        admin_minter, jupiter_order_engine_program_id, ondo_gm_program_id, stable_mint_symbol,
+        STABLE_DECIMALS,
+    },
+    transaction::with_blockhash,
+    types::{BalanceChange, BundleError, BundleSimulationResult, GmSimulatorError, GmTradeInfo},
+};
+
+/// Lamports given to each fee payer so they can create ATAs
+const FEE_PAYER_LAMPORTS: u64 = 10_000_000_000;
+
+/// Build a `ProgramTest` with stub GM and Jupiter programs and the trade's accounts.
+///
+/// Creates the quote mint (`trade_info.quote_mint`, e.g. USDC), the GM token mint (owned
+/// by `trade_info.output_token_program` with the GM program's `mint_authority` PDA as
+/// authority), funds the admin minter, taker and maker with SOL, and gives the taker a
+/// quote account holding `taker_quote_balance`.
+///
+/// # Arguments
+///
+/// * `trade_info` - The GM trade to set up accounts for
+/// * `taker_quote_balance` - Quote mint base units in the taker's input token account
+pub fn program_test(trade_info: &GmTradeInfo, taker_quote_balance: u64) -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program(
+        "ondo_gm_stub",
+        ondo_gm_program_id(),
+        processor!(process_mint_gm),
+    );
+    program_test.add_program(
+        "jupiter_order_engine_stub",
+        jupiter_order_engine_program_id(),
+        processor!(process_fill),
+    );
+
+    let (mint_authority, _) =
+        Pubkey::find_program_address(&[b"mint_authority"], &ondo_gm_program_id());
+    program_test.add_account(
+        trade_info.quote_mint,
+        mint_account(&trade_info.input_token_program, None, STABLE_DECIMALS),
+    );
+    program_test.add_account(
+        trade_info.gm_token_mint,
+        mint_account(&trade_info.output_token_program, Some(mint_authority), 9),
+    );
+
+    for payer in [admin_minter(), trade_info.taker, trade_info.maker] {
+        program_test.add_account(
+            payer,
+            Account::new(
+                FEE_PAYER_LAMPORTS,
+                0,
+                &solana_system_interface::program::id(),
+            ),
+        );
+    }
+
+    let taker_quote_ata = get_associated_token_address_with_program_id(
+        &trade_info.taker,
+        &trade_info.quote_mint,
+        &trade_info.input_token_program,
+    );
+    program_test.add_account(
+        taker_quote_ata,
+        token_account(
+            &trade_info.input_token_program,
+            &trade_info.quote_mint,
+            &trade_info.taker,
+            taker_quote_balance,
+        ),
+    );
+
+    program_test
+}
+
+/// Execute a `[mock_mint, fill]` bundle in the bank and report the taker's balance changes.
+///
+/// Transactions are rebuilt with the bank's latest blockhash and executed in order
+/// without signature verification, mirroring `replaceRecentBlockhash` and
+/// `skipSigVerify` in a Jito `simulateBundle` call. Unlike a real bundle, transactions
+/// that ran before a failure are not rolled back.
+///
+/// # Arguments
+///
+/// * `banks_client` - Client for a bank started from `program_test`
+/// * `transactions` - The bundle, typically `[mock_mint_tx, fill_tx]`
+/// * `trade_info` - The GM trade info containing taker and token information
+pub async fn simulate_bundle_in_bank(
+    banks_client: &mut BanksClient,
+    transactions: Vec<Transaction>,
+    trade_info: &GmTradeInfo,
+) -> Result<BundleSimulationResult, GmSimulatorError> {
+    let bank_error = |e: solana_program_test::BanksClientError| {
+        GmSimulatorError::InstructionParseError(format!("Bank error: {}", e))
+    };
+
+    let blockhash = banks_client
+        .get_latest_blockhash()
+        .await
+        .map_err(bank_error)?;
+
+    let taker_quote_ata = get_associated_token_address_with_program_id(
+        &trade_info.taker,
+        &trade_info.quote_mint,
+        &trade_info.input_token_program,
+    );
+    let taker_gm_ata = get_associated_token_address_with_program_id(
+        &trade_info.taker,
+        &trade_info.gm_token_mint,
+        &trade_info.output_token_program,
+    );
+    let tracked = [
+        (
+            taker_quote_ata,
+            trade_info.quote_mint,
+            stable_mint_symbol(&trade_info.quote_mint).map(str::to_string),
+            STABLE_DECIMALS,
+        ),
+        (
+            taker_gm_ata,
+            trade_info.gm_token_mint,
+            Some(trade_info.gm_token_symbol.clone()),
+            9,
+        ),
+    ];
+
+    let mut pre_balances = Vec::new();
+    let mut logs = None;
+
+    for (index, tx) in transactions.iter().enumerate() {
+        // Balances are tracked around the fill (index 1), like the pre/post account configs
+        if index == 1 {
+            for (account, ..) in &tracked {
+                pre_balances.push(token_balance(banks_client, account).await?);
+            }
+        }
+
+        let result = banks_client
+            .process_transaction_with_metadata(with_blockhash(tx, blockhash))
+            .await
+            .map_err(bank_error)?;
+
+        if index == 1 {
+            logs = result.metadata.as_ref().map(|m| m.log_messages.clone());
+        }
+
+        if let Err(err) = result.result {
+            let mut failed = BundleSimulationResult::failed(BundleError::TransactionFailed {
+                index,
+                error: err.to_string(),
+            });
+            failed.logs = result.metadata.map(|m| m.log_messages);
+            return Ok(failed);
+        }
+    }
+
+    let mut taker_balance_changes = Vec::new();
+    for ((token_account, mint, symbol, decimals), pre) in tracked.into_iter().zip(pre_balances) {
+        let post = token_balance(banks_client, &token_account).await?;
+        if pre.is_none() && post.is_none() {
+            continue;
+        }
+        let pre_balance = pre.unwrap_or(0);
+        let post_balance = post.unwrap_or(0);
+        taker_balance_changes.push(BalanceChange {
+            mint,
+            symbol,
+            owner: trade_info.taker,
+            token_account,
+            pre_balance,
+            pre_balance_known: true,
+            post_balance,
+            change: post_balance as i128 - pre_balance as i128,
+            decimals,
+        });
+    }
+
+    Ok(BundleSimulationResult {
+        success: true,
+        error: None,
+        bundle_error: None,
+        taker_balance_changes,
+        logs,
+        priority_fee_lamports: None,
+    })
+}
+
+/// Read a token account's balance, or `None` if it does not exist
+async fn token_balance(
+    banks_client: &mut BanksClient,
+    token_account: &Pubkey,
+) -> Result<Option<u64>, GmSimulatorError> {
+    let account = banks_client
+        .get_account(*token_account)
+        .await
+        .map_err(|e| GmSimulatorError::InstructionParseError(format!("Bank error: {}", e)))?;
+
+    Ok(account.and_then(|a| {
+        StateWithExtensions::<TokenAccount>::unpack(&a.data)
+            .ok()
+            .map(|s| s.base.amount)
+    }))
+}
+
+/// Build an initialized mint account owned by `token_program`
+fn mint_account(token_program: &Pubkey, mint_authority: Option<Pubkey>, decimals: u8) -> Account {
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: mint_authority.map_or(COption::None, COption::Some),
+        supply: 0,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+
+    Account {
+        lamports: Rent::default().minimum_balance(Mint::LEN),
+        data,
+        owner: *token_program,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Build an initialized token account owned by `token_program`
+fn token_account(token_program: &Pubkey, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; TokenAccount::LEN];
+    TokenAccount {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        ..TokenAccount::default()
+    }
+    .pack_into_slice(&mut data);
+
+    Account {
+        lamports: Rent::default().minimum_balance(TokenAccount::LEN),
+        data,
+        owner: *token_program,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Read the decimals of a mint account
+fn mint_decimals(mint: &AccountInfo) -> Result<u8, ProgramError> {
+    let data = mint.try_borrow_data()?;
+    Ok(StateWithExtensions::<Mint>::unpack(&data)?.base.decimals)
+}
+
+/// Read the little-endian u64 at `offset` in instruction data
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, ProgramError> {
+    data.get(offset..offset + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+/// Stub `mint_gm`: mint `amount` to the destination, signed by the `mint_authority` PDA
+///
+/// Accounts follow `MintGmAccounts`; role and oracle checks are skipped.
+fn process_mint_gm(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let amount = read_u64(data, 8)?;
+    let [_payer, _authority, _user, _role, _oracle, mint_authority, mint, destination, _manager, token_program, ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (_, bump) = Pubkey::find_program_address(&[b"mint_authority"], program_id);
+    let ix = spl_token_2022::instruction::mint_to(
+        token_program.key,
+        mint.key,
+        destination.key,
+        mint_authority.key,
+        &[],
+        amount,
+    )?;
+    invoke_signed(
+        &ix,
+        &[
+            mint.clone(),
+            destination.clone(),
+            mint_authority.clone(),
+            token_program.clone(),
+        ],
+        &[&[b"mint_authority", &[bump]]],
+    )
+}
+
+/// Stub Jupiter `fill`: the taker sends `input_amount` and the maker sends `output_amount`
+///
+/// Accounts follow the fill layout in `parser`; expiry is not checked.
+fn process_fill(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let input_amount = read_u64(data, 8)?;
+    let output_amount = read_u64(data, 16)?;
+    let [taker, maker, taker_input, maker_input, taker_output, maker_output, input_mint, input_token_program, output_mint, output_token_program, ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    transfer_checked(
+        input_token_program,
+        taker_input,
+        input_mint,
+        maker_input,
+        taker,
+        input_amount,
+    )?;
+    transfer_checked(
+        output_token_program,
+        maker_output,
+        output_mint,
+        taker_output,
+        maker,
+        output_amount,
+    )
+}
+
+/// CPI a `transfer_checked` of `amount` from `source` to `destination`
+fn transfer_checked<'a>(
+    token_program: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let ix = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        source.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        &[],
+        amount,
+        mint_decimals(mint)?,
+    )?;
+    invoke(
+        &ix,
+        &[
+            source.clone(),
+            mint.clone(),
+            destination.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        build_mock_mint_transaction,
+        test_utils::{fill_transaction, sample_trade_info},
+    };
+    use solana_sdk::hash::Hash;
+
+    #[tokio::test]
+    async fn test_simulate_bundle_in_bank() {
+        let trade_info = sample_trade_info();
+        let (mut banks_client, _, _) = program_test(&trade_info, 200_000_000).start().await;
+
+        let mock_mint = build_mock_mint_transaction(&trade_info, Hash::default());
+        let fill = fill_transaction(&trade_info, 200_000_000, Hash::default());
+
+        let result = simulate_bundle_in_bank(&mut banks_client, vec![mock_mint, fill], &trade_info)
+            .await
+            .unwrap();
+
+        assert!(result.success, "{:?}", result);
+        assert_eq!(result.taker_balance_changes.len(), 2);
+        assert_eq!(result.taker_balance_changes[0].change, -200_000_000);
+        assert_eq!(result.taker_balance_changes[1].pre_balance, 0);
+        assert_eq!(result.taker_balance_changes[1].change, 1_500_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_bundle_in_bank_fill_fails() {
+        let trade_info = sample_trade_info();
+        // The taker can't cover the input amount
+        let (mut banks_client, _, _) = program_test(&trade_info, 100_000_000).start().await;
+
+        let mock_mint = build_mock_mint_transaction(&trade_info, Hash::default());
+        let fill = fill_transaction(&trade_info, 200_000_000, Hash::default());
+
+        let result = simulate_bundle_in_bank(&mut banks_client, vec![mock_mint, fill], &trade_info)
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(matches!(
+            result.bundle_error,
+            Some(BundleError::TransactionFailed { index: 1, .. })
+        ));
+        assert!(result.logs.is_some());
+    }
+}
@@ -0,0 +1,104 @@
+//! Fixtures shared by the unit tests.
+
+use solana_sdk::{
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::str::FromStr;
+
+use crate::{
+    constants::{
+        jupiter_order_engine_program_id, spl_token_program_id, token_2022_program_id, usdc_mint,
+    },
+    discriminator::instruction_discriminator,
+    types::{GmTradeInfo, TradeDirection},
+};
+
+/// A BUY of 1.5 AAPLon for USDC from an authorized solver to a fresh taker
+///
+/// The maker output account is the solver's canonical AAPLon ATA.
+pub(crate) fn sample_trade_info() -> GmTradeInfo {
+    let maker = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
+    let gm_token_mint = Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap();
+    let output_token_program = token_2022_program_id();
+
+    GmTradeInfo {
+        maker,
+        taker: Pubkey::new_unique(),
+        gm_token_mint,
+        gm_token_symbol: "AAPLon".to_string(),
+        gm_token_amount: 1_500_000_000.into(),
+        input_amount: 200_000_000.into(),
+        maker_output_account: get_associated_token_address_with_program_id(
+            &maker,
+            &gm_token_mint,
+            &output_token_program,
+        ),
+        expire_at: 1704067200,
+        input_token_program: spl_token_program_id(),
+        output_token_program,
+        direction: TradeDirection::Buy,
+        quote_mint: usdc_mint(),
+    }
+}
+
+/// An unsigned Jupiter fill settling `trade_info` for `quote_amount` of its quote mint
+///
+/// The token accounts are the taker's and maker's ATAs, apart from the trade's
+/// `maker_output_account`.
+pub(crate) fn fill_transaction(
+    trade_info: &GmTradeInfo,
+    quote_amount: u64,
+    blockhash: Hash,
+) -> Transaction {
+    let ix = fill_instruction(trade_info, quote_amount);
+    let message = Message::new_with_blockhash(&[ix], Some(&trade_info.taker), &blockhash);
+    Transaction::new_unsigned(message)
+}
+
+/// The Jupiter fill instruction in `fill_transaction`, for tests that edit its accounts
+///
+/// A BUY sends the quote mint and receives the GM token, a SELL the reverse; the input
+/// and output legs use the trade's `input_token_program` and `output_token_program`.
+pub(crate) fn fill_instruction(trade_info: &GmTradeInfo, quote_amount: u64) -> Instruction {
+    let ata = |owner: &Pubkey, mint: &Pubkey, program: &Pubkey| {
+        get_associated_token_address_with_program_id(owner, mint, program)
+    };
+
+    let (quote, gm) = (trade_info.quote_mint, trade_info.gm_token_mint);
+    let gm_amount = trade_info.gm_token_amount.0;
+    let (input_mint, output_mint, input_amount, output_amount) = match trade_info.direction {
+        TradeDirection::Buy => (quote, gm, quote_amount, gm_amount),
+        TradeDirection::Sell => (gm, quote, gm_amount, quote_amount),
+    };
+    let (input_program, output_program) = (
+        trade_info.input_token_program,
+        trade_info.output_token_program,
+    );
+
+    let mut data = instruction_discriminator("fill").to_vec();
+    data.extend_from_slice(&input_amount.to_le_bytes());
+    data.extend_from_slice(&output_amount.to_le_bytes());
+    data.extend_from_slice(&trade_info.expire_at.to_le_bytes());
+
+    Instruction {
+        program_id: jupiter_order_engine_program_id(),
+        accounts: vec![
+            AccountMeta::new(trade_info.taker, true),
+            AccountMeta::new(trade_info.maker, true),
+            AccountMeta::new(ata(&trade_info.taker, &input_mint, &input_program), false),
+            AccountMeta::new(ata(&trade_info.maker, &input_mint, &input_program), false),
+            AccountMeta::new(ata(&trade_info.taker, &output_mint, &output_program), false),
+            AccountMeta::new(trade_info.maker_output_account, false),
+            AccountMeta::new_readonly(input_mint, false),
+            AccountMeta::new_readonly(input_program, false),
+            AccountMeta::new_readonly(output_mint, false),
+            AccountMeta::new_readonly(output_program, false),
+        ],
+        data,
+    }
+}
@@ -0,0 +1,291 @@
+//! A local `solana-test-validator` with the mainnet programs cloned.
+//!
+//! Closer to mainnet than `test_bank`: the real Ondo GM, Jupiter Order Engine and
+//! Token-2022 programs, plus any accounts listed (e.g. GM mints), are cloned from a
+//! source cluster when the validator starts. The `TestValidator` guard kills the
+//! validator and removes its temporary ledger when dropped.
+//!
+//! `simulateBundle` is a Jito RPC method, so the stock Agave validator can't simulate
+//! bundles; set `TestValidatorConfig::validator_path` to the `solana-test-validator`
+//! built from jito-solana for that.
+//!
+//! Enabled with the `test-validator` feature.
+
+use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+use std::{
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    constants::{jupiter_order_engine_program_id, ondo_gm_program_id, token_2022_program_id},
+    rpc::is_healthy,
+    simulator::simulate_as_bundle_with_options,
+    types::{BundleSimulationResult, GmSimulatorError, GmTradeInfo, SimulateOptions},
+};
+
+/// How often to poll `getHealth` while waiting for the validator
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How to start a local test validator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestValidatorConfig {
+    /// The validator binary (default `solana-test-validator` on `PATH`)
+    pub validator_path: PathBuf,
+    /// The cluster to clone programs and accounts from (default mainnet-beta)
+    pub clone_url: String,
+    /// Upgradeable programs to clone (default Ondo GM, Jupiter Order Engine and Token-2022)
+    pub programs: Vec<Pubkey>,
+    /// Accounts to clone, e.g. the GM mints a test trades (none by default)
+    pub accounts: Vec<Pubkey>,
+    /// JSON-RPC port (default 8899)
+    pub rpc_port: u16,
+    /// Ledger directory, kept after shutdown; a removed temporary directory if `None`
+    pub ledger_dir: Option<PathBuf>,
+    /// How long to wait for the validator to report healthy (default 60 seconds)
+    pub startup_timeout: Duration,
+}
+
+impl Default for TestValidatorConfig {
+    fn default() -> Self {
+        Self {
+            validator_path: PathBuf::from("solana-test-validator"),
+            clone_url: "https://api.mainnet-beta.solana.com".to_string(),
+            programs: vec![
+                ondo_gm_program_id(),
+                jupiter_order_engine_program_id(),
+                token_2022_program_id(),
+            ],
+            accounts: vec![],
+            rpc_port: 8899,
+            ledger_dir: None,
+            startup_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl TestValidatorConfig {
+    /// The validator's command-line arguments for a ledger at `ledger_dir`
+    pub fn args(&self, ledger_dir: &Path) -> Vec<String> {
+        let mut args = vec![
+            "--reset".to_string(),
+            "--quiet".to_string(),
+            "--ledger".to_string(),
+            ledger_dir.display().to_string(),
+            "--rpc-port".to_string(),
+            self.rpc_port.to_string(),
+            "--url".to_string(),
+            self.clone_url.clone(),
+        ];
+        for program in &self.programs {
+            args.push("--clone-upgradeable-program".to_string());
+            args.push(program.to_string());
+        }
+        for account in &self.accounts {
+            args.push("--clone".to_string());
+            args.push(account.to_string());
+        }
+        args
+    }
+
+    /// The validator's JSON-RPC URL
+    pub fn rpc_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.rpc_port)
+    }
+}
+
+/// A running test validator, torn down on drop
+///
+/// Validators started with `start` are killed on drop and their temporary ledger
+/// removed; one attached with `connect` is left running.
+#[derive(Debug)]
+pub struct TestValidator {
+    child: Option<Child>,
+    rpc_url: String,
+    temp_ledger: Option<PathBuf>,
+}
+
+impl TestValidator {
+    /// Start a validator and wait until it reports healthy
+    ///
+    /// Cloning fetches every program and account from `clone_url`, so this needs
+    /// network access and can take a while.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TestValidator)` once `getHealth` returns "ok"
+    /// * `Err(TestValidator)` if the binary can't be started, exits early (e.g. a clone
+    ///   failed) or isn't healthy within `startup_timeout`
+    pub fn start(config: &TestValidatorConfig) -> Result<Self, GmSimulatorError> {
+        let temp_ledger = config.ledger_dir.is_none().then(|| {
+            std::env::temp_dir().join(format!("gm-sim-validator-{}", Pubkey::new_unique()))
+        });
+        let ledger_dir = config
+            .ledger_dir
+            .as_deref()
+            .or(temp_ledger.as_deref())
+            .expect("one of the ledger directories is set");
+
+        let child = Command::new(&config.validator_path)
+            .args(config.args(ledger_dir))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                GmSimulatorError::TestValidator(format!(
+                    "failed to start {}: {}",
+                    config.validator_path.display(),
+                    e
+                ))
+            })?;
+
+        // From here on, dropping the guard tears the validator down
+        let mut validator = Self {
+            child: Some(child),
+            rpc_url: config.rpc_url(),
+            temp_ledger,
+        };
+        validator.wait_until_healthy(config.startup_timeout)?;
+        Ok(validator)
+    }
+
+    /// Attach to a validator that is already running, e.g. one started by CI
+    ///
+    /// # Arguments
+    ///
+    /// * `rpc_url` - The validator's JSON-RPC URL
+    /// * `timeout` - How long to wait for it to report healthy
+    pub fn connect(rpc_url: &str, timeout: Duration) -> Result<Self, GmSimulatorError> {
+        let mut validator = Self {
+            child: None,
+            rpc_url: rpc_url.to_string(),
+            temp_ledger: None,
+        };
+        validator.wait_until_healthy(timeout)?;
+        Ok(validator)
+    }
+
+    /// The validator's JSON-RPC URL
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    /// Simulate a bundle against this validator with `simulate_as_bundle_with_options`
+    pub fn simulate_as_bundle(
+        &self,
+        transactions: Vec<Transaction>,
+        trade_info: &GmTradeInfo,
+        options: &SimulateOptions,
+    ) -> Result<BundleSimulationResult, GmSimulatorError> {
+        simulate_as_bundle_with_options(transactions, trade_info, &self.rpc_url, options)
+    }
+
+    /// Poll `getHealth` until it returns "ok", the validator exits, or `timeout` passes
+    fn wait_until_healthy(&mut self, timeout: Duration) -> Result<(), GmSimulatorError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(child) = &mut self.child {
+                if let Ok(Some(status)) = child.try_wait() {
+                    return Err(GmSimulatorError::TestValidator(format!(
+                        "validator exited early ({})",
+                        status
+                    )));
+                }
+            }
+            if is_healthy(&self.rpc_url) {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(GmSimulatorError::TestValidator(format!(
+                    "{} not healthy after {:?}",
+                    self.rpc_url, timeout
+                )));
+            }
+            thread::sleep(HEALTH_POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        if let Some(ledger) = &self.temp_ledger {
+            let _ = std::fs::remove_dir_all(ledger);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validator_args() {
+        let mint = Pubkey::new_unique();
+        let config = TestValidatorConfig {
+            accounts: vec![mint],
+            rpc_port: 18899,
+            ..TestValidatorConfig::default()
+        };
+
+        let args = config.args(Path::new("/tmp/ledger"));
+        let value = |flag: &str| -> Vec<&str> {
+            args.windows(2)
+                .filter(|pair| pair[0] == flag)
+                .map(|pair| pair[1].as_str())
+                .collect()
+        };
+
+        assert_eq!(value("--ledger"), vec!["/tmp/ledger"]);
+        assert_eq!(value("--rpc-port"), vec!["18899"]);
+        assert_eq!(value("--url"), vec!["https://api.mainnet-beta.solana.com"]);
+        assert_eq!(
+            value("--clone-upgradeable-program"),
+            vec![
+                ondo_gm_program_id().to_string(),
+                jupiter_order_engine_program_id().to_string(),
+                token_2022_program_id().to_string(),
+            ]
+        );
+        assert_eq!(value("--clone"), vec![mint.to_string()]);
+        assert_eq!(config.rpc_url(), "http://127.0.0.1:18899");
+    }
+
+    #[test]
+    fn test_validator_start_failures() {
+        let config = TestValidatorConfig {
+            validator_path: PathBuf::from("/nonexistent/solana-test-validator"),
+            ..TestValidatorConfig::default()
+        };
+        assert!(matches!(
+            TestValidator::start(&config),
+            Err(GmSimulatorError::TestValidator(_))
+        ));
+
+        // Nothing listens on port 1
+        assert!(matches!(
+            TestValidator::connect("http://127.0.0.1:1", Duration::ZERO),
+            Err(GmSimulatorError::TestValidator(_))
+        ));
+    }
+
+    #[test]
+    #[ignore] // Needs solana-test-validator on PATH and network access for cloning
+    fn test_validator_clones_programs() {
+        let config = TestValidatorConfig {
+            rpc_port: 18999,
+            ..TestValidatorConfig::default()
+        };
+        let validator = TestValidator::start(&config).unwrap();
+        assert!(is_healthy(validator.rpc_url()));
+
+        // Dropping the guard shuts the validator down
+        drop(validator);
+        assert!(!is_healthy(&config.rpc_url()));
+    }
+}
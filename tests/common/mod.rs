@@ -0,0 +1,78 @@
+//! Fixtures shared by the integration test binaries.
+//!
+//! Each binary compiles this module separately and uses only part of it.
+#![allow(dead_code)]
+
+use gm_solana_simulator::{
+    instruction_discriminator, jupiter_order_engine_program_id, token_2022_program_id, usdc_mint,
+};
+use solana_sdk::{
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+use std::str::FromStr;
+
+/// An authorized mainnet solver
+pub fn solver() -> Pubkey {
+    Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap()
+}
+
+/// The AAPLon GM token mint
+pub fn gm_mint() -> Pubkey {
+    Pubkey::from_str("123mYEnRLM2LLYsJW3K6oyYh8uP1fngj732iG638ondo").unwrap()
+}
+
+/// Jupiter `fill` data: discriminator, the two amounts and an `expire_at` of 2024-01-01
+pub fn fill_data(input_amount: u64, output_amount: u64) -> Vec<u8> {
+    let mut data = instruction_discriminator("fill").to_vec();
+    data.extend_from_slice(&input_amount.to_le_bytes());
+    data.extend_from_slice(&output_amount.to_le_bytes());
+    data.extend_from_slice(&1704067200i64.to_le_bytes());
+    data
+}
+
+/// An unsigned transaction whose only instruction is a fill on `program_id`
+///
+/// The taker and the four token accounts are fresh keys; `input` and `output` are the
+/// `(mint, token program)` the taker sends and receives.
+pub fn fill_transaction_with(
+    program_id: Pubkey,
+    maker: Pubkey,
+    input: (Pubkey, Pubkey),
+    output: (Pubkey, Pubkey),
+    data: Vec<u8>,
+) -> Transaction {
+    let taker = Pubkey::new_unique();
+    let ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(taker, true),
+            AccountMeta::new(maker, true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(input.0, false),
+            AccountMeta::new_readonly(input.1, false),
+            AccountMeta::new_readonly(output.0, false),
+            AccountMeta::new_readonly(output.1, false),
+        ],
+        data,
+    };
+    let message = Message::new_with_blockhash(&[ix], Some(&taker), &Hash::new_unique());
+    Transaction::new_unsigned(message)
+}
+
+/// A Jupiter fill from `maker` of 200 USDC for 1.5 AAPLon
+pub fn fill_transaction(maker: Pubkey) -> Transaction {
+    fill_transaction_with(
+        jupiter_order_engine_program_id(),
+        maker,
+        (usdc_mint(), spl_token::id()),
+        (gm_mint(), token_2022_program_id()),
+        fill_data(200_000_000, 1_500_000_000),
+    )
+}
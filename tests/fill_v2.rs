@@ -0,0 +1,38 @@
+//! Integration test for detecting Jupiter `fill_v2` with `accept_fill_v2`.
+//!
+//! The global config can only be set once per process, so this lives in its own
+//! test binary.
+
+mod common;
+
+use common::{fill_data, fill_transaction_with, gm_mint, solver};
+use gm_solana_simulator::{
+    check_gm_trade, jupiter_order_engine_program_id, parser::FILL_V2, set_global_config,
+    token_2022_program_id, usdc_mint, GmConfig,
+};
+
+#[test]
+fn test_fill_v2_detection() {
+    set_global_config(GmConfig {
+        accept_fill_v2: true,
+        ..GmConfig::mainnet()
+    })
+    .unwrap();
+
+    let mut data = fill_data(200_000_000, 1_500_000_000);
+    data[..8].copy_from_slice(&FILL_V2.discriminator);
+    let transaction = fill_transaction_with(
+        jupiter_order_engine_program_id(),
+        solver(),
+        (usdc_mint(), spl_token::id()),
+        (gm_mint(), token_2022_program_id()),
+        data,
+    );
+
+    let result = check_gm_trade(&transaction).unwrap();
+    assert!(result.use_gm_bundle_sim);
+    assert_eq!(
+        result.trade_info.unwrap().gm_token_amount,
+        1_500_000_000.into()
+    );
+}
@@ -0,0 +1,73 @@
+//! Integration test for detecting fills from a registered second venue.
+//!
+//! Venues are registered through the global config, which can only be set once per
+//! process, so this lives in its own test binary.
+
+mod common;
+
+use common::{fill_data, fill_transaction_with, gm_mint, solver};
+use gm_solana_simulator::{
+    check_gm_trade, instruction_discriminator, jupiter_order_engine_program_id,
+    parser::{FillLayout, FillVenue},
+    set_global_config, token_2022_program_id, usdc_mint, GmConfig,
+};
+use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+
+/// A synthetic venue whose `swap` takes `expire_at` first, then the two amounts
+const SWAP: FillLayout = FillLayout {
+    name: "swap",
+    discriminator: [0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8],
+    input_amount_offset: 16,
+    output_amount_offset: 24,
+    expire_at_offset: 8,
+};
+
+/// A fill of USDC for AAPLon from an authorized solver on `program_id`
+fn fill_transaction(program_id: Pubkey, data: Vec<u8>) -> Transaction {
+    fill_transaction_with(
+        program_id,
+        solver(),
+        (usdc_mint(), spl_token::id()),
+        (gm_mint(), token_2022_program_id()),
+        data,
+    )
+}
+
+#[test]
+fn test_second_venue_detection() {
+    assert_eq!(SWAP.discriminator, instruction_discriminator("swap"));
+
+    let venue = Pubkey::new_unique();
+    set_global_config(GmConfig {
+        extra_fill_venues: vec![FillVenue {
+            program_id: venue,
+            layout: SWAP,
+        }],
+        ..GmConfig::mainnet()
+    })
+    .unwrap();
+
+    let mut swap_data = SWAP.discriminator.to_vec();
+    swap_data.extend_from_slice(&1704067200i64.to_le_bytes());
+    swap_data.extend_from_slice(&200_000_000u64.to_le_bytes());
+    swap_data.extend_from_slice(&1_500_000_000u64.to_le_bytes());
+
+    // The second venue's fill is detected with its own argument layout
+    let result = check_gm_trade(&fill_transaction(venue, swap_data.clone())).unwrap();
+    assert!(result.use_gm_bundle_sim);
+    let trade_info = result.trade_info.unwrap();
+    assert_eq!(trade_info.gm_token_amount, 1_500_000_000.into());
+    assert_eq!(trade_info.expire_at, 1704067200);
+
+    // The venue's discriminator means nothing to another program
+    let result = check_gm_trade(&fill_transaction(Pubkey::new_unique(), swap_data)).unwrap();
+    assert!(!result.use_gm_bundle_sim);
+
+    // Jupiter is still detected
+    let result = check_gm_trade(&fill_transaction(
+        jupiter_order_engine_program_id(),
+        fill_data(200_000_000, 1_500_000_000),
+    ))
+    .unwrap();
+    assert!(result.use_gm_bundle_sim);
+}
@@ -0,0 +1,41 @@
+//! Integration test for `set_global_config`.
+//!
+//! The global config can only be set once per process, so this lives in its own
+//! test binary rather than alongside the unit tests.
+
+mod common;
+
+use common::fill_transaction;
+use gm_solana_simulator::{
+    check_gm_trade, is_authorized_solver, set_global_config, GmConfig, GmSimulatorError,
+};
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn test_set_global_config_overrides_solvers() {
+    let solver = Pubkey::new_unique();
+    let config = GmConfig {
+        authorized_solvers: vec![solver],
+        ..GmConfig::mainnet()
+    };
+
+    set_global_config(config.clone()).unwrap();
+
+    assert!(is_authorized_solver(&solver));
+    let result = check_gm_trade(&fill_transaction(solver)).unwrap();
+    assert!(result.use_gm_bundle_sim);
+    assert_eq!(result.trade_info.unwrap().maker, solver);
+
+    // Mainnet solvers are no longer authorized
+    let mainnet_solver = GmConfig::mainnet().authorized_solvers[0];
+    assert_eq!(
+        check_gm_trade(&fill_transaction(mainnet_solver)),
+        Err(GmSimulatorError::UnauthorizedMaker(mainnet_solver))
+    );
+
+    // The config can only be set once
+    assert_eq!(
+        set_global_config(config),
+        Err(GmSimulatorError::ConfigAlreadySet)
+    );
+}
@@ -129,7 +129,7 @@ fn test_simulate_mock_mint_from_fill() {
 /// Test that we can construct mock mint instructions from trade info
 #[test]
 fn test_build_mock_mint_instruction() {
-    use gm_solana_simulator::GmTradeInfo;
+    use gm_solana_simulator::{GmTradeInfo, TradeDirection};
     use solana_sdk::pubkey::Pubkey;
 
     let solver = Pubkey::from_str("DSqMPMsMAbEJVNuPKv1ZFdzt6YvJaDPDddfeW7ajtqds").unwrap();
@@ -141,9 +141,14 @@ fn test_build_mock_mint_instruction() {
         taker: Pubkey::new_unique(),
         gm_token_mint: aapl,
         gm_token_symbol: "AAPLon".to_string(),
-        gm_token_amount: 1_500_000_000, // 1.5 AAPL (9 decimals)
+        gm_token_amount: 1_500_000_000.into(), // 1.5 AAPL (9 decimals)
+        input_amount: 200_000_000.into(),      // 200 USDC (6 decimals)
         maker_output_account: maker_output_ata,
         expire_at: 1704067200,
+        input_token_program: gm_solana_simulator::constants::spl_token_program_id(),
+        output_token_program: gm_solana_simulator::token_2022_program_id(),
+        direction: TradeDirection::Buy,
+        quote_mint: gm_solana_simulator::constants::usdc_mint(),
     };
 
     let instruction = build_mock_mint_instruction(&trade_info);
@@ -224,7 +229,8 @@ fn test_check_gm_trade_and_build_mock_mint() {
     let trade_info = result.trade_info.unwrap();
     assert_eq!(trade_info.maker, solver);
     assert_eq!(trade_info.gm_token_mint, aapl);
-    assert_eq!(trade_info.gm_token_amount, output_amount);
+    assert_eq!(trade_info.gm_token_amount, output_amount.into());
+    assert_eq!(trade_info.input_amount, input_amount.into());
     assert_eq!(trade_info.gm_token_symbol, "AAPLon");
 
     // Build mock mint transaction
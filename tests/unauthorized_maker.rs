@@ -0,0 +1,42 @@
+//! Integration test for `GmConfig::unauthorized_maker_is_error`.
+//!
+//! The global config can only be set once per process, so this lives in its own
+//! test binary rather than alongside the unit tests.
+
+mod common;
+
+use common::fill_transaction;
+use gm_solana_simulator::{
+    check_gm_trade, check_gm_trade_detailed, maybe_build_mock_mint, set_global_config,
+    DetectionOutcome, GmConfig, NoBundleReason,
+};
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+
+#[test]
+fn test_unauthorized_maker_is_not_an_error_when_disabled() {
+    set_global_config(GmConfig {
+        unauthorized_maker_is_error: false,
+        ..GmConfig::mainnet()
+    })
+    .unwrap();
+
+    let tx = fill_transaction(Pubkey::new_unique());
+
+    let result = check_gm_trade(&tx).unwrap();
+    assert!(!result.use_gm_bundle_sim);
+    assert!(result.trade_info.is_none());
+    assert_eq!(result.reason, Some(NoBundleReason::UnauthorizedMaker));
+    assert_eq!(
+        check_gm_trade_detailed(&tx.message),
+        Ok(DetectionOutcome::FillButNotGm)
+    );
+    assert_eq!(maybe_build_mock_mint(&tx, Hash::new_unique()), Ok(None));
+
+    // Authorized solvers are unaffected
+    let solver = GmConfig::mainnet().authorized_solvers[0];
+    assert!(
+        check_gm_trade(&fill_transaction(solver))
+            .unwrap()
+            .use_gm_bundle_sim
+    );
+}
@@ -0,0 +1,49 @@
+//! Integration test for USDON as a GM trade's stable leg.
+//!
+//! The USDON mint is set through the global config, which can only be set once per
+//! process, so this lives in its own test binary.
+
+mod common;
+
+use common::{fill_data, fill_transaction_with, gm_mint, solver};
+use gm_solana_simulator::{
+    check_gm_trade, is_stable_mint, jupiter_order_engine_program_id, set_global_config,
+    taker_usdon_ata, token_2022_program_id, usdon_decimals, usdon_mint, GmConfig, NoBundleReason,
+};
+use solana_sdk::pubkey::Pubkey;
+
+/// A placeholder USDON mint; no address is built into the crate
+const USDON_MINT: Pubkey = Pubkey::new_from_array([7; 32]);
+
+#[test]
+fn test_usdon_stable_leg() {
+    let usdon = USDON_MINT;
+    set_global_config(GmConfig {
+        usdon_mint: Some(usdon),
+        ..GmConfig::mainnet()
+    })
+    .unwrap();
+
+    assert_eq!(usdon_mint(), Some(usdon));
+    assert_eq!(usdon_decimals(), 6);
+    assert!(is_stable_mint(&usdon));
+
+    let owner = solver();
+    assert_eq!(
+        taker_usdon_ata(&owner).unwrap().to_string(),
+        "4jwfVMR6AhMABUKYRTson8J8qs8PHQqQQpSn6iiqu3R2"
+    );
+
+    // A taker selling AAPLon for USDON is a SELL, like selling it for USDC
+    let tx = fill_transaction_with(
+        jupiter_order_engine_program_id(),
+        owner,
+        (gm_mint(), token_2022_program_id()),
+        (usdon, token_2022_program_id()),
+        fill_data(1_500_000_000, 200_000_000),
+    );
+    let result = check_gm_trade(&tx).unwrap();
+
+    assert!(!result.use_gm_bundle_sim);
+    assert_eq!(result.reason, Some(NoBundleReason::SellDirection));
+}
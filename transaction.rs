@@ -0,0 +1,374 @@
+//! Transaction utilities for preparing bundle simulation inputs.
+//!
+//! Simulation inputs often need to be decoded from wallet payloads or rebuilt
+//! before sending (e.g. with a fresh blockhash). The rebuild helpers return
+//! unsigned copies without touching the originals.
+
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    hash::Hash,
+    signature::Signature,
+    transaction::{Transaction, VersionedTransaction},
+};
+use std::path::Path;
+
+use crate::types::GmSimulatorError;
+
+/// Decode a serialized transaction from a base58 or base64 string.
+///
+/// The encoding is auto-detected: strings that decode as base58 and deserialize
+/// are treated as base58, otherwise base64 is tried. Legacy transactions are
+/// returned as `VersionedTransaction`s with a legacy message.
+///
+/// # Arguments
+///
+/// * `input` - The encoded transaction; surrounding whitespace is ignored
+pub fn decode_transaction(input: &str) -> Result<VersionedTransaction, GmSimulatorError> {
+    let input = input.trim();
+
+    if let Ok(bytes) = bs58::decode(input).into_vec() {
+        if let Ok(tx) = bincode::deserialize::<VersionedTransaction>(&bytes) {
+            return Ok(tx);
+        }
+    }
+
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(input)
+        .map_err(|e| {
+            GmSimulatorError::TransactionDecodeError(format!("not base58 or base64: {}", e))
+        })?;
+
+    bincode::deserialize::<VersionedTransaction>(&bytes)
+        .map_err(|e| GmSimulatorError::TransactionDecodeError(e.to_string()))
+}
+
+/// Load a transaction from a file, e.g. one dumped with the `solana` CLI.
+///
+/// The file holds a single base58 or base64 encoded transaction, decoded with
+/// `decode_transaction`, so the encoding is auto-detected and surrounding whitespace
+/// (such as a trailing newline) is ignored.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file
+pub fn load_transaction_from_file(
+    path: impl AsRef<Path>,
+) -> Result<VersionedTransaction, GmSimulatorError> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        GmSimulatorError::TransactionDecodeError(format!(
+            "failed to read {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    decode_transaction(&contents)
+}
+
+/// Encode a bundle's transactions as base64 strings.
+///
+/// Produces the same `encodedTransactions` that `simulate_as_bundle` sends, so a
+/// bundle can be stored or submitted with another tool.
+///
+/// # Arguments
+///
+/// * `transactions` - The bundle, typically `[mock_mint_tx, fill_tx]`
+pub fn encode_bundle(transactions: &[Transaction]) -> Result<Vec<String>, GmSimulatorError> {
+    transactions
+        .iter()
+        .map(|tx| {
+            bincode::serialize(tx)
+                .map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+                .map_err(|e| GmSimulatorError::SerializationFailed(e.to_string()))
+        })
+        .collect()
+}
+
+/// Estimate a transaction's size on the wire, in bytes.
+///
+/// The length of its bincode serialization, which is what must fit in a packet
+/// (`PACKET_DATA_SIZE`, 1232 bytes). Signature slots are counted whether or not they
+/// are filled, so an unsigned transaction estimates the same as its signed copy.
+///
+/// # Arguments
+///
+/// * `tx` - The transaction to measure
+///
+/// # Returns
+///
+/// * `Ok(size)` in bytes
+/// * `Err(SerializationFailed)` if the transaction can't be serialized
+pub fn estimate_serialized_size(tx: &Transaction) -> Result<usize, GmSimulatorError> {
+    bincode::serialized_size(tx)
+        .map(|size| size as usize)
+        .map_err(|e| GmSimulatorError::SerializationFailed(e.to_string()))
+}
+
+/// Estimate a bundle's total size, in bytes.
+///
+/// The sum of `estimate_serialized_size` over the bundle. Each transaction must fit
+/// in a packet on its own, so check them individually too before deciding whether
+/// to split instructions out or move accounts into a lookup table.
+///
+/// # Arguments
+///
+/// * `transactions` - The bundle, typically `[mock_mint_tx, fill_tx]`
+///
+/// # Returns
+///
+/// * `Ok(size)` in bytes
+/// * `Err(SerializationFailed)` if a transaction can't be serialized
+pub fn estimate_bundle_size(transactions: &[Transaction]) -> Result<usize, GmSimulatorError> {
+    transactions.iter().map(estimate_serialized_size).sum()
+}
+
+/// Compute a stable key for a bundle, e.g. to cache or dedupe simulation results.
+///
+/// SHA-256 over each transaction's serialized message with the recent blockhash
+/// zeroed. Signatures and blockhashes are left out, so refreshing a bundle's blockhash
+/// (see `with_blockhash`) or re-signing it keeps the same fingerprint, while any change
+/// to its instructions, accounts or transaction order produces a different one.
+///
+/// # Arguments
+///
+/// * `transactions` - The bundle, typically `[mock_mint_tx, fill_tx]`
+pub fn bundle_fingerprint(transactions: &[Transaction]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for tx in transactions {
+        let mut message = tx.message.clone();
+        message.recent_blockhash = Hash::default();
+        let bytes = message.serialize();
+        // Length-prefix each message so the boundaries between them are unambiguous
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    hasher.finalize().into()
+}
+
+/// Rebuild a transaction with a different recent blockhash.
+///
+/// The message is cloned with `recent_blockhash` replaced, and the returned
+/// transaction is unsigned (all signature slots are default), since any existing
+/// signatures would no longer be valid for the new message.
+///
+/// # Arguments
+///
+/// * `tx` - The transaction to rebuild
+/// * `blockhash` - The blockhash to use
+pub fn with_blockhash(tx: &Transaction, blockhash: Hash) -> Transaction {
+    let mut message = tx.message.clone();
+    message.recent_blockhash = blockhash;
+    Transaction::new_unsigned(message)
+}
+
+/// Rebuild a versioned transaction with a different recent blockhash.
+///
+/// Same as `with_blockhash` but for legacy or v0 `VersionedTransaction`s. The returned
+/// transaction has one default signature per required signer.
+pub fn with_blockhash_versioned(
+    tx: &VersionedTransaction,
+    blockhash: Hash,
+) -> VersionedTransaction {
+    let mut message = tx.message.clone();
+    message.set_recent_blockhash(blockhash);
+    let num_signatures = message.header().num_required_signatures as usize;
+
+    VersionedTransaction {
+        signatures: vec![Signature::default(); num_signatures],
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        message::{v0, Message, VersionedMessage},
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+    };
+
+    fn sample_instruction(payer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts: vec![AccountMeta::new(*payer, true)],
+            data: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn test_decode_transaction() {
+        let payer = Keypair::new();
+        let message = Message::new(
+            &[sample_instruction(&payer.pubkey())],
+            Some(&payer.pubkey()),
+        );
+        let tx = Transaction::new(&[&payer], message, Hash::new_unique());
+        let bytes = bincode::serialize(&tx).unwrap();
+
+        let from_base58 = decode_transaction(&bs58::encode(&bytes).into_string()).unwrap();
+        let from_base64 =
+            decode_transaction(&base64::engine::general_purpose::STANDARD.encode(&bytes)).unwrap();
+
+        assert_eq!(from_base58, from_base64);
+        assert_eq!(from_base58.signatures, tx.signatures);
+        assert_eq!(
+            from_base58.message,
+            VersionedMessage::Legacy(tx.message.clone())
+        );
+
+        assert!(matches!(
+            decode_transaction("not a transaction!"),
+            Err(GmSimulatorError::TransactionDecodeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_transaction_from_file() {
+        let payer = Keypair::new();
+        let message = Message::new(
+            &[sample_instruction(&payer.pubkey())],
+            Some(&payer.pubkey()),
+        );
+        let tx = Transaction::new(&[&payer], message, Hash::new_unique());
+        let encoded =
+            base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&tx).unwrap());
+
+        let path = std::env::temp_dir().join(format!("gm-sim-tx-{}", Pubkey::new_unique()));
+        std::fs::write(&path, format!("{}\n", encoded)).unwrap();
+        let loaded = load_transaction_from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.signatures, tx.signatures);
+        assert_eq!(loaded.message, VersionedMessage::Legacy(tx.message));
+
+        assert!(matches!(
+            load_transaction_from_file(&path),
+            Err(GmSimulatorError::TransactionDecodeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_encode_bundle() {
+        let payer = Keypair::new();
+        let transactions: Vec<Transaction> = (0..2)
+            .map(|_| {
+                let message = Message::new(
+                    &[sample_instruction(&payer.pubkey())],
+                    Some(&payer.pubkey()),
+                );
+                Transaction::new(&[&payer], message, Hash::new_unique())
+            })
+            .collect();
+
+        let encoded = encode_bundle(&transactions).unwrap();
+
+        assert_eq!(encoded.len(), 2);
+        for (encoded, tx) in encoded.iter().zip(&transactions) {
+            let decoded = decode_transaction(encoded).unwrap();
+            assert_eq!(
+                decoded.message,
+                VersionedMessage::Legacy(tx.message.clone())
+            );
+        }
+        assert_eq!(encode_bundle(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_estimate_bundle_size() {
+        let payer = Keypair::new();
+        let message = Message::new(
+            &[sample_instruction(&payer.pubkey())],
+            Some(&payer.pubkey()),
+        );
+        let signed = Transaction::new(&[&payer], message, Hash::new_unique());
+        let unsigned = with_blockhash(&signed, Hash::new_unique());
+
+        let size = estimate_serialized_size(&signed).unwrap();
+        assert_eq!(size, bincode::serialize(&signed).unwrap().len());
+        assert_eq!(estimate_serialized_size(&unsigned), Ok(size));
+        assert!(size <= solana_sdk::packet::PACKET_DATA_SIZE);
+
+        assert_eq!(estimate_bundle_size(&[signed, unsigned]), Ok(2 * size));
+        assert_eq!(estimate_bundle_size(&[]), Ok(0));
+    }
+
+    #[test]
+    fn test_bundle_fingerprint() {
+        let payer = Keypair::new();
+        let ix = sample_instruction(&payer.pubkey());
+        let bundle = |blockhash: Hash, data: u8| -> Vec<Transaction> {
+            let mut ix = ix.clone();
+            ix.data.push(data);
+            let message = Message::new(&[ix], Some(&payer.pubkey()));
+            let tx = Transaction::new(&[&payer], message, blockhash);
+            vec![tx.clone(), tx]
+        };
+
+        let fingerprint = bundle_fingerprint(&bundle(Hash::new_unique(), 0));
+
+        // Same bundle, fresh blockhash (and so different signatures)
+        assert_eq!(
+            bundle_fingerprint(&bundle(Hash::new_unique(), 0)),
+            fingerprint
+        );
+        // Different instruction data
+        assert_ne!(
+            bundle_fingerprint(&bundle(Hash::new_unique(), 1)),
+            fingerprint
+        );
+        // Only part of the bundle
+        assert_ne!(
+            bundle_fingerprint(&bundle(Hash::new_unique(), 0)[..1]),
+            fingerprint
+        );
+    }
+
+    #[test]
+    fn test_with_blockhash() {
+        let payer = Keypair::new();
+        let original_blockhash = Hash::new_unique();
+        let message = Message::new_with_blockhash(
+            &[sample_instruction(&payer.pubkey())],
+            Some(&payer.pubkey()),
+            &original_blockhash,
+        );
+        let signed = Transaction::new(&[&payer], message, original_blockhash);
+
+        let fresh_blockhash = Hash::new_unique();
+        let rebuilt = with_blockhash(&signed, fresh_blockhash);
+
+        assert_eq!(rebuilt.message.recent_blockhash, fresh_blockhash);
+        assert_eq!(rebuilt.message.instructions, signed.message.instructions);
+        assert_eq!(rebuilt.signatures, vec![Signature::default()]);
+        // The original is untouched
+        assert_eq!(signed.message.recent_blockhash, original_blockhash);
+    }
+
+    #[test]
+    fn test_with_blockhash_versioned() {
+        let payer = Keypair::new();
+        let message = v0::Message::try_compile(
+            &payer.pubkey(),
+            &[sample_instruction(&payer.pubkey())],
+            &[],
+            Hash::new_unique(),
+        )
+        .unwrap();
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            message: VersionedMessage::V0(message),
+        };
+
+        let fresh_blockhash = Hash::new_unique();
+        let rebuilt = with_blockhash_versioned(&tx, fresh_blockhash);
+
+        assert_eq!(*rebuilt.message.recent_blockhash(), fresh_blockhash);
+        assert_eq!(rebuilt.signatures, vec![Signature::default()]);
+        assert!(matches!(rebuilt.message, VersionedMessage::V0(_)));
+    }
+}
@@ -1,8 +1,12 @@
 //! Data types for the Ondo GM transaction simulator.
 
-use solana_sdk::pubkey::Pubkey;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{commitment_config::CommitmentLevel, pubkey::Pubkey};
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::{constants::DETECTION_LATENCY_BUCKETS, parser::FillVenue};
+
 /// Error types for the GM simulator
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum GmSimulatorError {
@@ -29,34 +33,326 @@ pub enum GmSimulatorError {
 
     #[error("Transaction has no instructions")]
     EmptyTransaction,
+
+    #[error("Transaction has {count} instructions, more than the {max} checked")]
+    TooManyInstructions { count: usize, max: usize },
+
+    #[error("Taker and maker are the same account: {0}")]
+    TakerMakerSame(Pubkey),
+
+    #[error("Failed to decode transaction: {0}")]
+    TransactionDecodeError(String),
+
+    #[error("Failed to serialize transaction: {0}")]
+    SerializationFailed(String),
+
+    #[error("Fill output amount is zero")]
+    ZeroAmount,
+
+    #[error("Invalid bundle: {0}")]
+    InvalidBundle(String),
+
+    #[error("Fill settles {filled}, less than the quoted {quoted}")]
+    PartialFill { quoted: u64, filled: u64 },
+
+    #[error("Global config is already set or in use")]
+    ConfigAlreadySet,
+
+    #[error("Transaction is not a GM BUY: {0}")]
+    NotGmBuy(String),
+
+    #[error("Admin minter {0} is a party to the trade")]
+    AdminMinterInTrade(Pubkey),
+
+    #[error("Invalid trade info: {0}")]
+    InvalidTradeInfo(String),
+
+    #[error("Invalid IDL: {0}")]
+    InvalidIdl(String),
+
+    #[error("Test validator failed: {0}")]
+    TestValidator(String),
+
+    #[error("RPC unreachable: {0}")]
+    RpcUnreachable(String),
+
+    #[error("RPC returned HTTP status {0}")]
+    RpcHttpStatus(u16),
+
+    #[error("Taker received {received} of {mint}, less than the quoted {quoted}")]
+    UnderDelivered {
+        mint: Pubkey,
+        quoted: u64,
+        received: i128,
+    },
+}
+
+/// Which way a GM trade goes, from the taker's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeDirection {
+    /// The taker receives a GM token (the only direction that needs a mock mint)
+    Buy,
+    /// The taker sends a GM token and receives a stablecoin (the quote mint)
+    Sell,
+}
+
+impl TradeDirection {
+    /// `"BUY"` or `"SELL"`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Buy => "BUY",
+            Self::Sell => "SELL",
+        }
+    }
+}
+
+/// A token amount in base units, as stored on chain
+///
+/// Keeps raw amounts from being mixed up with UI amounts: convert explicitly with
+/// `to_ui` and `from_ui` given the mint's decimals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawAmount(pub u64);
+
+impl RawAmount {
+    /// The amount in whole tokens, e.g. `1.5` for 1,500,000,000 base units at 9 decimals
+    pub fn to_ui(self, decimals: u8) -> f64 {
+        self.0 as f64 / 10f64.powi(decimals as i32)
+    }
+
+    /// The raw amount for `ui_amount` whole tokens, rounded to the nearest base unit
+    ///
+    /// Negative and NaN amounts give zero; amounts past `u64::MAX` base units saturate.
+    pub fn from_ui(ui_amount: f64, decimals: u8) -> Self {
+        Self((ui_amount * 10f64.powi(decimals as i32)).round() as u64)
+    }
+}
+
+impl From<u64> for RawAmount {
+    fn from(amount: u64) -> Self {
+        Self(amount)
+    }
+}
+
+impl From<RawAmount> for u64 {
+    fn from(amount: RawAmount) -> Self {
+        amount.0
+    }
+}
+
+impl std::fmt::Display for RawAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
 }
 
 /// Information extracted from a Jupiter RFQ fill instruction
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GmTradeInfo {
     /// The maker (market maker/solver) pubkey
     pub maker: Pubkey,
     /// The taker (user) pubkey
     pub taker: Pubkey,
-    /// The GM token mint that the taker is receiving
+    /// The GM token mint that the taker is receiving (sending for a SELL)
     pub gm_token_mint: Pubkey,
     /// The GM token symbol
     pub gm_token_symbol: String,
-    /// Amount of GM tokens the taker will receive (in base units, 9 decimals)
-    pub gm_token_amount: u64,
+    /// Amount of GM tokens the taker will receive (send for a SELL), in base units (9 decimals)
+    pub gm_token_amount: RawAmount,
+    /// Amount the taker sends, in base units of the input mint: the quote mint for a BUY,
+    /// the GM token (equal to `gm_token_amount`) for a SELL
+    pub input_amount: RawAmount,
     /// Maker's output token account (where tokens come from)
     pub maker_output_account: Pubkey,
     /// Unix timestamp when the quote expires
     pub expire_at: i64,
+    /// Token program of the taker's input token (e.g. SPL Token for USDC)
+    pub input_token_program: Pubkey,
+    /// Token program of the taker's output token (Token-2022 for GM tokens)
+    pub output_token_program: Pubkey,
+    /// Whether the taker buys or sells the GM token
+    pub direction: TradeDirection,
+    /// The other leg's mint: what the taker pays with for a BUY (normally a stablecoin
+    /// such as USDC or USDT), or the stablecoin it receives for a SELL
+    pub quote_mint: Pubkey,
+}
+
+impl GmTradeInfo {
+    /// Standardized JSON record of the trade for audit logs
+    ///
+    /// Contains `maker`, `taker`, `mint`, `symbol`, `ui_amount` (exact decimal string),
+    /// `raw_amount`, `direction` and `expire_at` as an ISO-8601 UTC timestamp.
+    pub fn to_receipt(&self) -> serde_json::Value {
+        serde_json::json!({
+            "maker": self.maker.to_string(),
+            "taker": self.taker.to_string(),
+            "mint": self.gm_token_mint.to_string(),
+            "symbol": self.gm_token_symbol,
+            "ui_amount": decimal_string(self.gm_token_amount.0.into(), 9),
+            "raw_amount": self.gm_token_amount.0,
+            "direction": self.direction.as_str(),
+            "expire_at": iso8601_utc(self.expire_at),
+        })
+    }
+
+    /// Whether `maker_output_account` is the maker's canonical ATA for the fill's output
+    ///
+    /// The output is the GM token for a BUY and the quote mint for a SELL, derived with
+    /// `output_token_program`. The mock mint mints into `maker_output_account` whatever
+    /// it is, but a non-canonical account is unusual and worth a look.
+    pub fn maker_output_is_ata(&self) -> bool {
+        let output_mint = match self.direction {
+            TradeDirection::Buy => &self.gm_token_mint,
+            TradeDirection::Sell => &self.quote_mint,
+        };
+        self.maker_output_account
+            == spl_associated_token_account::get_associated_token_address_with_program_id(
+                &self.maker,
+                output_mint,
+                &self.output_token_program,
+            )
+    }
+
+    /// Re-check the invariants detection guarantees, for trade info built by hand
+    ///
+    /// Checks that the maker is an authorized solver, the mint is a GM token whose
+    /// symbol is `gm_token_symbol`, the taker is neither the maker nor the admin minter
+    /// and the amount is nonzero, returning the error detection would have for the
+    /// first violation.
+    pub fn validate(&self) -> Result<(), GmSimulatorError> {
+        use crate::constants::{
+            get_gm_token_symbol, is_admin_minter, is_authorized_solver, is_gm_token,
+        };
+
+        if !is_authorized_solver(&self.maker) {
+            return Err(GmSimulatorError::UnauthorizedMaker(self.maker));
+        }
+        if !is_gm_token(&self.gm_token_mint) {
+            return Err(GmSimulatorError::InvalidTradeInfo(format!(
+                "{} is not a GM token",
+                self.gm_token_mint
+            )));
+        }
+        let symbol = get_gm_token_symbol(&self.gm_token_mint).unwrap_or("GM");
+        if self.gm_token_symbol != symbol {
+            return Err(GmSimulatorError::InvalidTradeInfo(format!(
+                "symbol {} does not match {} for mint {}",
+                self.gm_token_symbol, symbol, self.gm_token_mint
+            )));
+        }
+        if self.taker == self.maker {
+            return Err(GmSimulatorError::TakerMakerSame(self.maker));
+        }
+        if is_admin_minter(&self.taker) {
+            return Err(GmSimulatorError::AdminMinterInTrade(self.taker));
+        }
+        if self.gm_token_amount.0 == 0 {
+            return Err(GmSimulatorError::ZeroAmount);
+        }
+        Ok(())
+    }
+
+    /// The trade as one compact JSON line for append-only JSONL event logs
+    ///
+    /// The `to_receipt` fields plus `schema_version` (`TRADE_EVENT_SCHEMA_VERSION`),
+    /// with keys in sorted order. The string has no trailing newline.
+    pub fn to_jsonl_line(&self) -> String {
+        let mut record = self.to_receipt();
+        record["schema_version"] = crate::constants::TRADE_EVENT_SCHEMA_VERSION.into();
+        record.to_string()
+    }
+}
+
+/// Why a message was or wasn't treated as a GM trade
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetectionOutcome {
+    /// The message has no Jupiter Order Engine fill instruction
+    NoFillFound,
+    /// The message has a fill, but it is neither a GM BUY nor a GM SELL
+    FillButNotGm,
+    /// The fill is a GM BUY
+    GmTrade(Box<GmTradeInfo>),
+    /// The fill is a GM SELL (GM token for a stablecoin), which needs no mock mint
+    GmSell(Box<GmTradeInfo>),
+}
+
+/// Raw arguments of a Jupiter fill instruction, decoded without GM-specific checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FillArgs {
+    /// Amount the taker sends (in the input token's base units)
+    pub input_amount: RawAmount,
+    /// Amount the taker receives (in the output token's base units)
+    pub output_amount: RawAmount,
+    /// Unix timestamp when the quote expires
+    pub expire_at: i64,
+    /// Instruction data past the known arguments (empty for today's layouts)
+    pub raw_tail: Vec<u8>,
+}
+
+impl FillArgs {
+    /// Read a little-endian u64 at `offset` into `raw_tail`
+    ///
+    /// No trailing argument (e.g. a min-out or slippage) is confirmed yet; this is
+    /// for inspecting fills that carry one until it gets a typed accessor.
+    pub fn tail_u64(&self, offset: usize) -> Option<u64> {
+        let bytes = self.raw_tail.get(offset..offset.checked_add(8)?)?;
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    }
+}
+
+/// An instruction account resolved against its message, see `resolve_instruction_accounts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedAccount {
+    /// The account address
+    pub pubkey: Pubkey,
+    /// Whether the message requires this account's signature
+    pub is_signer: bool,
+    /// Whether the account is writable in the message
+    pub is_writable: bool,
 }
 
 /// Result of checking whether a transaction is a GM trade
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GmCheckResult {
     /// Whether this transaction should use GM bundle simulation
     pub use_gm_bundle_sim: bool,
     /// Trade info if this is a GM trade
     pub trade_info: Option<GmTradeInfo>,
+    /// The transaction uses a non-idempotent `createAssociatedTokenAccount`, which fails
+    /// in bundle simulation once the mock mint has created that ATA
+    pub nonidempotent_ata_create: bool,
+    /// The fill's maker output account is not the maker's ATA for the GM token (see
+    /// `GmTradeInfo::maker_output_is_ata`)
+    pub noncanonical_maker_output: bool,
+    /// Why bundle simulation isn't needed, if detection determined it
+    pub reason: Option<NoBundleReason>,
+}
+
+/// Why a transaction doesn't need GM bundle simulation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoBundleReason {
+    /// The transaction has no Jupiter Order Engine fill instruction
+    NotAFill,
+    /// The fill sells a GM token for another token; the solver already holds the output
+    SellDirection,
+    /// The fill's output is not a GM token, and its input isn't either
+    OutputNotGm,
+    /// The maker is not an authorized solver (with `unauthorized_maker_is_error` off)
+    UnauthorizedMaker,
+    /// The fill buys a GM token with something other than an accepted stablecoin
+    QuoteNotStable,
+}
+
+impl NoBundleReason {
+    /// Short human-readable explanation
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::NotAFill => "no Jupiter fill instruction",
+            Self::SellDirection => "SELL, solver already holds the output token",
+            Self::OutputNotGm => "output is not a GM token",
+            Self::UnauthorizedMaker => "maker is not an authorized solver",
+            Self::QuoteNotStable => "GM token is paid for with a non-stablecoin",
+        }
+    }
 }
 
 impl GmCheckResult {
@@ -65,6 +361,17 @@ impl GmCheckResult {
         Self {
             use_gm_bundle_sim: false,
             trade_info: None,
+            nonidempotent_ata_create: false,
+            noncanonical_maker_output: false,
+            reason: None,
+        }
+    }
+
+    /// Create a result indicating this is not a GM trade for a known reason
+    pub fn not_gm_trade_because(reason: NoBundleReason) -> Self {
+        Self {
+            reason: Some(reason),
+            ..Self::not_gm_trade()
         }
     }
 
@@ -73,12 +380,73 @@ impl GmCheckResult {
         Self {
             use_gm_bundle_sim: true,
             trade_info: Some(info),
+            nonidempotent_ata_create: false,
+            noncanonical_maker_output: false,
+            reason: None,
         }
     }
+
+    /// Compact multi-line report of the detection result for CLI output
+    ///
+    /// Covers the trade direction, whether the maker is authorized, the GM token and
+    /// amount, and whether bundle simulation is needed.
+    pub fn summary_string(&self) -> String {
+        let bundle_sim = if self.use_gm_bundle_sim {
+            "REQUIRED"
+        } else {
+            "NOT REQUIRED"
+        };
+
+        let Some(info) = &self.trade_info else {
+            let reason = self
+                .reason
+                .map_or("SELL or non-GM", |reason| reason.description());
+            return format!(
+                "No GM trade to simulate ({})\n  Bundle simulation: {}",
+                reason, bundle_sim
+            );
+        };
+
+        let authorized = if crate::constants::is_authorized_solver(&info.maker) {
+            "authorized"
+        } else {
+            "NOT authorized"
+        };
+        // A hand-built quote mint that isn't a known stablecoin is shown by address
+        let quote = crate::constants::stable_mint_symbol(&info.quote_mint)
+            .map_or_else(|| info.quote_mint.to_string(), str::to_string);
+        let legs = match info.direction {
+            TradeDirection::Buy => format!("{} -> {}", quote, info.gm_token_symbol),
+            TradeDirection::Sell => format!("{} -> {}", info.gm_token_symbol, quote),
+        };
+        let mut summary = format!(
+            "GM trade: {direction} ({legs})\n  \
+             Maker: {maker} ({authorized})\n  \
+             Taker: {taker}\n  \
+             Token: {symbol} ({mint})\n  \
+             Amount: {whole}.{fraction:09} {symbol} ({amount})\n  \
+             Bundle simulation: {bundle_sim}",
+            direction = info.direction.as_str(),
+            symbol = info.gm_token_symbol,
+            maker = info.maker,
+            taker = info.taker,
+            mint = info.gm_token_mint,
+            whole = info.gm_token_amount.0 / 1_000_000_000,
+            fraction = info.gm_token_amount.0 % 1_000_000_000,
+            amount = info.gm_token_amount,
+        );
+        if self.nonidempotent_ata_create {
+            summary.push_str("\n  Warning: non-idempotent ATA create will fail if the ATA exists");
+        }
+        if self.noncanonical_maker_output {
+            summary.push_str("\n  Warning: maker output account is not the maker's GM ATA");
+        }
+        summary
+    }
 }
 
 /// Represents a balance change for a token account
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BalanceChange {
     /// The token mint address
     pub mint: Pubkey,
@@ -88,11 +456,14 @@ pub struct BalanceChange {
     pub owner: Pubkey,
     /// The token account address
     pub token_account: Pubkey,
-    /// Balance before the transaction (in base units)
+    /// Balance before the transaction (in base units), 0 if not known
     pub pre_balance: u64,
+    /// Whether `pre_balance` was reported; some RPCs only return post-execution accounts
+    pub pre_balance_known: bool,
     /// Balance after the transaction (in base units)
     pub post_balance: u64,
-    /// The change amount (positive = received, negative = sent)
+    /// The change amount (positive = received, negative = sent); just the post-balance
+    /// if the pre-balance is not known
     pub change: i128,
     /// Decimals for display
     pub decimals: u8,
@@ -103,17 +474,1326 @@ impl BalanceChange {
     pub fn change_display(&self) -> f64 {
         self.change as f64 / 10f64.powi(self.decimals as i32)
     }
+
+    /// Get the change as an exact decimal string with `decimals` fractional digits
+    ///
+    /// Uses integer arithmetic, so large amounts don't lose precision the way
+    /// `change_display` does (e.g. `-123456.789123456` for 9 decimals).
+    pub fn change_decimal_string(&self) -> String {
+        decimal_string(self.change, self.decimals)
+    }
+}
+
+/// Format a base-unit amount as an exact decimal string with `decimals` fractional digits
+fn decimal_string(amount: i128, decimals: u8) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", amount.unsigned_abs(), width = decimals + 1);
+    if decimals == 0 {
+        return format!("{}{}", sign, digits);
+    }
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    format!("{}{}.{}", sign, whole, fraction)
+}
+
+/// Format a unix timestamp as an ISO-8601 UTC string (e.g. `2024-01-01T00:00:00Z`)
+fn iso8601_utc(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86_400);
+    let secs = timestamp.rem_euclid(86_400);
+
+    // Civil date from days since 1970-01-01 (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
+
+/// Structured failure reported by a Jito `simulateBundle` call
+///
+/// Bundle-level variants describe why the bundle as a whole could not be
+/// simulated; `TransactionFailed` describes a single transaction that executed
+/// and returned an error.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BundleError {
+    /// The bundle was rejected before or during execution (e.g. lock errors, cost limits)
+    #[error("Bundle rejected: {0}")]
+    BundleRejected(String),
+
+    /// A transaction referenced a blockhash the simulation bank does not know
+    #[error("Blockhash not found")]
+    BlockhashNotFound,
+
+    /// The RPC could not provide a bank to simulate against
+    #[error("Simulation bank unavailable: {0}")]
+    SimulationBankUnavailable(String),
+
+    /// A transaction in the bundle executed and returned an error
+    #[error("Transaction {index} failed: {error}")]
+    TransactionFailed {
+        /// Index of the failing transaction within the bundle
+        index: usize,
+        /// The transaction error as reported by the RPC
+        error: String,
+    },
 }
 
 /// Result of a bundle simulation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BundleSimulationResult {
     /// Whether the simulation succeeded
     pub success: bool,
     /// Error message if simulation failed
     pub error: Option<String>,
+    /// Structured error if simulation failed
+    pub bundle_error: Option<BundleError>,
     /// Balance changes for the taker from the fill transaction
     pub taker_balance_changes: Vec<BalanceChange>,
     /// Raw simulation logs (optional)
     pub logs: Option<Vec<String>>,
+    /// Priority fee of the mock mint, from its compute-unit price and the units it
+    /// consumed; `None` if it sets no price or the RPC reported no units
+    pub priority_fee_lamports: Option<u64>,
+}
+
+/// A bundle simulation's `simulateBundle` request and response with the parsed result
+///
+/// Returned by `simulate_as_bundle_with_transcript` for debugging and record keeping.
+/// `response` can be replayed through `parse_simulate_bundle_response` later; that
+/// gives `result` back except for `priority_fee_lamports` (which needs the bundle) and
+/// any `max_logs` truncation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationTranscript {
+    /// The JSON-RPC request body sent to the RPC
+    pub request: serde_json::Value,
+    /// The JSON-RPC response body as received
+    pub response: serde_json::Value,
+    /// The result parsed from `response`
+    pub result: BundleSimulationResult,
+}
+
+impl BundleSimulationResult {
+    /// Create a failed result from a structured bundle error
+    pub fn failed(bundle_error: BundleError) -> Self {
+        Self {
+            success: false,
+            error: Some(bundle_error.to_string()),
+            bundle_error: Some(bundle_error),
+            taker_balance_changes: vec![],
+            logs: None,
+            priority_fee_lamports: None,
+        }
+    }
+
+    /// Program error found in the simulation logs, if any
+    ///
+    /// Useful when a fill fails and the cause (e.g. an Anchor `QuoteExpired`) is
+    /// buried in the logs. Returns `None` if no logs were returned.
+    pub fn parsed_program_error(&self) -> Option<ProgramError> {
+        ProgramError::from_logs(self.logs.as_deref()?)
+    }
+
+    /// Check that the taker received at least the quoted GM amount
+    ///
+    /// Sums the taker's balance changes for `trade_info.gm_token_mint` and fails with
+    /// `UnderDelivered` if that is less than `gm_token_amount - tolerance`. The tolerance
+    /// covers e.g. a Token-2022 transfer fee; receiving more than quoted is fine.
+    ///
+    /// # Arguments
+    ///
+    /// * `trade_info` - The BUY trade that was simulated
+    /// * `tolerance` - Shortfall in base units to accept
+    pub fn assert_taker_received(
+        &self,
+        trade_info: &GmTradeInfo,
+        tolerance: u64,
+    ) -> Result<(), GmSimulatorError> {
+        let received: i128 = self
+            .taker_balance_changes
+            .iter()
+            .filter(|c| c.mint == trade_info.gm_token_mint)
+            .map(|c| c.change)
+            .sum();
+        let minimum = trade_info.gm_token_amount.0.saturating_sub(tolerance);
+
+        if received < i128::from(minimum) {
+            return Err(GmSimulatorError::UnderDelivered {
+                mint: trade_info.gm_token_mint,
+                quoted: trade_info.gm_token_amount.0,
+                received,
+            });
+        }
+        Ok(())
+    }
+
+    /// Copy of the result with the taker's addresses masked, for sharing diagnostics
+    ///
+    /// Each balance change's `owner` and `token_account` is replaced by a placeholder
+    /// pubkey numbered by first appearance, so the same address always maps to the
+    /// same placeholder. Occurrences of those addresses in the errors and logs are
+    /// replaced too. Mints, symbols and amounts are kept.
+    pub fn anonymized(&self) -> Self {
+        let mut masked: Vec<(Pubkey, Pubkey)> = Vec::new();
+        let mut placeholder = |address: Pubkey| -> Pubkey {
+            if let Some((_, mask)) = masked.iter().find(|(original, _)| *original == address) {
+                return *mask;
+            }
+            let mut bytes = [0u8; 32];
+            bytes[24..].copy_from_slice(&(masked.len() as u64 + 1).to_be_bytes());
+            let mask = Pubkey::new_from_array(bytes);
+            masked.push((address, mask));
+            mask
+        };
+
+        let taker_balance_changes: Vec<BalanceChange> = self
+            .taker_balance_changes
+            .iter()
+            .map(|change| BalanceChange {
+                owner: placeholder(change.owner),
+                token_account: placeholder(change.token_account),
+                ..change.clone()
+            })
+            .collect();
+
+        let mask = |text: &str| -> String {
+            masked
+                .iter()
+                .fold(text.to_string(), |text, (original, mask)| {
+                    text.replace(&original.to_string(), &mask.to_string())
+                })
+        };
+        let bundle_error = self.bundle_error.as_ref().map(|error| match error {
+            BundleError::BundleRejected(reason) => BundleError::BundleRejected(mask(reason)),
+            BundleError::BlockhashNotFound => BundleError::BlockhashNotFound,
+            BundleError::SimulationBankUnavailable(reason) => {
+                BundleError::SimulationBankUnavailable(mask(reason))
+            }
+            BundleError::TransactionFailed { index, error } => BundleError::TransactionFailed {
+                index: *index,
+                error: mask(error),
+            },
+        });
+
+        Self {
+            success: self.success,
+            error: self.error.as_deref().map(mask),
+            bundle_error,
+            taker_balance_changes,
+            logs: self
+                .logs
+                .as_ref()
+                .map(|logs| logs.iter().map(|line| mask(line)).collect()),
+            priority_fee_lamports: self.priority_fee_lamports,
+        }
+    }
+
+    /// Compare against another result, e.g. the same bundle simulated by a newer build
+    ///
+    /// Balance changes are matched by token account; accounts whose change is
+    /// identical in both results are omitted.
+    pub fn diff(&self, other: &Self) -> ResultDiff {
+        let success = (self.success != other.success).then_some((self.success, other.success));
+        let error = (self.error != other.error).then(|| (self.error.clone(), other.error.clone()));
+
+        let find = |changes: &[BalanceChange], account: &Pubkey| {
+            changes
+                .iter()
+                .find(|c| c.token_account == *account)
+                .map(|c| c.change)
+        };
+
+        let mut balance_changes = Vec::new();
+        for change in &self.taker_balance_changes {
+            let after = find(&other.taker_balance_changes, &change.token_account);
+            if after != Some(change.change) {
+                balance_changes.push(BalanceChangeDiff {
+                    token_account: change.token_account,
+                    mint: change.mint,
+                    before: Some(change.change),
+                    after,
+                });
+            }
+        }
+        for change in &other.taker_balance_changes {
+            if find(&self.taker_balance_changes, &change.token_account).is_none() {
+                balance_changes.push(BalanceChangeDiff {
+                    token_account: change.token_account,
+                    mint: change.mint,
+                    before: None,
+                    after: Some(change.change),
+                });
+            }
+        }
+
+        ResultDiff {
+            success,
+            error,
+            balance_changes,
+        }
+    }
+}
+
+/// Program error extracted from simulation logs
+///
+/// Fields are filled from whichever log lines are present: `AnchorError` lines give
+/// the code, name and message; `Program log: Error: ...` gives a message; and the
+/// runtime's `Program <id> failed: ...` line gives the program and, for custom
+/// errors, the code.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgramError {
+    /// The program that failed, if the runtime's failure line was logged
+    pub program_id: Option<Pubkey>,
+    /// Custom error code (e.g. `6001` for an Anchor error, `0x1` for SPL Token)
+    pub code: Option<u32>,
+    /// Error name (e.g. `QuoteExpired`), only known for Anchor errors
+    pub name: Option<String>,
+    /// Human-readable error message
+    pub message: Option<String>,
+}
+
+impl ProgramError {
+    /// Scan simulation logs for the first program error
+    pub fn from_logs(logs: &[String]) -> Option<Self> {
+        let mut error = Self::default();
+
+        for line in logs {
+            if let Some(anchor) = line.strip_prefix("Program log: AnchorError") {
+                if error.name.is_none() {
+                    error.name = anchor_field(anchor, "Error Code: ").map(str::to_string);
+                    error.code =
+                        anchor_field(anchor, "Error Number: ").and_then(|n| n.parse().ok());
+                    // The message is the last field and may itself contain ". "
+                    error.message = anchor.split_once("Error Message: ").map(|(_, message)| {
+                        message.strip_suffix('.').unwrap_or(message).to_string()
+                    });
+                }
+            } else if let Some(message) = line.strip_prefix("Program log: Error: ") {
+                error.message.get_or_insert_with(|| message.to_string());
+            } else if let Some((program, failure)) = line
+                .strip_prefix("Program ")
+                .and_then(|rest| rest.split_once(" failed: "))
+            {
+                if error.program_id.is_none() {
+                    error.program_id = program.parse().ok();
+                }
+                match failure.strip_prefix("custom program error: 0x") {
+                    Some(hex) => {
+                        if error.code.is_none() {
+                            error.code = u32::from_str_radix(hex, 16).ok();
+                        }
+                    }
+                    None => {
+                        error.message.get_or_insert_with(|| failure.to_string());
+                    }
+                }
+            }
+        }
+
+        (error != Self::default()).then_some(error)
+    }
+}
+
+/// Value of a `<label><value>. ` field in an `AnchorError` log line
+fn anchor_field<'a>(line: &'a str, label: &str) -> Option<&'a str> {
+    let (_, value) = line.split_once(label)?;
+    Some(value.split_once(". ").map_or(value, |(value, _)| value))
+}
+
+/// Differences between two bundle simulation results
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResultDiff {
+    /// `(before, after)` if the success flag changed
+    pub success: Option<(bool, bool)>,
+    /// `(before, after)` if the error message changed
+    pub error: Option<(Option<String>, Option<String>)>,
+    /// Token accounts whose balance change differs
+    pub balance_changes: Vec<BalanceChangeDiff>,
+}
+
+impl ResultDiff {
+    /// Whether the two results were equivalent
+    pub fn is_empty(&self) -> bool {
+        self.success.is_none() && self.error.is_none() && self.balance_changes.is_empty()
+    }
+}
+
+/// A token account whose simulated balance change differs between two results
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChangeDiff {
+    /// The token account address
+    pub token_account: Pubkey,
+    /// The token mint address
+    pub mint: Pubkey,
+    /// Change in the first result (`None` if the account was not reported)
+    pub before: Option<i128>,
+    /// Change in the second result (`None` if the account was not reported)
+    pub after: Option<i128>,
+}
+
+impl BalanceChangeDiff {
+    /// How much the balance change moved, treating a missing account as zero
+    pub fn delta(&self) -> i128 {
+        self.after.unwrap_or(0) - self.before.unwrap_or(0)
+    }
+}
+
+/// Detection counts and latencies for a monitoring scrape, see `render_metrics_text`
+///
+/// Unlike the `metrics` feature, which pushes to an installed recorder, this is a plain
+/// snapshot the caller fills with `record` and renders on demand.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DetectionMetrics {
+    /// GM BUYs detected
+    pub buy: u64,
+    /// Authorized fills selling a GM token
+    pub sell: u64,
+    /// Transactions that are not GM trades
+    pub non_gm: u64,
+    /// Fills from an unauthorized maker
+    pub unauthorized: u64,
+    /// Detections at or under each of `DETECTION_LATENCY_BUCKETS` (cumulative)
+    pub latency_buckets: [u64; DETECTION_LATENCY_BUCKETS.len()],
+    /// Number of detections timed
+    pub latency_count: u64,
+    /// Total detection time in seconds
+    pub latency_sum_seconds: f64,
+}
+
+impl DetectionMetrics {
+    /// Count one `check_gm_trade*` result and the time it took
+    ///
+    /// Outcomes are counted like the `metrics` feature's counters: errors other than an
+    /// unauthorized maker are malformed transactions and aren't counted, though their
+    /// latency is.
+    pub fn record(&mut self, result: &Result<GmCheckResult, GmSimulatorError>, latency: Duration) {
+        match result {
+            Ok(result) if result.use_gm_bundle_sim => self.buy += 1,
+            Ok(result) => match result.reason {
+                Some(NoBundleReason::SellDirection) => self.sell += 1,
+                Some(NoBundleReason::UnauthorizedMaker) => self.unauthorized += 1,
+                _ => self.non_gm += 1,
+            },
+            Err(GmSimulatorError::UnauthorizedMaker(_)) => self.unauthorized += 1,
+            Err(_) => {}
+        }
+
+        let seconds = latency.as_secs_f64();
+        for (bound, count) in DETECTION_LATENCY_BUCKETS
+            .iter()
+            .zip(&mut self.latency_buckets)
+        {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+        self.latency_count += 1;
+        self.latency_sum_seconds += seconds;
+    }
+}
+
+/// Differences between an external GM token list and the built-in `GM_TOKENS`
+///
+/// Entries are `(symbol, mint)` pairs, matched by symbol.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenListDiff {
+    /// Tokens in the external list but not the built-in one
+    pub added: Vec<(String, String)>,
+    /// Built-in tokens missing from the external list
+    pub removed: Vec<(String, String)>,
+    /// Symbols whose mint differs between the lists
+    pub changed: Vec<TokenMintChange>,
+}
+
+impl TokenListDiff {
+    /// Whether the external list matches the built-in one
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Result of `validate_against_idl`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdlValidationReport {
+    /// Instructions and accounts found in the IDL and checked (`mint_gm`,
+    /// `OracleSanityCheck` and/or `fill`)
+    pub checked: Vec<String>,
+    /// Differences from the crate's hardcoded layouts
+    pub mismatches: Vec<IdlMismatch>,
+}
+
+impl IdlValidationReport {
+    /// Whether every checked instruction matches the crate's layout
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// A difference between an IDL instruction and the layout the crate hardcodes
+///
+/// For the `OracleSanityCheck` account, `instruction` is the account's name and the
+/// argument variants describe its fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlMismatch {
+    /// The IDL lists a different discriminator
+    Discriminator {
+        instruction: String,
+        expected: [u8; 8],
+        actual: Vec<u8>,
+    },
+    /// The instruction takes a different number of accounts
+    AccountCount {
+        instruction: String,
+        expected: usize,
+        actual: usize,
+    },
+    /// A different account at `index`
+    Account {
+        instruction: String,
+        index: usize,
+        expected: String,
+        actual: String,
+    },
+    /// The instruction takes a different number of arguments
+    ArgCount {
+        instruction: String,
+        expected: usize,
+        actual: usize,
+    },
+    /// A different argument (as `name: type`) at `index`
+    Arg {
+        instruction: String,
+        index: usize,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// A GM token symbol whose mint differs between two token lists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMintChange {
+    /// The token symbol (e.g. `AAPLon`)
+    pub symbol: String,
+    /// Mint in the built-in `GM_TOKENS`
+    pub builtin_mint: String,
+    /// Mint in the external list
+    pub external_mint: String,
+}
+
+/// Decoded `oracle_sanity_check` account for a GM token
+///
+/// Fields follow the Anchor account layout after the 8-byte discriminator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OracleSanityCheck {
+    /// The GM token mint this check applies to
+    pub mint: Pubkey,
+    /// Last accepted oracle price
+    pub last_price: u64,
+    /// Maximum allowed price move between updates, in basis points
+    pub allowed_deviation_bps: u64,
+    /// Maximum age of the last update in seconds before the price is stale
+    pub max_time_delay: i64,
+    /// Unix timestamp of the last price update
+    pub last_updated: i64,
+    /// Slot at which the RPC read the account
+    pub context_slot: u64,
+}
+
+/// Bytes of the `oracle_sanity_check` account decoded into `OracleSanityCheck`
+const ORACLE_SANITY_CHECK_LEN: usize = 72;
+
+impl OracleSanityCheck {
+    /// Decode raw `oracle_sanity_check` account data
+    ///
+    /// The layout is the 8-byte Anchor discriminator (`ORACLE_SANITY_CHECK_DISCRIMINATOR`,
+    /// checked), then `mint`, `last_price`, `allowed_deviation_bps`, `max_time_delay` and
+    /// `last_updated`. The account records
+    /// when the price was last updated as a unix timestamp, not a slot, so staleness is
+    /// judged with `is_stale`. `context_slot` is left at 0 since raw data carries no
+    /// RPC context; use `rpc::fetch_oracle_sanity_check` to read and decode it in one call.
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, GmSimulatorError> {
+        if data.len() < ORACLE_SANITY_CHECK_LEN {
+            return Err(GmSimulatorError::InstructionParseError(format!(
+                "Oracle sanity check data too short: {} bytes",
+                data.len()
+            )));
+        }
+        if data[..8] != crate::mint_instruction::ORACLE_SANITY_CHECK_DISCRIMINATOR {
+            return Err(GmSimulatorError::InstructionParseError(format!(
+                "Not an oracle sanity check account: discriminator {:02x?}",
+                &data[..8]
+            )));
+        }
+        let field = |offset: usize| -> [u8; 8] { data[offset..offset + 8].try_into().unwrap() };
+
+        Ok(Self {
+            mint: Pubkey::try_from(&data[8..40]).unwrap(),
+            last_price: u64::from_le_bytes(field(40)),
+            allowed_deviation_bps: u64::from_le_bytes(field(48)),
+            max_time_delay: i64::from_le_bytes(field(56)),
+            last_updated: i64::from_le_bytes(field(64)),
+            context_slot: 0,
+        })
+    }
+
+    /// Whether the last update is older than `max_time_delay` at `now` (unix seconds)
+    pub fn is_stale(&self, now: i64) -> bool {
+        now.saturating_sub(self.last_updated) > self.max_time_delay
+    }
+}
+
+/// Account state injected into the simulation in place of on-chain state.
+///
+/// Serialized into the `accountOverrides` map of the `simulateBundle` config as
+/// `{ "lamports": u64, "data": [<base64>, "base64"], "owner": <pubkey> }`. Only these
+/// three fields are supported; the account is never marked executable and its rent
+/// epoch is left to the RPC. Overrides are only honored by RPCs that support them.
+#[derive(Debug, Clone)]
+pub struct AccountOverride {
+    /// Lamport balance of the account
+    pub lamports: u64,
+    /// Raw account data (sent base64-encoded)
+    pub data: Vec<u8>,
+    /// Program that owns the account
+    pub owner: Pubkey,
+}
+
+/// Accounts to return for one transaction of a Jito `simulateBundle` request
+///
+/// The `preExecutionAccountsConfigs` and `postExecutionAccountsConfigs` arrays take one
+/// entry per transaction, so they are built from a `Vec<Option<AccountsConfig>>` where
+/// `None` serializes to `null`. Serialized as `{ "addresses": [<base58>, ...] }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountsConfig {
+    /// Accounts to return, in order
+    #[serde(with = "base58_pubkeys")]
+    pub addresses: Vec<Pubkey>,
+}
+
+/// Serde for a list of pubkeys as base58 strings, the format the RPC expects
+mod base58_pubkeys {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(keys: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(keys.iter().map(|key| key.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Pubkey>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|key| Pubkey::from_str(key).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+/// Options for a Jito `simulateBundle` request
+///
+/// `SimulateOptions::default()` matches the historical request: the latest processed
+/// bank, blockhash replacement and signature verification skipped, a 30 second HTTP
+/// timeout and no retries. Adjust it with the `with_*` builder methods:
+///
+/// ```ignore
+/// let options = SimulateOptions::default()
+///     .with_timeout(Duration::from_secs(5))
+///     .with_commitment(CommitmentLevel::Confirmed);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SimulateOptions {
+    /// Account states to inject before the bundle executes
+    pub account_overrides: Vec<(Pubkey, AccountOverride)>,
+    /// Maximum number of fill transaction logs to keep (`None` keeps all)
+    pub max_logs: Option<usize>,
+    /// Keep logs in the result; when `false`, `logs` is `None`
+    ///
+    /// `simulateBundle` has no option to leave logs out of the response, so this only
+    /// trims the result, e.g. for high-volume checks that only need balance changes.
+    pub fetch_logs: bool,
+    /// Slot whose bank to simulate against (`None` uses the latest `commitment` bank)
+    ///
+    /// Set this to reproduce a past simulation deterministically; the RPC must still
+    /// have the slot's bank available.
+    pub slot: Option<u64>,
+    /// Skip signature verification (the default, since mock mints are unsigned)
+    ///
+    /// Turn this off to have the RPC verify signatures of fully-signed bundles.
+    pub skip_sig_verify: bool,
+    /// Commitment of the bank to simulate against when no `slot` is set
+    pub commitment: CommitmentLevel,
+    /// Have the RPC replace each transaction's recent blockhash with a current one
+    pub replace_recent_blockhash: bool,
+    /// HTTP timeout for each attempt
+    pub timeout: Duration,
+    /// How many times to resend the request after an HTTP failure
+    pub max_retries: u32,
+    /// Extra HTTP headers (e.g. an API key) sent with the request
+    pub headers: Vec<(String, String)>,
+}
+
+impl Default for SimulateOptions {
+    fn default() -> Self {
+        Self {
+            account_overrides: vec![],
+            max_logs: None,
+            fetch_logs: true,
+            slot: None,
+            skip_sig_verify: true,
+            commitment: CommitmentLevel::Processed,
+            replace_recent_blockhash: true,
+            timeout: Duration::from_secs(30),
+            max_retries: 0,
+            headers: vec![],
+        }
+    }
+}
+
+impl SimulateOptions {
+    /// Inject `account` at `address` before the bundle executes
+    pub fn with_account_override(mut self, address: Pubkey, account: AccountOverride) -> Self {
+        self.account_overrides.push((address, account));
+        self
+    }
+
+    /// Keep at most `max_logs` fill transaction logs
+    pub fn with_max_logs(mut self, max_logs: usize) -> Self {
+        self.max_logs = Some(max_logs);
+        self
+    }
+
+    /// Set whether the result keeps logs
+    pub fn with_fetch_logs(mut self, fetch_logs: bool) -> Self {
+        self.fetch_logs = fetch_logs;
+        self
+    }
+
+    /// Simulate against the bank of `slot`
+    pub fn with_slot(mut self, slot: u64) -> Self {
+        self.slot = Some(slot);
+        self
+    }
+
+    /// Set whether the RPC skips signature verification
+    pub fn with_skip_sig_verify(mut self, skip_sig_verify: bool) -> Self {
+        self.skip_sig_verify = skip_sig_verify;
+        self
+    }
+
+    /// Simulate against the latest bank at `commitment`
+    pub fn with_commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Set whether the RPC replaces recent blockhashes
+    pub fn with_replace_recent_blockhash(mut self, replace_recent_blockhash: bool) -> Self {
+        self.replace_recent_blockhash = replace_recent_blockhash;
+        self
+    }
+
+    /// Give up on each HTTP attempt after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Resend the request up to `max_retries` times after an HTTP failure
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Send an extra HTTP header with the request
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// Options for building a mock mint transaction
+#[derive(Debug, Clone, Default)]
+pub struct MockMintOptions {
+    /// Leave out the maker's quote mint ATA create (the solver's USDC or USDT ATA already
+    /// exists)
+    pub skip_maker_quote_ata: bool,
+    /// SPL Memo to put first in the transaction, e.g. a request id for correlating the
+    /// bundle in RPC provider logs (no memo by default)
+    pub memo: Option<String>,
+    /// Compute unit limit to request with a `SetComputeUnitLimit` instruction, e.g. from
+    /// `recommended_compute_limit` (none by default, so the runtime default applies)
+    pub compute_unit_limit: Option<u32>,
+}
+
+/// Cluster-specific addresses used for detection and mock mints
+///
+/// Defaults to mainnet. Install a different one at startup with
+/// `constants::set_global_config`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GmConfig {
+    /// The Ondo GM program
+    pub ondo_gm_program_id: Pubkey,
+    /// The Jupiter Order Engine program
+    pub jupiter_order_engine_program_id: Pubkey,
+    /// The admin minter that signs mock mints
+    pub admin_minter: Pubkey,
+    /// The USDC mint
+    pub usdc_mint: Pubkey,
+    /// The USDT mint, accepted like USDC as a GM trade's stable leg (`None` to reject USDT)
+    pub usdt_mint: Option<Pubkey>,
+    /// The USDON mint, accepted like USDC as a GM trade's stable leg (unset by default)
+    pub usdon_mint: Option<Pubkey>,
+    /// Solvers allowed to fill GM trades
+    pub authorized_solvers: Vec<Pubkey>,
+    /// Whether a fill from an unknown maker is `Err(UnauthorizedMaker)` (the default)
+    /// rather than an ordinary non-GM trade
+    pub unauthorized_maker_is_error: bool,
+    /// Venues checked for fills after Jupiter Order Engine (none by default)
+    pub extra_fill_venues: Vec<FillVenue>,
+    /// Whether Jupiter `fill_v2` instructions are detected (off by default, since
+    /// `parser::FILL_V2`'s argument layout is assumed rather than read from the IDL)
+    pub accept_fill_v2: bool,
+    /// Messages with more instructions are rejected with `Err(TooManyInstructions)`
+    /// before scanning for a fill (`DEFAULT_MAX_INSTRUCTIONS` by default)
+    pub max_instructions: usize,
+}
+
+impl GmConfig {
+    /// The mainnet addresses from `constants`
+    pub fn mainnet() -> Self {
+        use crate::constants::{
+            ADMIN_MINTER, AUTHORIZED_SOLVERS, DEFAULT_MAX_INSTRUCTIONS,
+            JUPITER_ORDER_ENGINE_PROGRAM_ID, ONDO_GM_PROGRAM_ID, USDC_MINT, USDT_MINT,
+        };
+        use std::str::FromStr;
+
+        let parse = |s: &str| Pubkey::from_str(s).expect("Invalid mainnet address");
+        Self {
+            ondo_gm_program_id: parse(ONDO_GM_PROGRAM_ID),
+            jupiter_order_engine_program_id: parse(JUPITER_ORDER_ENGINE_PROGRAM_ID),
+            admin_minter: parse(ADMIN_MINTER),
+            usdc_mint: parse(USDC_MINT),
+            usdt_mint: Some(parse(USDT_MINT)),
+            usdon_mint: None,
+            authorized_solvers: AUTHORIZED_SOLVERS.iter().map(|s| parse(s)).collect(),
+            unauthorized_maker_is_error: true,
+            extra_fill_venues: vec![],
+            accept_fill_v2: false,
+            max_instructions: DEFAULT_MAX_INSTRUCTIONS,
+        }
+    }
+}
+
+impl Default for GmConfig {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_trade_info;
+
+    fn change(token_account: Pubkey, mint: Pubkey, change: i128) -> BalanceChange {
+        BalanceChange {
+            mint,
+            symbol: None,
+            owner: Pubkey::default(),
+            token_account,
+            pre_balance: 0,
+            pre_balance_known: true,
+            post_balance: 0,
+            change,
+            decimals: 6,
+        }
+    }
+
+    #[test]
+    fn test_bundle_simulation_result_diff() {
+        let usdc_ata = Pubkey::new_unique();
+        let gm_ata = Pubkey::new_unique();
+        let extra_ata = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let gm = Pubkey::new_unique();
+
+        let before = BundleSimulationResult {
+            success: true,
+            error: None,
+            bundle_error: None,
+            taker_balance_changes: vec![
+                change(usdc_ata, usdc, -200_000_000),
+                change(gm_ata, gm, 1_500_000_000),
+            ],
+            logs: None,
+            priority_fee_lamports: None,
+        };
+        assert!(before.diff(&before.clone()).is_empty());
+
+        let mut after = before.clone();
+        after.taker_balance_changes[1].change = 1_400_000_000;
+        after.taker_balance_changes.push(change(extra_ata, usdc, 5));
+        let diff = before.diff(&after);
+        assert_eq!(diff.success, None);
+        assert_eq!(diff.error, None);
+        assert_eq!(diff.balance_changes.len(), 2);
+        assert_eq!(diff.balance_changes[0].token_account, gm_ata);
+        assert_eq!(diff.balance_changes[0].delta(), -100_000_000);
+        assert_eq!(diff.balance_changes[1].before, None);
+        assert_eq!(diff.balance_changes[1].delta(), 5);
+
+        let failed = BundleSimulationResult::failed(BundleError::BlockhashNotFound);
+        let diff = before.diff(&failed);
+        assert_eq!(diff.success, Some((true, false)));
+        assert_eq!(
+            diff.error,
+            Some((None, Some("Blockhash not found".to_string())))
+        );
+        assert_eq!(diff.balance_changes.len(), 2);
+        assert!(diff.balance_changes.iter().all(|d| d.after.is_none()));
+    }
+
+    #[test]
+    fn test_bundle_simulation_result_anonymized() {
+        let taker = Pubkey::new_unique();
+        let usdc_ata = Pubkey::new_unique();
+        let gm_ata = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let gm = Pubkey::new_unique();
+
+        let mut usdc_change = change(usdc_ata, usdc, -200_000_000);
+        usdc_change.owner = taker;
+        usdc_change.symbol = Some("USDC".to_string());
+        let mut gm_change = change(gm_ata, gm, 1_500_000_000);
+        gm_change.owner = taker;
+        gm_change.post_balance = 1_500_000_000;
+        let error = BundleError::TransactionFailed {
+            index: 1,
+            error: format!("insufficient funds in {}", usdc_ata),
+        };
+        let result = BundleSimulationResult {
+            success: false,
+            error: Some(error.to_string()),
+            bundle_error: Some(error),
+            taker_balance_changes: vec![usdc_change, gm_change],
+            logs: Some(vec![format!("Program log: owner {}", taker)]),
+            priority_fee_lamports: None,
+        };
+
+        let anonymized = result.anonymized();
+        let changes = &anonymized.taker_balance_changes;
+
+        // Amounts, mints and symbols are preserved
+        for (masked, original) in changes.iter().zip(&result.taker_balance_changes) {
+            assert_eq!(masked.mint, original.mint);
+            assert_eq!(masked.symbol, original.symbol);
+            assert_eq!(masked.change, original.change);
+            assert_eq!(masked.post_balance, original.post_balance);
+        }
+
+        // Addresses are masked deterministically, the taker once for both changes
+        assert_eq!(changes[0].owner, changes[1].owner);
+        assert_ne!(changes[0].token_account, changes[1].token_account);
+        let text = format!("{:?}", anonymized);
+        for address in [taker, usdc_ata, gm_ata] {
+            assert!(!text.contains(&address.to_string()));
+        }
+        assert_eq!(
+            anonymized.logs,
+            Some(vec![format!("Program log: owner {}", changes[0].owner)])
+        );
+        assert!(anonymized
+            .error
+            .unwrap()
+            .ends_with(&changes[0].token_account.to_string()));
+        assert_eq!(result.anonymized(), result.anonymized());
+    }
+
+    #[test]
+    fn test_raw_amount_ui_conversion() {
+        let amount = RawAmount::from(1_500_000_000);
+        assert_eq!(amount.to_ui(9), 1.5);
+        assert_eq!(amount.to_ui(0), 1_500_000_000.0);
+        assert_eq!(RawAmount::from_ui(1.5, 9), amount);
+        assert_eq!(RawAmount::from_ui(200.0, 6), RawAmount(200_000_000));
+
+        // Rounds to the nearest base unit rather than truncating 0.29 * 1e6 = 289999.99..
+        assert_eq!(RawAmount::from_ui(0.29, 6), RawAmount(290_000));
+        assert_eq!(RawAmount::from_ui(-1.0, 6), RawAmount(0));
+        assert_eq!(RawAmount::from_ui(f64::NAN, 6), RawAmount(0));
+        assert_eq!(RawAmount::from_ui(1e30, 9), RawAmount(u64::MAX));
+
+        assert_eq!(u64::from(amount), 1_500_000_000);
+        assert_eq!(amount.to_string(), "1500000000");
+    }
+
+    #[test]
+    fn test_assert_taker_received() {
+        let info = sample_trade_info();
+        let gm = info.gm_token_mint;
+        let result = |gm_change: i128| BundleSimulationResult {
+            success: true,
+            error: None,
+            bundle_error: None,
+            taker_balance_changes: vec![
+                change(Pubkey::new_unique(), Pubkey::new_unique(), -200_000_000),
+                change(Pubkey::new_unique(), gm, gm_change),
+            ],
+            logs: None,
+            priority_fee_lamports: None,
+        };
+
+        // Exact delivery
+        assert_eq!(
+            result(1_500_000_000).assert_taker_received(&info, 0),
+            Ok(())
+        );
+
+        // Under delivery, e.g. a transfer fee, is only accepted within the tolerance
+        assert_eq!(
+            result(1_499_000_000).assert_taker_received(&info, 0),
+            Err(GmSimulatorError::UnderDelivered {
+                mint: gm,
+                quoted: 1_500_000_000,
+                received: 1_499_000_000,
+            })
+        );
+        assert_eq!(
+            result(1_499_000_000).assert_taker_received(&info, 1_000_000),
+            Ok(())
+        );
+
+        // Over delivery
+        assert_eq!(
+            result(1_600_000_000).assert_taker_received(&info, 0),
+            Ok(())
+        );
+
+        // No GM balance change at all
+        let mut missing = result(0);
+        missing.taker_balance_changes.pop();
+        assert!(matches!(
+            missing.assert_taker_received(&info, 0),
+            Err(GmSimulatorError::UnderDelivered { received: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_gm_check_result_summary_string() {
+        let info = GmTradeInfo {
+            gm_token_amount: 3_880_411_000.into(),
+            ..sample_trade_info()
+        };
+        let maker = info.maker;
+
+        let summary = GmCheckResult::gm_trade(info.clone()).summary_string();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], "GM trade: BUY (USDC -> AAPLon)");
+        assert_eq!(lines[1], format!("  Maker: {} (authorized)", maker));
+        assert_eq!(lines[2], format!("  Taker: {}", info.taker));
+        assert_eq!(
+            lines[3],
+            format!("  Token: AAPLon ({})", info.gm_token_mint)
+        );
+        assert_eq!(lines[4], "  Amount: 3.880411000 AAPLon (3880411000)");
+        assert_eq!(lines[5], "  Bundle simulation: REQUIRED");
+        assert_eq!(lines.len(), 6);
+
+        let mut result = GmCheckResult::gm_trade(info.clone());
+        result.nonidempotent_ata_create = true;
+        assert_eq!(
+            result.summary_string().lines().last(),
+            Some("  Warning: non-idempotent ATA create will fail if the ATA exists")
+        );
+
+        let sell = GmTradeInfo {
+            direction: TradeDirection::Sell,
+            ..info.clone()
+        };
+        let summary = GmCheckResult::gm_trade(sell).summary_string();
+        assert_eq!(
+            summary.lines().next(),
+            Some("GM trade: SELL (AAPLon -> USDC)")
+        );
+
+        let usdt = GmTradeInfo {
+            quote_mint: crate::constants::usdt_mint().unwrap(),
+            ..info.clone()
+        };
+        let summary = GmCheckResult::gm_trade(usdt).summary_string();
+        assert_eq!(
+            summary.lines().next(),
+            Some("GM trade: BUY (USDT -> AAPLon)")
+        );
+
+        assert_eq!(
+            GmCheckResult::not_gm_trade().summary_string(),
+            "No GM trade to simulate (SELL or non-GM)\n  Bundle simulation: NOT REQUIRED"
+        );
+        assert_eq!(
+            GmCheckResult::not_gm_trade_because(NoBundleReason::SellDirection).summary_string(),
+            "No GM trade to simulate (SELL, solver already holds the output token)\n  \
+             Bundle simulation: NOT REQUIRED"
+        );
+    }
+
+    #[test]
+    fn test_accounts_config_serde() {
+        let address = Pubkey::new_unique();
+        let configs = vec![
+            None,
+            Some(AccountsConfig {
+                addresses: vec![address],
+            }),
+        ];
+
+        let json = serde_json::to_value(&configs).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([null, { "addresses": [address.to_string()] }])
+        );
+        assert_eq!(
+            serde_json::from_value::<Vec<Option<AccountsConfig>>>(json).unwrap(),
+            configs
+        );
+
+        let invalid = serde_json::json!({ "addresses": ["not a pubkey"] });
+        assert!(serde_json::from_value::<AccountsConfig>(invalid).is_err());
+    }
+
+    #[test]
+    fn test_change_decimal_string() {
+        let mut gm = change(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            123_456_789_123_456,
+        );
+        gm.decimals = 9;
+        assert_eq!(gm.change_decimal_string(), "123456.789123456");
+
+        gm.change = -123_456_789_123_456;
+        assert_eq!(gm.change_decimal_string(), "-123456.789123456");
+
+        let usdc = change(Pubkey::new_unique(), Pubkey::new_unique(), -5);
+        assert_eq!(usdc.change_decimal_string(), "-0.000005");
+
+        gm.decimals = 0;
+        assert_eq!(gm.change_decimal_string(), "-123456789123456");
+    }
+
+    #[test]
+    fn test_gm_trade_info_to_receipt() {
+        let info = sample_trade_info();
+
+        let receipt = info.to_receipt();
+
+        assert_eq!(receipt["maker"], info.maker.to_string());
+        assert_eq!(receipt["taker"], info.taker.to_string());
+        assert_eq!(receipt["mint"], info.gm_token_mint.to_string());
+        assert_eq!(receipt["symbol"], "AAPLon");
+        assert_eq!(receipt["ui_amount"], "1.500000000");
+        assert_eq!(receipt["raw_amount"], 1_500_000_000u64);
+        assert_eq!(receipt["direction"], "BUY");
+        assert_eq!(receipt["expire_at"], "2024-01-01T00:00:00Z");
+
+        assert_eq!(iso8601_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(iso8601_utc(951_782_400 + 3_661), "2000-02-29T01:01:01Z");
+        assert_eq!(iso8601_utc(-1), "1969-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn test_gm_trade_info_validate() {
+        let info = sample_trade_info();
+        let maker = info.maker;
+        assert_eq!(info.validate(), Ok(()));
+
+        let unauthorized = Pubkey::new_unique();
+        assert_eq!(
+            GmTradeInfo {
+                maker: unauthorized,
+                ..info.clone()
+            }
+            .validate(),
+            Err(GmSimulatorError::UnauthorizedMaker(unauthorized))
+        );
+        assert!(matches!(
+            GmTradeInfo {
+                gm_token_mint: Pubkey::new_unique(),
+                ..info.clone()
+            }
+            .validate(),
+            Err(GmSimulatorError::InvalidTradeInfo(msg)) if msg.contains("not a GM token")
+        ));
+        assert!(matches!(
+            GmTradeInfo {
+                gm_token_symbol: "TSLAon".to_string(),
+                ..info.clone()
+            }
+            .validate(),
+            Err(GmSimulatorError::InvalidTradeInfo(msg)) if msg.contains("AAPLon")
+        ));
+        assert_eq!(
+            GmTradeInfo {
+                taker: maker,
+                ..info.clone()
+            }
+            .validate(),
+            Err(GmSimulatorError::TakerMakerSame(maker))
+        );
+        let minter = crate::constants::admin_minter();
+        assert_eq!(
+            GmTradeInfo {
+                taker: minter,
+                ..info.clone()
+            }
+            .validate(),
+            Err(GmSimulatorError::AdminMinterInTrade(minter))
+        );
+        assert_eq!(
+            GmTradeInfo {
+                gm_token_amount: 0.into(),
+                ..info
+            }
+            .validate(),
+            Err(GmSimulatorError::ZeroAmount)
+        );
+    }
+
+    #[test]
+    fn test_gm_trade_info_to_jsonl_line() {
+        let info = sample_trade_info();
+
+        let line = info.to_jsonl_line();
+
+        assert!(!line.contains('\n'));
+        assert!(line.starts_with(r#"{"direction":"BUY","expire_at":"2024-01-01T00:00:00Z","#));
+        let record: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(record["schema_version"], 1);
+        assert_eq!(record["raw_amount"], 1_500_000_000u64);
+        assert_eq!(record["mint"], info.gm_token_mint.to_string());
+    }
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_oracle_sanity_check_try_from_account_data() {
+        use crate::mint_instruction::ORACLE_SANITY_CHECK_DISCRIMINATOR;
+
+        assert_eq!(
+            ORACLE_SANITY_CHECK_DISCRIMINATOR,
+            crate::account_discriminator("OracleSanityCheck")
+        );
+
+        let mint = Pubkey::new_unique();
+        let mut data = ORACLE_SANITY_CHECK_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(mint.as_ref());
+        data.extend_from_slice(&187_500_000u64.to_le_bytes());
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.extend_from_slice(&300i64.to_le_bytes());
+        data.extend_from_slice(&1_704_067_200i64.to_le_bytes());
+
+        let sanity = OracleSanityCheck::try_from_account_data(&data).unwrap();
+        assert_eq!(sanity.mint, mint);
+        assert_eq!(sanity.last_price, 187_500_000);
+        assert_eq!(sanity.allowed_deviation_bps, 500);
+        assert_eq!(sanity.max_time_delay, 300);
+        assert_eq!(sanity.last_updated, 1_704_067_200);
+        assert_eq!(sanity.context_slot, 0);
+
+        assert!(matches!(
+            OracleSanityCheck::try_from_account_data(&data[..71]),
+            Err(GmSimulatorError::InstructionParseError(_))
+        ));
+
+        // Another account of the same size
+        data[..8].copy_from_slice(&[0; 8]);
+        assert!(matches!(
+            OracleSanityCheck::try_from_account_data(&data),
+            Err(GmSimulatorError::InstructionParseError(msg)) if msg.contains("discriminator")
+        ));
+    }
+
+    #[test]
+    fn test_parsed_program_error_anchor() {
+        let result = BundleSimulationResult {
+            logs: Some(logs(&[
+                "Program 61DFfeTKM7trxYcPQCM78bJ794ddZprZpAwAnLiwTpYH invoke [1]",
+                "Program log: Instruction: Fill",
+                "Program log: AnchorError thrown in programs/order-engine/src/lib.rs:112. \
+                 Error Code: QuoteExpired. Error Number: 6001. Error Message: Quote has expired.",
+                "Program 61DFfeTKM7trxYcPQCM78bJ794ddZprZpAwAnLiwTpYH consumed 9120 of 200000 \
+                 compute units",
+                "Program 61DFfeTKM7trxYcPQCM78bJ794ddZprZpAwAnLiwTpYH failed: custom program \
+                 error: 0x1771",
+            ])),
+            ..BundleSimulationResult::failed(BundleError::BlockhashNotFound)
+        };
+
+        assert_eq!(
+            result.parsed_program_error(),
+            Some(ProgramError {
+                program_id: Some(
+                    "61DFfeTKM7trxYcPQCM78bJ794ddZprZpAwAnLiwTpYH"
+                        .parse()
+                        .unwrap()
+                ),
+                code: Some(6001),
+                name: Some("QuoteExpired".to_string()),
+                message: Some("Quote has expired".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parsed_program_error_spl_token() {
+        let result = BundleSimulationResult {
+            logs: Some(logs(&[
+                "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]",
+                "Program log: Instruction: TransferChecked",
+                "Program log: Error: insufficient funds",
+                "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA consumed 4645 of 180000 \
+                 compute units",
+                "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA failed: custom program \
+                 error: 0x1",
+            ])),
+            ..BundleSimulationResult::failed(BundleError::BlockhashNotFound)
+        };
+
+        let error = result.parsed_program_error().unwrap();
+        assert_eq!(
+            error.program_id,
+            Some(crate::constants::spl_token_program_id())
+        );
+        assert_eq!(error.code, Some(1));
+        assert_eq!(error.name, None);
+        assert_eq!(error.message.as_deref(), Some("insufficient funds"));
+    }
+
+    #[test]
+    fn test_parsed_program_error_none() {
+        let mut result = BundleSimulationResult::failed(BundleError::BlockhashNotFound);
+        assert_eq!(result.parsed_program_error(), None);
+
+        result.logs = Some(logs(&[
+            "Program 11111111111111111111111111111111 invoke [1]",
+            "Program 11111111111111111111111111111111 success",
+        ]));
+        assert_eq!(result.parsed_program_error(), None);
+    }
 }